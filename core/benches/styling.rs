@@ -0,0 +1,43 @@
+//! Styling throughput, `cargo bench -p cli-styler`
+
+use std::hint::black_box;
+
+use cli_styler::style::{Color, CompiledStyle, Stylable, Style};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+/// A typical log line
+const LINE: &str = "request handled in 12ms status=200 path=/api/v1/items";
+
+/// `Style::style` collects the SGR parameters on every call, `CompiledStyle` reuses its prefix
+fn style(c: &mut Criterion) {
+    let style = Style::new().fg(Color::Cyan).fg_brighten().bold();
+    let compiled = style.compile();
+
+    // Benchmarks shouldn't depend on the environment's NO_COLOR
+    cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+
+    let mut group = c.benchmark_group("style");
+    group.bench_function("Style", |b| b.iter(|| black_box(&style).style(LINE)));
+    group.bench_function("CompiledStyle", |b| {
+        b.iter(|| black_box(&compiled).style(LINE))
+    });
+    group.finish();
+}
+
+/// Streaming into a reused buffer, as a logger would
+fn write_styled(c: &mut Criterion) {
+    let compiled = CompiledStyle::new_from_cli_spec("f #ff8800 m b").unwrap();
+    let mut buf = Vec::with_capacity(256);
+
+    cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+
+    c.bench_function("CompiledStyle::write_styled", |b| {
+        b.iter(|| {
+            buf.clear();
+            black_box(&compiled).write_styled(&mut buf, LINE).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, style, write_styled);
+criterion_main!(benches);
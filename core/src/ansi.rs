@@ -0,0 +1,363 @@
+//! Utilities for text which already contains ANSI escape sequences
+
+use std::io;
+
+use cli_styler_spec::__private;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{spans::StyledString, style::Style};
+
+/// A piece of ANSI-formatted text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    /// Printable text
+    Text(&'a str),
+    /// Control Sequence Introducer: `ESC [ <params> <final>`
+    Csi {
+        /// Parameter and intermediate bytes
+        params: &'a str,
+        /// Final byte, `m` for SGR
+        action: char,
+    },
+    /// Any other escape sequence (OSC, DCS, 2-byte escapes, ...)
+    Escape(&'a str),
+}
+
+/// Iterator splitting a string into [`Segment`]s
+pub(crate) struct Segments<'a> {
+    /// Remaining input
+    rest: &'a str,
+}
+
+/// Split `s` into printable text and escape sequences
+pub(crate) fn segments(s: &str) -> Segments<'_> {
+    Segments { rest: s }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let bytes = self.rest.as_bytes();
+
+        if bytes[0] != 0x1b {
+            let end = self.rest.find('\x1b').unwrap_or(self.rest.len());
+            let (text, rest) = self.rest.split_at(end);
+            self.rest = rest;
+            return Some(Segment::Text(text));
+        }
+
+        let len = match bytes.get(1) {
+            // CSI: parameters and intermediates followed by a final byte
+            Some(b'[') => match bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b)) {
+                Some(i) => {
+                    let (seq, rest) = self.rest.split_at(i + 3);
+                    self.rest = rest;
+                    return Some(Segment::Csi {
+                        params: &seq[2..i + 2],
+                        action: bytes[i + 2] as char,
+                    });
+                }
+                // Unterminated sequence, swallow the rest
+                None => bytes.len(),
+            },
+            // OSC, DCS, SOS, PM, APC: terminated by BEL or ST (`ESC \`)
+            Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+                let mut i = 2;
+                loop {
+                    match bytes.get(i) {
+                        None => break bytes.len(),
+                        Some(0x07) => break i + 1,
+                        Some(0x1b) if bytes.get(i + 1) == Some(&b'\\') => break i + 2,
+                        Some(_) => i += 1,
+                    }
+                }
+            }
+            // Two byte escapes, a lone trailing ESC is dropped
+            Some(c) if c.is_ascii() => 2,
+            Some(_) | None => 1,
+        };
+
+        let (seq, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(Segment::Escape(seq))
+    }
+}
+
+/// Get the number of terminal columns `s` occupies
+///
+/// Escape sequences are ignored and Unicode width rules are applied, so wide
+/// characters (CJK, emoji) count as two columns.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{ansi::display_width, prelude::*};
+///
+/// assert_eq!(display_width(&Style::new().bold().style("hello")), 5);
+/// assert_eq!(display_width("日本"), 4);
+/// ```
+pub fn display_width(s: &str) -> usize {
+    segments(s)
+        .map(|seg| match seg {
+            Segment::Text(text) => text.width(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Interpret the SGR sequences of `s`, returning its text as `(text, Style)` spans
+///
+/// Only SGR (`ESC [ ... m`) sequences affect the style, every other escape sequence is
+/// dropped. Adjacent text sharing a style is merged and empty spans are skipped.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{ansi, prelude::*};
+///
+/// let spans = ansi::parse("\u{1b}[1;31merror\u{1b}[0m: oops");
+///
+/// assert_eq!(spans, [
+///     ("error".to_string(), Style::new().fg(Color::Red).bold()),
+///     (": oops".to_string(), Style::new()),
+/// ]);
+/// ```
+pub fn parse(s: &str) -> Vec<(String, Style)> {
+    let mut spans: Vec<(String, Style)> = Vec::new();
+    let mut style = Style::new();
+
+    for seg in segments(s) {
+        match seg {
+            Segment::Text(text) => match spans.last_mut() {
+                Some((last, last_style)) if *last_style == style => last.push_str(text),
+                _ => spans.push((text.to_string(), style)),
+            },
+            Segment::Csi {
+                params,
+                action: 'm',
+            } => __private::apply_sgr(&mut style, params),
+            _ => {}
+        }
+    }
+
+    spans
+}
+
+/// Word-wrap styled text to `width` columns, see [`wrap_lines`]
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{ansi, prelude::*};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let text = Style::new().bg(Color::Blue).style("one two three");
+///
+/// assert_eq!(
+///     ansi::wrap(&text, 7),
+///     "\u{1b}[44mone two\u{1b}[0m\n\u{1b}[44mthree\u{1b}[0m"
+/// );
+/// ```
+pub fn wrap(text: &str, width: usize) -> String {
+    wrap_lines(text, width).join("\n")
+}
+
+/// Word-wrap styled text to `width` columns, returning the lines
+///
+/// Every line is closed with a reset and each continuation line re-emits the
+/// active style, so backgrounds and underlines survive the line breaks.
+/// Existing newlines are kept, and words wider than `width` are split.
+pub fn wrap_lines(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let chars = parse(text)
+        .into_iter()
+        .flat_map(|(text, style)| text.chars().map(move |ch| (ch, style)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+
+    for paragraph in chars.split(|&(ch, _)| ch == '\n') {
+        let mut line: Vec<(char, Style)> = Vec::new();
+        let mut line_width = 0;
+        let mut pending_space: Vec<(char, Style)> = Vec::new();
+
+        for word in split_words(paragraph) {
+            if word[0].0.is_whitespace() {
+                // Only the paragraph's indentation can occur on an empty line
+                if line.is_empty() {
+                    line.extend_from_slice(word);
+                    line_width += chars_width(word);
+                } else {
+                    pending_space = word.to_vec();
+                }
+                continue;
+            }
+
+            let word_width = chars_width(word);
+            let space_width = chars_width(&pending_space);
+
+            if line_width > 0 && line_width + space_width + word_width > width {
+                lines.push(render(&line));
+                line.clear();
+                line_width = 0;
+            } else if line_width > 0 {
+                line.append(&mut pending_space);
+                line_width += space_width;
+            }
+            pending_space.clear();
+
+            for &(ch, style) in word {
+                let ch_width = ch.width().unwrap_or(0);
+
+                if line_width > 0 && line_width + ch_width > width {
+                    lines.push(render(&line));
+                    line.clear();
+                    line_width = 0;
+                }
+
+                line.push((ch, style));
+                line_width += ch_width;
+            }
+        }
+
+        lines.push(render(&line));
+    }
+
+    lines
+}
+
+/// Split styled chars into alternating runs of whitespace and non-whitespace
+fn split_words(chars: &[(char, Style)]) -> impl Iterator<Item = &[(char, Style)]> {
+    let mut rest = chars;
+
+    std::iter::from_fn(move || {
+        let first = rest.first()?.0.is_whitespace();
+        let end = rest
+            .iter()
+            .position(|(ch, _)| ch.is_whitespace() != first)
+            .unwrap_or(rest.len());
+
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some(word)
+    })
+}
+
+/// Number of columns taken by styled chars
+fn chars_width(chars: &[(char, Style)]) -> usize {
+    chars.iter().map(|(ch, _)| ch.width().unwrap_or(0)).sum()
+}
+
+/// Render styled chars, grouping runs which share a style
+fn render(chars: &[(char, Style)]) -> String {
+    chars
+        .iter()
+        .map(|&(ch, style)| (ch.to_string(), style))
+        .collect::<StyledString>()
+        .to_string()
+}
+
+/// Remove every escape sequence (CSI, OSC, ...) from `s`, keeping only printable text
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{ansi::strip_ansi, prelude::*};
+///
+/// assert_eq!(strip_ansi(&Style::new().fg(Color::Red).style("error")), "error");
+/// ```
+pub fn strip_ansi(s: &str) -> String {
+    segments(s)
+        .filter_map(|seg| match seg {
+            Segment::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
+}
+
+/// State of the [`StripWriter`] escape sequence scanner
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum StripState {
+    /// Printable text
+    #[default]
+    Ground,
+    /// Encountered `ESC`
+    Esc,
+    /// Inside `ESC [`, waiting for the final byte
+    Csi,
+    /// Inside a string sequence (OSC, DCS, ...), waiting for BEL or ST
+    Str,
+    /// Encountered `ESC` inside a string sequence, possibly starting ST
+    StrEsc,
+}
+
+/// An [`io::Write`] wrapper removing escape sequences from everything written through it
+///
+/// Sequences split across several `write` calls are handled, which makes it
+/// suitable for teeing colored output into log files.
+#[derive(Debug)]
+pub struct StripWriter<W: io::Write> {
+    /// Wrapped writer
+    inner: W,
+    /// Scanner state carried across writes
+    state: StripState,
+}
+
+impl<W: io::Write> StripWriter<W> {
+    /// Wrap a writer
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: StripState::Ground,
+        }
+    }
+
+    /// Get a reference to the wrapped writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the wrapped writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> io::Write for StripWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut out = Vec::with_capacity(buf.len());
+
+        for &b in buf {
+            self.state = match (self.state, b) {
+                (StripState::Ground, 0x1b) => StripState::Esc,
+                (StripState::Ground, _) => {
+                    out.push(b);
+                    StripState::Ground
+                }
+                (StripState::Esc, b'[') => StripState::Csi,
+                (StripState::Esc, b']' | b'P' | b'X' | b'^' | b'_') => StripState::Str,
+                (StripState::Esc, _) => StripState::Ground,
+                (StripState::Csi, 0x40..=0x7e) => StripState::Ground,
+                (StripState::Csi, _) => StripState::Csi,
+                (StripState::Str, 0x07) => StripState::Ground,
+                (StripState::Str, 0x1b) => StripState::StrEsc,
+                (StripState::Str, _) => StripState::Str,
+                (StripState::StrEsc, b'\\') => StripState::Ground,
+                (StripState::StrEsc, _) => StripState::Str,
+            };
+        }
+
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
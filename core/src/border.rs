@@ -0,0 +1,96 @@
+//! Border line styles shared by [`crate::table`] and [`crate::panel`]
+
+/// Line style used to draw borders
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Border {
+    /// No border lines at all
+    None,
+    /// Plain ASCII: `+`, `-`, `|`
+    Ascii,
+    /// Light box-drawing lines: `┌─┐`
+    #[default]
+    Light,
+    /// Light lines with rounded corners: `╭─╮`
+    Rounded,
+    /// Heavy box-drawing lines: `┏━┓`
+    Heavy,
+    /// Double box-drawing lines: `╔═╗`
+    Double,
+}
+
+/// The characters used to draw a [`Border`]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BoxChars {
+    /// Horizontal line
+    pub(crate) h: char,
+    /// Vertical line
+    pub(crate) v: char,
+    /// Top left, top junction, top right
+    pub(crate) top: [char; 3],
+    /// Left junction, cross, right junction
+    pub(crate) mid: [char; 3],
+    /// Bottom left, bottom junction, bottom right
+    pub(crate) bottom: [char; 3],
+}
+
+impl Border {
+    /// Get the characters for this border, `None` for [`Border::None`]
+    pub(crate) fn chars(self) -> Option<BoxChars> {
+        let chars = |h, v, top, mid, bottom| BoxChars {
+            h,
+            v,
+            top,
+            mid,
+            bottom,
+        };
+
+        match self {
+            Border::None => None,
+            Border::Ascii => Some(chars('-', '|', ['+'; 3], ['+'; 3], ['+'; 3])),
+            Border::Light => Some(chars(
+                '─',
+                '│',
+                ['┌', '┬', '┐'],
+                ['├', '┼', '┤'],
+                ['└', '┴', '┘'],
+            )),
+            Border::Rounded => Some(chars(
+                '─',
+                '│',
+                ['╭', '┬', '╮'],
+                ['├', '┼', '┤'],
+                ['╰', '┴', '╯'],
+            )),
+            Border::Heavy => Some(chars(
+                '━',
+                '┃',
+                ['┏', '┳', '┓'],
+                ['┣', '╋', '┫'],
+                ['┗', '┻', '┛'],
+            )),
+            Border::Double => Some(chars(
+                '═',
+                '║',
+                ['╔', '╦', '╗'],
+                ['╠', '╬', '╣'],
+                ['╚', '╩', '╝'],
+            )),
+        }
+    }
+}
+
+impl std::str::FromStr for Border {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Border::None),
+            "ascii" => Ok(Border::Ascii),
+            "light" | "unicode" => Ok(Border::Light),
+            "rounded" => Ok(Border::Rounded),
+            "heavy" => Ok(Border::Heavy),
+            "double" => Ok(Border::Double),
+            other => Err(format!("unknown border style: {other}")),
+        }
+    }
+}
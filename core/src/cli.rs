@@ -1,55 +1,399 @@
-use std::env;
+use std::{
+    env,
+    ffi::OsString,
+    io::{self, BufRead, Read, Write},
+};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use cli_styler_spec::__private::{Cli, parse_style};
 
 use crate::{
+    ansi::{StripWriter, strip_ansi},
+    border::Border,
     error::StylerError,
-    parser::{Cli, parse_style},
-    style::Stylable,
+    style::{ColorLevel, ParseOptions, Stylable, Style},
+    table::{Column, Table},
 };
 
+#[cfg(feature = "markup")]
+use std::collections::HashMap;
+
 #[cfg(feature = "markup")]
 use crate::markup::Markup;
 
+/// Text styled by `preview` unless told otherwise
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog";
+
+/// How arguments without a subcommand are taken, see [`with_default_subcommand`]
+const DEFAULT_SUBCOMMAND: &str = "Without a subcommand, `style TEXT SPEC...` runs \
+    `style style TEXT SPEC...` and `style --markup MARKUP` runs `style markup MARKUP`.";
+
+/// Build the command line interface of the `style` binary
+pub(crate) fn command() -> Command {
+    // Specs may be split over several words, `m -u` mustn't be taken for a flag
+    let spec = |required| {
+        Arg::new("spec")
+            .value_name("SPEC")
+            .help("Style spec, its words may be passed separately after any options")
+            .num_args(if required { 1.. } else { 0.. })
+            .required(required)
+            .allow_hyphen_values(true)
+            .trailing_var_arg(true)
+    };
+
+    // Lines of stdin are styled one at a time, so the output keeps up with the input
+    let filter = Arg::new("filter")
+        .long("filter")
+        .help("Render MARKUP for every line read from stdin, filling in {line}")
+        .action(ArgAction::SetTrue);
+
+    Command::new("style")
+        .about("Style text for the terminal")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .after_help(DEFAULT_SUBCOMMAND)
+        .after_long_help(format!(
+            "{DEFAULT_SUBCOMMAND}\n\n{}",
+            include_str!("spec/verbose.txt")
+        ))
+        .arg(
+            Arg::new("no-color")
+                .long("no-color")
+                .help("Print text without any escape sequences")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .subcommand(
+            Command::new("style")
+                .about("Style TEXT with a spec")
+                .arg(
+                    Arg::new("filter")
+                        .long("filter")
+                        .value_name("SPEC")
+                        .help("Style every line read from stdin with SPEC instead, writing them as they come")
+                        .num_args(0..)
+                        .allow_hyphen_values(true)
+                        .conflicts_with_all(["text", "spec"]),
+                )
+                .arg(
+                    Arg::new("text")
+                        .value_name("TEXT")
+                        .help("Text to style")
+                        .required_unless_present("filter"),
+                )
+                .arg(spec(false))
+                .after_help(include_str!("spec/concise.txt"))
+                .after_long_help(include_str!("spec/verbose.txt")),
+        )
+        .subcommand(
+            Command::new("markup")
+                .about("Render markup, e.g. \"<f r>error:</> disk full\"")
+                .arg(filter)
+                .arg(Arg::new("markup").value_name("MARKUP").required(true)),
+        )
+        .subcommand(
+            Command::new("strip")
+                .about("Remove escape sequences from TEXT, or from stdin if not given")
+                .arg(Arg::new("text").value_name("TEXT")),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Print a spec in another format")
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .help("Canonical spec, CSS declarations or the escape sequence")
+                        .value_parser(["spec", "css", "sgr"])
+                        .default_value("spec"),
+                )
+                .arg(spec(true))
+                .after_help(include_str!("spec/concise.txt"))
+                .after_long_help(include_str!("spec/verbose.txt")),
+        )
+        .subcommand(
+            Command::new("preview")
+                .about("Show a spec rendered for every color level")
+                .arg(
+                    Arg::new("text")
+                        .long("text")
+                        .value_name("TEXT")
+                        .help("Text to style")
+                        .default_value(SAMPLE),
+                )
+                .arg(spec(true))
+                .after_help(include_str!("spec/concise.txt"))
+                .after_long_help(include_str!("spec/verbose.txt")),
+        )
+        .subcommand(
+            Command::new("table")
+                .about(
+                    "Render CSV or TSV read from stdin as a table, the first row being the header",
+                )
+                .arg(
+                    Arg::new("tsv")
+                        .long("tsv")
+                        .help("Split fields at tabs, guessed from the first line otherwise")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("border")
+                        .long("border")
+                        .help("Border style")
+                        .value_parser(["none", "ascii", "light", "rounded", "heavy", "double"]),
+                )
+                .arg(
+                    Arg::new("header")
+                        .long("header")
+                        .value_name("SPEC")
+                        .help("Style spec of the header row"),
+                ),
+        )
+}
+
 /// CLI Handler
 pub fn run() -> Result<(), StylerError> {
-    let args = env::args().skip(1).collect::<Vec<_>>();
+    let matches = match command().try_get_matches_from(with_default_subcommand(env::args_os())) {
+        Ok(matches) => matches,
+        // `--help` isn't an error
+        Err(err) if !err.use_stderr() => return err.print().map_err(StylerError::Io),
+        Err(err) => return Err(StylerError::Usage(err)),
+    };
+
+    if matches.get_flag("no-color") {
+        crate::set_enabled(false);
+    }
+
+    match matches.subcommand() {
+        Some(("style", args)) if args.contains_id("filter") => {
+            let style = spec_of(args.get_many("filter").unwrap_or_default())?;
+            filter_lines(io::stdin().lock(), io::stdout().lock(), |line| {
+                style.style(line)
+            })?;
+        }
+        Some(("style", args)) => {
+            let text = args.get_one::<String>("text").expect("TEXT is required");
+            let style = spec_of(args.get_many("spec").unwrap_or_default())?;
+            println!("{}", style.style(text));
+        }
+        Some(("markup", args)) => run_markup(
+            args.get_one::<String>("markup")
+                .expect("MARKUP is required"),
+            args.get_flag("filter"),
+        )?,
+        Some(("strip", args)) => match args.get_one::<String>("text") {
+            Some(text) => println!("{}", strip_ansi(text)),
+            None => {
+                io::copy(
+                    &mut io::stdin().lock(),
+                    &mut StripWriter::new(io::stdout().lock()),
+                )
+                .map_err(StylerError::Io)?;
+            }
+        },
+        Some(("convert", args)) => {
+            let style = spec_of(args.get_many("spec").unwrap_or_default())?;
 
-    if args.is_empty() {
-        println!("{}", include_str!("spec/concise.txt"));
-        return Ok(());
+            match args.get_one::<String>("to").map(String::as_str) {
+                Some("css") => println!("{}", style.to_css()),
+                Some("sgr") => println!("{}", style.compile().prefix().escape_debug()),
+                _ => println!("{}", style.to_spec()),
+            }
+        }
+        Some(("preview", args)) => {
+            let style = spec_of(args.get_many("spec").unwrap_or_default())?;
+            let text = args.get_one::<String>("text").expect("TEXT has a default");
+
+            println!("{:>9}  {}", "spec", style.to_spec());
+            for (name, level) in [
+                ("truecolor", ColorLevel::TrueColor),
+                ("ansi256", ColorLevel::Ansi256),
+                ("ansi16", ColorLevel::Ansi16),
+                ("none", ColorLevel::None),
+            ] {
+                println!("{name:>9}  {}", style.adapt(level).style(text));
+            }
+        }
+        Some(("table", args)) => run_table(args)?,
+        _ => unreachable!("a subcommand is required"),
     }
 
-    if args.len() == 1 && args[0] == "--help" {
-        println!("{}", include_str!("spec/verbose.txt"));
-        return Ok(());
+    Ok(())
+}
+
+/// Rewrite the arguments of the `style` binary from before it had subcommands
+///
+/// `style TEXT SPEC...` becomes `style style TEXT SPEC...` and `--markup MARKUP`
+/// becomes `markup MARKUP`, so TEXT can't be the name of a subcommand.
+pub(crate) fn with_default_subcommand(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args = args.into_iter().collect::<Vec<_>>();
+    let command = command();
+
+    // `--no-color` is global, it may come before the subcommand
+    let Some(first) = (1..args.len()).find(|&i| args[i] != "--no-color") else {
+        return args;
+    };
+
+    if args[first] == "--markup" {
+        args[first] = OsString::from("markup");
+    } else if !["-h", "--help", "help"]
+        .iter()
+        .any(|&arg| args[first] == arg)
+        && command.find_subcommand(&args[first]).is_none()
+    {
+        args.insert(first, OsString::from("style"));
     }
 
-    if args.len() == 2 && args[0] == "--markup" {
-        #[cfg(feature = "markup")]
-        {
-            let mk = Markup::new_cli(&args[1]).map_err(StylerError::ParsingError)?;
-            println!("{}", mk.render());
+    args
+}
+
+/// Parse the words of a spec passed as separate arguments
+fn spec_of<'a>(words: impl Iterator<Item = &'a String>) -> Result<Style, StylerError> {
+    let spec = words.map(String::as_str).collect::<Vec<_>>().join(" ");
+    parse_style(spec, &Cli, &ParseOptions::new()).map_err(StylerError::ParsingError)
+}
+
+/// Write `f(line)` for every line of `input`, flushing each one as soon as it's read
+///
+/// Invalid UTF-8 is replaced by `U+FFFD` instead of ending the filter. A closed
+/// `out`, like `| head` exiting, ends the filter without an error.
+pub(crate) fn filter_lines(
+    mut input: impl BufRead,
+    mut out: impl Write,
+    mut f: impl FnMut(&str) -> String,
+) -> Result<(), StylerError> {
+    let mut write = || -> io::Result<()> {
+        let mut line = Vec::new();
+
+        while input.read_until(b'\n', &mut line)? > 0 {
+            let text = String::from_utf8_lossy(&line);
+            // Like `BufRead::lines`, without the `\n` or `\r\n`
+            let text = text
+                .strip_suffix('\n')
+                .map_or(&*text, |text| text.strip_suffix('\r').unwrap_or(text));
+
+            writeln!(out, "{}", f(text))?;
+            out.flush()?;
+            line.clear();
         }
 
-        #[cfg(not(feature = "markup"))]
-        eprintln!(
-            "Error: 'markup' feature not enabled, pass the \"--all-features\" flag during compilation."
+        Ok(())
+    };
+
+    match write() {
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        written => written.map_err(StylerError::Io),
+    }
+}
+
+/// Handler for the `markup` subcommand, rendering `markup` once or per line with `filter`
+#[cfg(feature = "markup")]
+fn run_markup(markup: &str, filter: bool) -> Result<(), StylerError> {
+    let mk = Markup::new_cli(markup).map_err(StylerError::ParsingError)?;
+
+    if filter {
+        return filter_lines(io::stdin().lock(), io::stdout().lock(), |line| {
+            mk.render_with(&HashMap::from([("line", line)]))
+        });
+    }
+
+    println!("{}", mk.render());
+    Ok(())
+}
+
+/// Handler for the `markup` subcommand
+#[cfg(not(feature = "markup"))]
+fn run_markup(_markup: &str, _filter: bool) -> Result<(), StylerError> {
+    Err(StylerError::InvalidArgument(
+        "'markup' feature not enabled, pass the \"--all-features\" flag during compilation"
+            .to_string(),
+    ))
+}
+
+/// Handler for the `table` subcommand: renders CSV/TSV read from stdin, the first row being the header
+fn run_table(args: &ArgMatches) -> Result<(), StylerError> {
+    let mut table = Table::new();
+
+    if let Some(name) = args.get_one::<String>("border") {
+        table = table.border(
+            name.parse::<Border>()
+                .map_err(StylerError::InvalidArgument)?,
+        );
+    }
+
+    if let Some(spec) = args.get_one::<String>("header") {
+        table = table.header_style(
+            parse_style(spec, &Cli, &ParseOptions::new()).map_err(StylerError::ParsingError)?,
         );
+    }
+
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(StylerError::Io)?;
+
+    // Guess the delimiter from the first line if not told explicitly
+    let tsv = args.get_flag("tsv") || input.lines().next().is_some_and(|line| line.contains('\t'));
+    let mut rows = parse_delimited(&input, if tsv { '\t' } else { ',' }).into_iter();
 
-        return Ok(());
+    for header in rows.next().unwrap_or_default() {
+        table = table.column(Column::new(header));
     }
 
-    let text = &args[0];
-    let style = parse_style(args[1..].join(" "), Cli).map_err(StylerError::ParsingError)?;
+    for row in rows {
+        table.push_row(row);
+    }
 
-    println!("{}", style.style(text));
+    println!("{table}");
 
     Ok(())
 }
 
+/// Split delimiter separated input into rows of fields
+///
+/// Fields may be quoted with `"`, quoted fields can contain the delimiter, newlines and `""` escapes.
+fn parse_delimited(input: &str, sep: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if quoted => field.push(c),
+            c if c == sep => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows
+}
+
 /// Silenced CLI Handler
 pub fn wrapped_run() {
     match run() {
         Ok(()) => {}
+        // Already formatted with the usage
+        Err(StylerError::Usage(err)) => {
+            let _ = err.print();
+        }
         Err(err) => eprintln!("{err}"),
     }
 }
@@ -0,0 +1,81 @@
+//! Syntax highlighting with [`syntect`], used by `<code lang>` markup sections
+//!
+//! The default syntaxes and themes of `syntect` are loaded on first use, which takes
+//! a moment. Only foreground colors, bold, italic and underline are taken from the
+//! theme, the background of the snippet is left to the terminal.
+
+use std::sync::OnceLock;
+
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::{spans::StyledString, style::Style};
+
+/// Name of the `syntect` theme [`highlight`] uses
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Get the default syntaxes, loaded once
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+/// Get the theme named [`DEFAULT_THEME`], loaded once
+fn default_theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+
+    THEME.get_or_init(|| {
+        ThemeSet::load_defaults()
+            .themes
+            .remove(DEFAULT_THEME)
+            .expect("the default theme is built into syntect")
+    })
+}
+
+/// Highlight `code` written in `lang` with the [default theme](DEFAULT_THEME)
+///
+/// `lang` is a language name or file extension (`rust`, `rs`, `toml`), matched
+/// case-insensitively. Unknown languages are kept as plain text.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::highlight;
+///
+/// let snippet = highlight::highlight("let x = 1;", "rust");
+///
+/// assert_eq!(snippet.plain_text(), "let x = 1;");
+/// assert!(snippet.len() > 1);
+/// ```
+pub fn highlight(code: &str, lang: &str) -> StyledString {
+    highlight_with(code, lang, default_theme())
+}
+
+/// Like [`highlight`], with a `syntect` theme of your own
+pub fn highlight_with(code: &str, lang: &str, theme: &Theme) -> StyledString {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut output = StyledString::new();
+
+    for line in LinesWithEndings::from(code) {
+        match highlighter.highlight_line(line, syntaxes) {
+            Ok(ranges) => output.extend(
+                ranges
+                    .into_iter()
+                    .map(|(style, text)| (text, Style::from(style))),
+            ),
+            // Only happens for broken syntax definitions, keep the line readable
+            Err(_) => output.push_plain(line),
+        }
+    }
+
+    output
+}
@@ -0,0 +1,7 @@
+//! Conversions from [`Markup`](crate::markup::Markup) to the text types of other crates
+//!
+//! The conversions of styles live next to [`Style`](crate::style::Style), behind the same
+//! features.
+
+#[cfg(feature = "ratatui")]
+mod ratatui;
@@ -0,0 +1,43 @@
+//! [`ratatui`] conversions of markup, see `cli_styler_spec` for the ones of styles
+
+use ::ratatui::{
+    style as rt,
+    text::{Line, Span, Text},
+};
+
+use crate::{markup::Markup, style::ColorLevel};
+
+/// Nested tags become spans with the style of the innermost tag
+impl From<&Markup> for Line<'static> {
+    fn from(markup: &Markup) -> Self {
+        let mut spans = Vec::new();
+        markup.for_each_text(ColorLevel::TrueColor, &mut |text, style| {
+            spans.push(Span::styled(text.to_string(), *style))
+        });
+
+        spans.into()
+    }
+}
+
+/// Like the [`Line`] conversion, starting a new line at every `\n`
+impl From<&Markup> for Text<'static> {
+    fn from(markup: &Markup) -> Self {
+        let mut lines = vec![Line::default()];
+        markup.for_each_text(ColorLevel::TrueColor, &mut |text, style| {
+            let style = rt::Style::from(*style);
+
+            for (i, part) in text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Line::default());
+                }
+
+                if !part.is_empty() {
+                    let line = lines.last_mut().expect("lines is never empty");
+                    line.spans.push(Span::styled(part.to_string(), style));
+                }
+            }
+        });
+
+        lines.into()
+    }
+}
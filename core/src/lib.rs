@@ -2,10 +2,22 @@
 
 #![warn(missing_docs, clippy::missing_docs_in_private_items)]
 
-mod parser;
+#[cfg(all(feature = "ratatui", feature = "markup"))]
+mod interop;
 
-pub mod error;
-pub mod style;
+pub mod ansi;
+pub mod border;
+pub mod panel;
+pub mod spans;
+pub mod stream;
+pub mod table;
+
+pub use cli_styler_spec::{caps, error, palette, parser, style, theme};
+
+/// Internals the macros expand to
+#[cfg(feature = "macros")]
+#[doc(hidden)]
+pub use cli_styler_spec::__private;
 
 /// Module for CLI support
 #[cfg(feature = "cli")]
@@ -15,15 +27,190 @@ pub mod cli;
 #[cfg(feature = "markup")]
 pub mod markup;
 
+#[cfg(feature = "termcolor")]
+pub mod termcolor;
+
+#[cfg(feature = "tracing")]
+pub mod tracing;
+
+#[cfg(feature = "log")]
+pub mod log;
+
+#[cfg(feature = "term")]
+pub mod term;
+
+#[cfg(feature = "syntect")]
+pub mod highlight;
+
+/// Parse a style spec at compile time into a [`CompiledStyle`](style::CompiledStyle)
+///
+/// The spec syntax is the same as [`Style::new_from_cli_spec`](style::Style::new_from_cli_spec),
+/// but typos become compile errors and the result is usable in `const` context.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::style::CompiledStyle;
+///
+/// const WARNING: CompiledStyle = cli_styler::style!("fb y m b");
+///
+/// assert_eq!(WARNING, CompiledStyle::new_from_cli_spec("fb y m b").unwrap());
+/// ```
+///
+/// ```compile_fail
+/// const BROKEN: cli_styler::style::CompiledStyle = cli_styler::style!("f notacolor");
+/// ```
+#[cfg(feature = "macros")]
+pub use cli_styler_macros::style;
+
+/// Build a [`Markup`](markup::Markup) from a literal checked at compile time
+///
+/// The literal is validated like [`Markup::new`](markup::Markup::new) would, so typos
+/// in tags fail the build instead of the first render.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::markup::Markup;
+///
+/// let banner = cli_styler::markup!("<f g m b>ready</> on <raw><port></raw>");
+///
+/// assert_eq!(banner, Markup::new("<f g m b>ready</> on <raw><port></raw>").unwrap());
+/// ```
+///
+/// ```compile_fail
+/// let _ = cli_styler::markup!("<f r m z>bad modifier</>");
+/// ```
+#[cfg(all(feature = "macros", feature = "markup"))]
+pub use cli_styler_macros::markup;
+
+/// [`format!`] with a markup format string, the markup is parsed at compile time
+///
+/// Tags use the [`Markup`](markup::Markup) syntax, interpolated values are never
+/// parsed as markup. Styles are only emitted while [`is_enabled`] holds.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, styled};
+///
+/// let n = 3;
+/// assert_eq!(
+///     styled!("<f r m b>{}</> items, <f c>{n:>3}</>", "<no tag>"),
+///     format!(
+///         "{} items, {}",
+///         Style::new().fg(Color::Red).bold().style("<no tag>"),
+///         Style::new().fg(Color::Cyan).style("  3")
+///     )
+/// );
+/// ```
+///
+/// Shortcodes in the format string are resolved when it's formatted, like
+/// [`Markup`](markup::Markup) resolves them when rendering, see
+/// [`caps::set_shortcodes`]. Nothing inside `<raw>` is interpolated or resolved.
+///
+/// ```rust
+/// use cli_styler::{caps, style::Shortcodes, styled};
+///
+/// let path = "src/main.rs";
+/// assert_eq!(styled!(":warning: {path}"), ":warning: src/main.rs");
+///
+/// caps::set_shortcodes(Shortcodes::Ascii);
+/// assert_eq!(styled!(":warning: {path}"), "! src/main.rs");
+/// assert_eq!(styled!("<raw>:warning: {path}</raw>"), ":warning: {path}");
+/// ```
+///
+/// ```compile_fail
+/// let _ = cli_styler::styled!("<f r>unclosed");
+/// ```
+#[cfg(feature = "macros")]
+pub use cli_styler_macros::styled;
+
+/// [`print!`] with a markup format string, see [`styled!`]
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! cprint {
+    ($($arg:tt)*) => {
+        ::std::print!("{}", $crate::styled!($($arg)*))
+    };
+}
+
+/// [`println!`] with a markup format string, see [`styled!`]
+///
+/// # Example
+/// ```rust
+/// let count = 2;
+/// cli_styler::cprintln!("<f g m b>ok</> {count} tests passed");
+/// ```
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! cprintln {
+    ($($arg:tt)*) => {
+        ::std::println!("{}", $crate::styled!($($arg)*))
+    };
+}
+
+/// [`eprint!`] with a markup format string, see [`styled!`]
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! ceprint {
+    ($($arg:tt)*) => {
+        ::std::eprint!("{}", $crate::styled!($($arg)*))
+    };
+}
+
+/// [`eprintln!`] with a markup format string, see [`styled!`]
+#[cfg(feature = "macros")]
+#[macro_export]
+macro_rules! ceprintln {
+    ($($arg:tt)*) => {
+        ::std::eprintln!("{}", $crate::styled!($($arg)*))
+    };
+}
+
+/// Globally enable or disable styled output, e.g. to implement a `--no-color` flag
+///
+/// Every [`Stylable`](style::Stylable) impl, [`Styled`](style::Styled) and markup render consults this switch.
+/// It sits on top of [`caps::set_color_choice`]: disabling wins over any choice, and re-enabling
+/// restores whatever choice was made before, so `NO_COLOR` is still honored under the default
+/// [`ColorChoice::Auto`](caps::ColorChoice::Auto).
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{caps::{self, ColorChoice}, prelude::*};
+///
+/// caps::set_color_choice(ColorChoice::Always);
+///
+/// cli_styler::set_enabled(false);
+/// assert!(!cli_styler::is_enabled());
+/// assert_eq!(Style::new().bold().style("x"), "x");
+///
+/// cli_styler::set_enabled(true);
+/// assert!(cli_styler::is_enabled());
+/// assert_eq!(caps::color_choice(), ColorChoice::Always);
+/// assert_eq!(Style::new().bold().style("x"), "\x1b[1mx\x1b[0m");
+/// ```
+pub fn set_enabled(enabled: bool) {
+    cli_styler_spec::__private::set_enabled(enabled);
+}
+
+/// Check whether styled output is currently enabled, see [`set_enabled`]
+pub fn is_enabled() -> bool {
+    cli_styler_spec::__private::should_colorize()
+}
+
 /// Basic imports
 pub mod prelude {
-    pub use super::style::{Color, Stylable, Style};
+    pub use super::style::{Color, Stylable, Style, Styled, StylizeExt};
 }
 
 /// Unit Tests
 #[cfg(test)]
 mod tests {
     use super::prelude::*;
+    use super::style::{ColorLevel, CompiledStyle, Modifier};
+
+    /// Pin colored output, so tests asserting escapes don't depend on `NO_COLOR`
+    fn colored() {
+        crate::caps::set_color_choice(crate::caps::ColorChoice::Always);
+    }
 
     #[cfg(test)]
     mod spec_test {
@@ -151,6 +338,44 @@ mod tests {
             );
         }
 
+        #[test]
+        fn named_colors() {
+            assert_eq!(
+                Style::new()
+                    .fg_rgb(0xFF, 0xA5, 0x00)
+                    .bg_rgb(0x70, 0x80, 0x90),
+                Style::new_from_cli_spec("f orange b slategray").unwrap()
+            );
+
+            assert_eq!(
+                Style::new().fg(Color::Red).fg_brighten(),
+                Style::new_from_cli_spec("fb Red").unwrap()
+            );
+
+            assert_eq!(
+                Color::from_name("Dark-Orange"),
+                Some(Color::RGB(0xFF, 0x8C, 0x00))
+            );
+            assert_eq!(Color::from_name("notacolor"), None);
+            assert!(Style::new_from_cli_spec("f notacolor").is_err());
+        }
+
+        #[test]
+        fn from_str() {
+            assert_eq!(
+                "f r m b".parse::<Style>().unwrap(),
+                Style::new().fg(Color::Red).bold()
+            );
+            assert_eq!(
+                CompiledStyle::try_from("b #ABC").unwrap(),
+                Style::new().bg_rgb(0xAA, 0xBB, 0xCC).compile()
+            );
+            assert_eq!("99".parse::<Color>().unwrap(), Color::Indexed(99));
+            assert_eq!(Color::try_from("k").unwrap(), Color::Black);
+            assert!("x r".parse::<Style>().is_err());
+            assert!("#GGG".parse::<Color>().is_err());
+        }
+
         #[test]
         fn modifiers() {
             assert_eq!(
@@ -168,5 +393,2715 @@ mod tests {
                 Style::new_from_cli_spec("m ilobu").unwrap()
             );
         }
+
+        #[test]
+        fn invalid_spec_is_an_error() {
+            assert!(Style::new_from_cli_spec("f").is_err());
+            assert!(Style::new_from_cli_spec("x r").is_err());
+            assert!(Style::new_from_cli_spec("m z").is_err());
+            assert!(CompiledStyle::new_from_cli_spec("f #ABCDE").is_err());
+        }
+
+        #[test]
+        fn error_spans() {
+            let span = |spec| match Style::new_from_cli_spec(spec) {
+                Err(crate::error::StylerError::ParsingError(err)) => err.span(),
+                other => panic!("expected a parsing error, got {other:?}"),
+            };
+
+            assert_eq!(span("f r  x r"), 5..6);
+            assert_eq!(span("f   #12345"), 4..10);
+            assert_eq!(span("m bu m biz"), 9..10);
+            assert_eq!(span("f r m biz"), 8..9);
+            assert_eq!(span("m u:wavy"), 4..8);
+            assert_eq!(span("m raw:1,x"), 6..9);
+            assert_eq!(span("f 1,2x,3"), 4..6);
+            assert_eq!(span("f r b"), 4..5);
+            assert_eq!(span("f r b g m b f g"), 12..13);
+            assert_eq!(span("m b bb r b g"), 9..10);
+        }
+
+        #[test]
+        fn argument_limit() {
+            use crate::style::ParseOptions;
+
+            let spec = "f r b k m b";
+            assert!(Style::new_from_cli_spec(spec).is_ok());
+            assert!(Style::new_from_cli_spec_with(spec, &ParseOptions::new().max_args(6)).is_ok());
+
+            let err = parse_style_err(spec, ParseOptions::new().max_args(4));
+            assert_eq!(err.span(), 8..11);
+            assert_eq!(err.to_string(), "Too many arguments (<=4): f r b k m b:6");
+        }
+
+        /// Parse a spec which is expected to fail
+        fn parse_style_err(
+            spec: &str,
+            opts: crate::style::ParseOptions,
+        ) -> crate::error::ParsingError {
+            match Style::new_from_cli_spec_with(spec, &opts) {
+                Err(crate::error::StylerError::ParsingError(err)) => err,
+                other => panic!("expected a parsing error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn repeated_params() {
+            use crate::style::ParseOptions;
+
+            assert_eq!(
+                Style::new_from_cli_spec("m bi f r m ui m u:curly").unwrap(),
+                Style::new()
+                    .fg(Color::Red)
+                    .bold()
+                    .italic()
+                    .underline_style(crate::style::UnderlineStyle::Curly)
+            );
+            assert_eq!(
+                Style::new_from_cli_spec("m raw:1,2 m raw:3")
+                    .unwrap()
+                    .raw_params(),
+                [1, 2, 3]
+            );
+            assert!(Style::new_from_cli_spec("m raw:1,2,3,4,5 m raw:6,7,8,9").is_err());
+
+            assert!(Style::new_from_cli_spec("b r bb g").is_err());
+            assert_eq!(
+                Style::new_from_cli_spec_with("b r bb g f y fb c f k", &ParseOptions::lenient())
+                    .unwrap(),
+                Style::new().bg(Color::Green).bg_brighten().fg(Color::Black)
+            );
+        }
+
+        #[test]
+        fn key_value_pairs() {
+            assert_eq!(
+                Style::new_from_cli_spec("f=red b=#ABC m=bu").unwrap(),
+                Style::new()
+                    .fg(Color::Red)
+                    .bg_rgb(0xAA, 0xBB, 0xCC)
+                    .bold()
+                    .underline()
+            );
+            assert_eq!(
+                Style::new_from_cli_spec("fb=c b k m=u:curly").unwrap(),
+                Style::new_from_cli_spec("fb c b k m u:curly").unwrap()
+            );
+
+            let span = |spec| match Style::new_from_cli_spec(spec) {
+                Err(crate::error::StylerError::ParsingError(err)) => err.span(),
+                other => panic!("expected a parsing error, got {other:?}"),
+            };
+            assert_eq!(span("f=r b="), 4..5);
+            assert_eq!(span("f b=r"), 0..1);
+            assert_eq!(span("f=r x=y"), 4..5);
+            assert_eq!(span("f=#12x"), 2..6);
+        }
+
+        #[test]
+        fn color_aliases() {
+            use crate::{palette::Palette, style::ParseOptions};
+
+            let opts = ParseOptions::new()
+                .with_alias("brand", Color::RGB(1, 2, 3))
+                .with_alias("r", Color::Indexed(160))
+                .with_aliases(&Palette::new().with("muted", Color::Indexed(244)));
+
+            assert_eq!(
+                Style::new_from_cli_spec_with("f brand b=muted", &opts).unwrap(),
+                Style::new().fg_rgb(1, 2, 3).bg_index(244)
+            );
+            assert_eq!(
+                Style::new_from_cli_spec_with("fb r", &opts).unwrap(),
+                Style::new().fg_index(160).fg_brighten()
+            );
+            assert!(Style::new_from_cli_spec("f brand").is_err());
+            assert!(Style::new_from_cli_spec_with("f Brand", &opts).is_err());
+        }
+
+        #[test]
+        fn spec_warnings() {
+            use crate::style::{SpecWarning::*, StyleWarning};
+
+            assert!(Style::check_spec("fb r b k m bi m u:curly").is_empty());
+
+            assert_eq!(
+                Style::check_spec("f r f 300 m bzr"),
+                [
+                    DuplicateColor("f".to_string(), 4..5),
+                    Invalid("Unknown color format: 300".to_string(), 6..9),
+                    OverriddenModifier('b', 12..13),
+                    Invalid("Invalid modifier: z".to_string(), 13..14),
+                ]
+            );
+            assert_eq!(
+                Style::check_spec("m bd m"),
+                [Invalid(
+                    "Missing value for parameter m (argument 3)".to_string(),
+                    5..6
+                )]
+            );
+            assert_eq!(
+                Style::check_spec("f k b k"),
+                [Conflict(StyleWarning::SameColors, 0..7)]
+            );
+
+            let warning = &Style::check_spec("m b m u:wavy")[0];
+            assert_eq!(warning.span(), 8..12);
+            assert_eq!(warning.to_string(), "Invalid underline style: wavy");
+        }
+
+        #[test]
+        fn canonical_format() {
+            for (spec, formatted) in [
+                ("", ""),
+                ("b=ORANGE", "b #ffa500"),
+                ("m i m r f 7 m b", "f 7 m rb"),
+                (
+                    "bb 0,128, m u:Curly m raw:1;2",
+                    "bb #008000 m u:curly m raw:1,2",
+                ),
+                ("f #aBc m d m b", "f #aabbcc m bd"),
+            ] {
+                assert_eq!(Style::format_spec(spec).unwrap(), formatted, "{spec}");
+                assert_eq!(Style::format_spec(formatted).unwrap(), formatted);
+            }
+        }
+
+        #[test]
+        fn hex_prefixes() {
+            assert_eq!(
+                Style::new_from_cli_spec("f #f80 b 0xFF8800").unwrap(),
+                Style::new().fg_rgb(255, 136, 0).bg_rgb(255, 136, 0)
+            );
+            assert_eq!("0x10".parse::<Color>().unwrap(), Color::RGB(16, 16, 16));
+            assert!(Style::new_from_cli_spec("f ff8800#").is_err());
+            assert!(matches!(
+                "0xff880".parse::<Color>(),
+                Err(crate::error::StylerError::ParsingError(
+                    crate::error::ParsingError::InvalidHexClr(_, 5, _)
+                ))
+            ));
+
+            #[cfg(feature = "cli")]
+            {
+                use crate::style::ParseOptions;
+                use cli_styler_spec::__private::{Cli, parse_style};
+
+                let orange = Style::new().fg_rgb(255, 136, 0);
+                for spec in ["f #ff8800", "f 0xff8800", "f ff8800#"] {
+                    assert_eq!(
+                        parse_style(spec, &Cli, &ParseOptions::new()).unwrap(),
+                        orange
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn color_functions() {
+            use crate::error::{ParsingError, StylerError};
+
+            assert_eq!(
+                Style::new_from_cli_spec("f rgb(0, 128, 255) b=RGB(100%,0%,50%)").unwrap(),
+                Style::new().fg_rgb(0, 128, 255).bg_rgb(255, 0, 128)
+            );
+            assert_eq!(
+                "hsl(210, 100%, 50%)".parse::<Color>().unwrap(),
+                Color::from_hsl(210.0, 1.0, 0.5)
+            );
+            assert_eq!(
+                "hsl(120deg,100,25)".parse::<Color>().unwrap(),
+                Color::RGB(0, 128, 0)
+            );
+            assert_eq!(
+                "rgba(1, 2, 3, 0.5)".parse::<Color>().unwrap(),
+                Color::RGBA(1, 2, 3, 128)
+            );
+
+            let err = |spec: &str| match Style::new_from_cli_spec(spec) {
+                Err(StylerError::ParsingError(err)) => err,
+                other => panic!("expected a parsing error, got {other:?}"),
+            };
+
+            assert!(matches!(
+                err("f rgb(1, 2)"),
+                ParsingError::InvalidClrFnArgs(_, 2, span) if span == (2..11)
+            ));
+            assert!(matches!(
+                err("f rgb(1, 256, 3)"),
+                ParsingError::InvalidClrFnComp(comp, span) if comp == "256" && span == (9..12)
+            ));
+            assert!(matches!(
+                err("f hsl(10%, 5, 5)"),
+                ParsingError::InvalidClrFnComp(..)
+            ));
+            assert!(matches!(
+                err("f lab(1, 2, 3)"),
+                ParsingError::UnknownClrFmt(..)
+            ));
+
+            #[cfg(feature = "markup")]
+            assert_eq!(
+                crate::markup::Markup::new("<f rgb(255, 0, 0)>hot</>")
+                    .unwrap()
+                    .render(),
+                Style::new().fg_rgb(255, 0, 0).style("hot")
+            );
+        }
+
+        #[test]
+        fn uppercase_is_bright() {
+            use crate::style::ParseOptions;
+
+            assert_eq!(
+                Style::new_from_cli_spec("f R b G").unwrap(),
+                Style::new_from_cli_spec("fb r bb g").unwrap()
+            );
+            assert_eq!(
+                Style::new_from_cli_spec("fb R").unwrap(),
+                Style::new_from_cli_spec("fb r").unwrap()
+            );
+            assert_eq!(
+                Style::new_from_cli_spec("f RED").unwrap(),
+                Style::new().fg(Color::Red)
+            );
+
+            let opts = ParseOptions::new().with_alias("R", Color::Indexed(9));
+            assert_eq!(
+                Style::new_from_cli_spec_with("f R", &opts).unwrap(),
+                Style::new().fg(Color::Indexed(9))
+            );
+        }
+
+        #[test]
+        fn negated_modifiers() {
+            colored();
+
+            use crate::style::Modifier;
+
+            assert_eq!(
+                Style::new_from_cli_spec("m !b-u").unwrap(),
+                [Modifier::NoBold, Modifier::NoUnderline]
+                    .into_iter()
+                    .collect()
+            );
+            assert_eq!(
+                Style::new_from_cli_spec("m b!b").unwrap(),
+                Style::new_from_cli_spec("m !b").unwrap()
+            );
+            assert_eq!(
+                Style::new_from_cli_spec("m !b").unwrap().style("x"),
+                "\x1b[22mx\x1b[0m"
+            );
+            assert_eq!(
+                parse_style_err("m !r", crate::style::ParseOptions::new()).span(),
+                2..4
+            );
+            assert_eq!(
+                parse_style_err("m b!", crate::style::ParseOptions::new()).span(),
+                3..4
+            );
+        }
+
+        #[test]
+        fn missing_value() {
+            use crate::{error::ParsingError, style::ParseOptions};
+
+            let missing = |spec: &str| match parse_style_err(spec, ParseOptions::new()) {
+                ParsingError::MissingParamVal(param, position, span) => (param, position, span),
+                other => panic!("expected a missing value, got {other:?}"),
+            };
+
+            assert_eq!(missing("f r b"), ("b".to_string(), 3, 4..5));
+            assert_eq!(missing("m"), ("m".to_string(), 1, 0..1));
+            assert_eq!(missing("f b=r"), ("f".to_string(), 1, 0..1));
+            assert_eq!(missing("f r m="), ("m".to_string(), 3, 4..5));
+        }
+
+        #[test]
+        fn strict_rgb() {
+            use crate::style::ParseOptions;
+
+            let lenient = ParseOptions::new().lenient_rgb(true);
+
+            assert_eq!(
+                Style::new_from_cli_spec("f 255,, b ,7,").unwrap(),
+                Style::new().fg_rgb(255, 0, 0).bg_rgb(0, 7, 0)
+            );
+            assert!(matches!(
+                "1,256,3".parse::<Color>(),
+                Err(crate::error::StylerError::ParsingError(
+                    crate::error::ParsingError::InvalidRgbComponent(..)
+                ))
+            ));
+            assert!(matches!(
+                "purpul".parse::<Color>(),
+                Err(crate::error::StylerError::ParsingError(
+                    crate::error::ParsingError::UnknownClrFmt(..)
+                ))
+            ));
+            assert_eq!(
+                Style::new_from_cli_spec_with("b 1,x,3", &lenient).unwrap(),
+                Style::new().bg_rgb(1, 0, 3)
+            );
+        }
+    }
+
+    #[cfg(test)]
+    #[cfg(feature = "markup")]
+    mod markup_test {
+        use super::*;
+        use crate::{
+            markup::{MarkdownStyles, Markup, MarkupBuilder, MarkupStream, MarkupVisitor},
+            theme::Theme,
+        };
+        use std::collections::HashMap;
+
+        #[test]
+        fn themed_tags() {
+            let theme = Theme::new().with("error", Style::new().fg(Color::Red).bold());
+
+            assert_eq!(
+                Markup::new_with_theme("<error>failed</> ok", &theme)
+                    .unwrap()
+                    .render(),
+                format!("{} ok", theme.style("error", "failed"))
+            );
+
+            assert_eq!(
+                Markup::new_with_theme("<f g>fine</>", &theme)
+                    .unwrap()
+                    .render(),
+                Style::new().fg(Color::Green).style("fine")
+            );
+
+            assert!(Markup::new("<error>failed</>").is_err());
+            assert!(Theme::builtin().contains("warning"));
+        }
+
+        #[test]
+        fn width() {
+            assert_eq!(Markup::new("<f r>ab<m b>日</></>c").unwrap().width(), 5);
+            assert_eq!(Markup::new("").unwrap().width(), 0);
+        }
+
+        #[test]
+        fn spanned_tokens() {
+            use crate::parser::{Token, tokens};
+
+            let src = "a\\<b<>c</><m b>\\d</>";
+            let all = tokens(src).collect::<Result<Vec<_>, _>>().unwrap();
+
+            assert_eq!(
+                all,
+                [
+                    (Token::Text("a<b".to_string()), 0..4),
+                    (Token::Empty, 4..6),
+                    (Token::Text("c".to_string()), 6..7),
+                    (Token::End, 7..10),
+                    (Token::Fmt(Style::new().bold()), 10..15),
+                    (Token::Text("\\d".to_string()), 15..17),
+                    (Token::End, 17..20),
+                ]
+            );
+
+            let mut bad = tokens("ok<f z>x</>");
+            assert_eq!(
+                bad.next().unwrap().unwrap(),
+                (Token::Text("ok".to_string()), 0..2)
+            );
+            assert_eq!(bad.next().unwrap().unwrap_err().span(), 5..6);
+            assert!(bad.next().is_none());
+
+            let unclosed = tokens("<m b>x").collect::<Vec<_>>();
+            assert_eq!(unclosed.last().unwrap().as_ref().unwrap_err().span(), 0..5);
+        }
+
+        #[test]
+        fn negated_modifiers() {
+            assert_eq!(
+                Markup::new("<m b>a<m !b>b</></>").unwrap().render(),
+                format!(
+                    "{}{}",
+                    Style::new().bold().style("a"),
+                    Style::new_from_cli_spec("m !b").unwrap().style("b")
+                )
+            );
+        }
+
+        #[test]
+        fn custom_dialect() {
+            use crate::{
+                parser::{Dialect, Param, Token, tokens_with},
+                style::ParseOptions,
+            };
+
+            /// Spells the foreground parameter `fg`
+            struct Long;
+
+            impl Dialect for Long {
+                fn param(&self, name: &str) -> Option<Param> {
+                    (name == "fg").then_some(Param::Fg)
+                }
+            }
+
+            let opts = ParseOptions::new();
+            let red = Style::new().fg(Color::Red);
+
+            assert_eq!(
+                Markup::new_with_dialect("<fg r>a</>", &Long, &opts)
+                    .unwrap()
+                    .render(),
+                red.style("a")
+            );
+            assert!(Markup::new_with_dialect("<f r>a</>", &Long, &opts).is_err());
+            assert_eq!(
+                tokens_with("<fg r>", &Long, &opts).next().unwrap().unwrap(),
+                (Token::Fmt(red), 0..6)
+            );
+        }
+
+        #[test]
+        fn input_limits() {
+            use crate::{
+                error::{Limit, ParsingError},
+                style::ParseOptions,
+            };
+
+            let opts = ParseOptions::new().max_len(16).max_tags(2).max_depth(1);
+            let limit = |s: &str| match Markup::new_with_options(s, &opts) {
+                Err(ParsingError::LimitExceeded(limit, max, span)) => (limit, max, span),
+                other => panic!("expected an exceeded limit, got {other:?}"),
+            };
+
+            assert!(Markup::new_with_options("<m b>a</><>b</>", &opts).is_ok());
+            assert_eq!(limit("<m b>a<m i>b</></>"), (Limit::Length, 16, 16..18));
+            assert_eq!(limit("<m b>a<>b</></>"), (Limit::Depth, 1, 6..8));
+            assert_eq!(limit("<>a</><>b</><>"), (Limit::Tags, 2, 12..14));
+            assert_eq!(
+                ParsingError::LimitExceeded(Limit::Depth, 1, 6..8).to_string(),
+                "Markup exceeds the nesting depth limit of 1"
+            );
+        }
+
+        #[test]
+        fn named_tags() {
+            use crate::error::ParsingError;
+
+            let theme = Theme::new()
+                .with("error", Style::new().fg(Color::Red))
+                .with("warning", Style::new().fg(Color::Yellow));
+
+            assert_eq!(
+                Markup::new_with_theme("<error>failed</error> <f g>ok</f  g>", &theme)
+                    .unwrap()
+                    .render(),
+                format!(
+                    "{} {}",
+                    theme.style("error", "failed"),
+                    Style::new().fg(Color::Green).style("ok")
+                )
+            );
+
+            let err = Markup::new_with_theme("<eror>failed</>", &theme).unwrap_err();
+            assert_eq!(err.to_string(), "Unknown tag: eror, did you mean error?");
+            assert_eq!(err.span(), 0..6);
+            assert!(matches!(
+                Markup::new("<error>failed</>"),
+                Err(ParsingError::UnknownTag(_, suggestions, _)) if suggestions.is_empty()
+            ));
+
+            let err = Markup::new_with_theme("<error>a<warning>b</error></>", &theme).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Closing tag </error> doesn't match <warning>"
+            );
+            assert_eq!(err.span(), 18..26);
+            assert!(matches!(
+                Markup::new("<m>a</>"),
+                Err(ParsingError::MissingParamVal(..))
+            ));
+        }
+
+        #[test]
+        fn render_repeatedly() {
+            let markup = Markup::new("<f r>a</> b").unwrap();
+            let once = markup.render();
+
+            assert_eq!(markup.render(), once);
+            assert_eq!(markup.to_string(), once);
+            assert_eq!(markup.clone().render_multiline(), once);
+        }
+
+        #[test]
+        fn builder() {
+            let theme = Theme::new().with_spec("warn", "f y m b").unwrap();
+            let built = Markup::builder()
+                .text("a")
+                .styled(Style::new().fg(Color::Red), |b| {
+                    b.styled(Style::new(), |b| b.text("b"))
+                        .markup(Markup::new_with_theme("<warn>c</>", &theme).unwrap())
+                })
+                .text("d")
+                .build();
+
+            assert_eq!(
+                built.render(),
+                Markup::new_with_theme("a<f r><>b</><warn>c</></>d", &theme)
+                    .unwrap()
+                    .render()
+            );
+            assert_eq!(MarkupBuilder::new().build(), Markup::new("").unwrap());
+        }
+
+        #[test]
+        fn visitor() {
+            /// Records the walk as a flat list of events
+            struct Events(Vec<String>);
+
+            impl MarkupVisitor for Events {
+                fn enter(&mut self, style: &Style) {
+                    self.0.push(format!("<{}>", style.to_spec()));
+                }
+
+                fn text(&mut self, text: &str, style: &Style) {
+                    self.0.push(format!("{text}:{}", style.to_spec()));
+                }
+
+                fn exit(&mut self, _style: &Style) {
+                    self.0.push("</>".to_string());
+                }
+            }
+
+            let markup = Markup::new("a<f r>b<m b>c</></>").unwrap();
+            let mut events = Events(Vec::new());
+            markup.walk(&mut events);
+
+            assert_eq!(
+                events.0,
+                ["a:", "<f r>", "b:f r", "<m b>", "c:m b", "</>", "</>"]
+            );
+
+            let mut seen = Vec::new();
+            let mapped = markup.map_styles(|style| {
+                seen.push(style.to_spec());
+                style.fg(Color::Green)
+            });
+
+            assert_eq!(seen, ["", "f r", "m b"]);
+            assert_eq!(
+                mapped.render(),
+                Markup::new("<f g>a<f g>b<f g m b>c</></></>")
+                    .unwrap()
+                    .render()
+            );
+        }
+
+        #[test]
+        fn streaming() {
+            colored();
+
+            use crate::{
+                error::{Limit, ParsingError},
+                style::ParseOptions,
+            };
+
+            let src = "a <f r>b \\<c <m b>d</m b>\\x</f r> é<>f</>\\";
+            let whole = Markup::new(src).unwrap().render();
+
+            let mut stream = MarkupStream::new();
+            let mut output = String::new();
+            for ch in src.chars() {
+                output += &stream.feed(ch.encode_utf8(&mut [0; 4])).unwrap();
+            }
+            output += &stream.finish().unwrap();
+            assert_eq!(output, whole);
+
+            // Displays the same, but the tree keeps the raw section in a node of its own
+            let src = "<f r>a<raw><m b></raw>b</>";
+            let mut stream = MarkupStream::new();
+            let output = stream.feed(src).unwrap() + &stream.finish().unwrap();
+            assert_eq!(output, Style::new().fg(Color::Red).style("a<m b>b"));
+            assert_ne!(output, Markup::new(src).unwrap().render());
+            assert_eq!(
+                crate::ansi::strip_ansi(&output),
+                crate::ansi::strip_ansi(&Markup::new(src).unwrap().render())
+            );
+
+            let mut stream = MarkupStream::new();
+            assert_eq!(stream.feed("a <f r>b").unwrap(), "a ");
+            assert_eq!(
+                stream.feed("</").unwrap(),
+                Style::new().fg(Color::Red).style("b")
+            );
+            assert_eq!(stream.feed(">").unwrap(), "");
+
+            let mut stream = MarkupStream::new();
+            stream.feed("ok<m b>").unwrap();
+            assert_eq!(stream.feed("x</m i>").unwrap_err().span(), 8..14);
+
+            let mut stream = MarkupStream::new();
+            stream.feed("ab<m b>").unwrap();
+            assert_eq!(stream.finish().unwrap_err().span(), 2..7);
+
+            let mut stream = MarkupStream::new();
+            assert_eq!(stream.feed("<f r m z>").unwrap_err().span(), 7..8);
+
+            let theme = Theme::new().with_spec("warn", "f y").unwrap();
+            let opts = ParseOptions::new().max_depth(1);
+            let mut stream = MarkupStream::new().with_theme(theme).with_options(opts);
+            stream.feed("<warn>a").unwrap();
+            assert!(matches!(
+                stream.feed("<m b>").unwrap_err(),
+                ParsingError::LimitExceeded(Limit::Depth, 1, span) if span == (7..12)
+            ));
+        }
+
+        #[test]
+        fn from_reader() {
+            use std::io;
+
+            use crate::error::{ParsingError, StylerError};
+
+            /// Hands out its input a byte at a time
+            struct Trickle<'a>(&'a [u8]);
+
+            impl io::Read for Trickle<'_> {
+                fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                    let Some((&first, rest)) = self.0.split_first() else {
+                        return Ok(0);
+                    };
+
+                    buf[0] = first;
+                    self.0 = rest;
+                    Ok(1)
+                }
+            }
+
+            let src = "a <f r>b \\<c <raw><x></raw><!-- <m z> -->é</f r> <m b>d</>\\";
+            let markup = Markup::from_reader(Trickle(src.as_bytes())).unwrap();
+            assert_eq!(markup, Markup::new(src).unwrap());
+            assert_eq!(Markup::from_reader(src.as_bytes()).unwrap(), markup);
+
+            assert!(matches!(
+                Markup::from_reader(Trickle(b"ok<m b>x</m i>")),
+                Err(StylerError::ParsingError(ParsingError::MismatchedClosingTag(_, _, span)))
+                    if span == (8..14)
+            ));
+            assert!(matches!(
+                Markup::from_reader(Trickle(b"ab<m b>")),
+                Err(StylerError::ParsingError(ParsingError::UnclosedTags(span))) if span == (2..7)
+            ));
+            for invalid in [&b"<f r>\xff</>"[..], b"a\xe9"] {
+                assert!(matches!(
+                    Markup::from_reader(Trickle(invalid)),
+                    Err(StylerError::Io(e)) if e.kind() == io::ErrorKind::InvalidData
+                ));
+            }
+            assert!(matches!(
+                Markup::from_file("does/not/exist.markup"),
+                Err(StylerError::Io(e)) if e.kind() == io::ErrorKind::NotFound
+            ));
+        }
+
+        #[test]
+        fn lenient() {
+            use crate::{
+                error::{Limit, ParsingError},
+                style::ParseOptions,
+            };
+
+            let render = |s: &str| Markup::new(s).unwrap().render();
+            let spans = |warnings: Vec<ParsingError>| {
+                warnings.iter().map(ParsingError::span).collect::<Vec<_>>()
+            };
+
+            let (markup, warnings) = Markup::new_lenient("<f r>1 <3 you <m z>!");
+            assert_eq!(markup.render(), render("<f r>1 \\<3 you \\<m z>!</>"));
+            assert_eq!(spans(warnings), [14..15, 17..18, 0..5]);
+
+            let (markup, warnings) = Markup::new_lenient("a</>b<m b>c</f r>d</><f r");
+            assert_eq!(markup.render(), render("a\\</>b<m b>c\\</f r>d</>\\<f r"));
+            assert!(matches!(
+                &warnings[..],
+                [
+                    ParsingError::UnexpectedClosingTag(_),
+                    ParsingError::MismatchedClosingTag(..),
+                    ParsingError::Eof(..)
+                ]
+            ));
+
+            let opts = ParseOptions::new().max_len(11).max_depth(1);
+            let (markup, warnings) = Markup::new_lenient_with("<m b><m i>x</></>", None, &opts);
+            assert_eq!(markup.render(), render("<m b>\\<m i>x</>"));
+            assert!(matches!(
+                &warnings[..],
+                [
+                    ParsingError::LimitExceeded(Limit::Length, 11, _),
+                    ParsingError::LimitExceeded(Limit::Depth, 1, _),
+                    ParsingError::UnclosedTags(_),
+                ]
+            ));
+
+            let src = "<f r>ok</> <m b>fine</>";
+            let (markup, warnings) = Markup::new_lenient(src);
+            assert_eq!(markup, Markup::new(src).unwrap());
+            assert!(warnings.is_empty());
+        }
+
+        #[test]
+        fn markdown() {
+            let styles = MarkdownStyles::new().with_code(Style::new().fg(Color::Yellow));
+            let md = |s: &str| Markup::new_markdown(s, &styles).unwrap().render();
+            let render = |s: &str| Markup::new(s).unwrap().render();
+
+            assert_eq!(
+                md("**bold *both* end** __u__ `a *b*` i*x*"),
+                render("<m b>bold <m i>both</> end</> <m u>u</> <f y>a *b*</> i<m i>x</>")
+            );
+            assert_eq!(
+                md("<f r>**é**</> \\*no\\* snake_case **open *"),
+                render("<f r><m b>é</></> *no* snake_case **open *")
+            );
+            assert_eq!(md("** `` __"), render("** `` __"));
+            assert!(Markup::new_markdown("**a</>**", &styles).is_err());
+        }
+
+        #[test]
+        fn source_spans() {
+            let src = "a\\<b<f r>c<m b>dé</>\\<</><>e</>";
+            let markup = Markup::new(src).unwrap();
+            let output = markup.render();
+            let spans = markup.spans();
+
+            assert_eq!(
+                spans
+                    .iter()
+                    .map(|(out, input, _)| (&output[out.clone()], &src[input.clone()]))
+                    .collect::<Vec<_>>(),
+                [
+                    ("a<b", "a\\<b"),
+                    ("c", "c"),
+                    ("dé", "dé"),
+                    ("<", "\\<"),
+                    ("e", "e")
+                ]
+            );
+            assert_eq!(spans[2].2, Style::new().bold());
+
+            let (lenient, _) = Markup::new_lenient("x<m z>y");
+            assert_eq!(lenient.spans()[0].1, 0..7);
+
+            let built = Markup::builder().text("a").markup(markup).build();
+            assert_eq!(built.spans()[0].1, 0..0);
+            assert_eq!(built.spans()[1].1, 0..4);
+        }
+
+        #[test]
+        fn raw_sections() {
+            let src = r"a<f r><raw><m b>\<x>\</raw>b</><raw></raw><raw>*</raw>";
+            let markup = Markup::new(src).unwrap();
+
+            assert_eq!(markup.render_plain(), r"a<m b>\<x>\b*");
+            assert_eq!(markup.spans()[1].1, 6..27);
+            assert_eq!(
+                markup.render(),
+                Markup::builder()
+                    .text("a")
+                    .styled(Style::new().fg(Color::Red), |b| b
+                        .text(r"<m b>\<x>\")
+                        .text("b"))
+                    .text("*")
+                    .build()
+                    .render()
+            );
+
+            assert_eq!(Markup::new("<raw>x</ra>").unwrap_err().span(), 0..11);
+            assert_eq!(
+                Markup::new_markdown("<raw>**a**</raw>", &MarkdownStyles::new())
+                    .unwrap()
+                    .render_plain(),
+                "**a**"
+            );
+
+            let mut stream = MarkupStream::new();
+            let mut output = stream.feed("<f r>a</><raw><").unwrap();
+            output += &stream.feed("x></ra").unwrap();
+            output += &stream.feed("w>").unwrap();
+            output += &stream.finish().unwrap();
+            assert_eq!(
+                output,
+                Markup::new("<f r>a</><raw><x></raw>").unwrap().render()
+            );
+        }
+
+        #[test]
+        fn escapes() {
+            use crate::style::ParseOptions;
+
+            let markup = Markup::new(r"a\<b\>c\\<m b>d\\</>\x\").unwrap();
+            assert_eq!(markup.render_plain(), r"a<b>c\d\\x\");
+
+            let opts = ParseOptions::new().escape_char('^');
+            let src = r"C:\dir\<f r>^<x^>^^</>^y\";
+            let markup = Markup::new_with_options(src, &opts).unwrap();
+            assert_eq!(markup.render_plain(), r"C:\dir\<x>^^y\");
+
+            let mut stream = MarkupStream::new().with_options(opts);
+            let mut output = String::new();
+            for ch in src.chars() {
+                output += &stream.feed(ch.encode_utf8(&mut [0; 4])).unwrap();
+            }
+            output += &stream.finish().unwrap();
+            assert_eq!(output, markup.render());
+        }
+
+        #[test]
+        fn comments() {
+            let src = "a<!-- <f r> it's -->b<m b><!---->c<!-- -- --->d</>";
+            let markup = Markup::new(src).unwrap();
+
+            assert_eq!(
+                markup.render(),
+                Markup::new("ab<m b>cd</>").unwrap().render()
+            );
+            assert_eq!(markup.spans()[0].1, 0..21);
+            assert_eq!(Markup::new("a<!-- x --->").unwrap().render_plain(), "a");
+            assert_eq!(Markup::new("a<!-- x ->").unwrap_err().span(), 1..10);
+            assert_eq!(
+                Markup::new("<raw><!-- x --></raw>").unwrap().render_plain(),
+                "<!-- x -->"
+            );
+
+            let mut stream = MarkupStream::new();
+            let mut output = stream.feed("<m b>a<!").unwrap();
+            output += &stream.feed("-- <f r").unwrap();
+            output += &stream.feed("> -").unwrap();
+            output += &stream.feed("->b</>").unwrap();
+            output += &stream.finish().unwrap();
+            assert_eq!(
+                output,
+                Markup::new("<m b>a<!-- <f r> -->b</>").unwrap().render()
+            );
+        }
+
+        #[test]
+        fn tree_dump() {
+            let theme = Theme::new().with_spec("warn", "fb y m u").unwrap();
+            let markup =
+                Markup::new_with_theme("<warn>a<f=#ff8800>\"b\"</></>\tc", &theme).unwrap();
+
+            assert_eq!(
+                markup.dump_tree(),
+                "<fb y m u>\n  \"a\"\n  <f #ff8800>\n    \"\\\"b\\\"\"\n\"\\tc\"\n"
+            );
+            assert_eq!(Markup::new("").unwrap().dump_tree(), "");
+        }
+
+        #[test]
+        fn markup_round_trip() {
+            let theme = Theme::new().with_spec("warn", "fb y m u").unwrap();
+            let src = "x<warn>a\\<b<f=#ff8800 m=!b>c\\d</></><!-- c --><>e</><raw>\\<</raw>";
+            let markup = Markup::new_with_theme(src, &theme).unwrap();
+            let canonical = markup.to_markup_string();
+
+            assert_eq!(
+                canonical,
+                "x<fb y m u>a\\<b<f #ff8800 m !b>c\\\\d</></><>e</><raw>\\<</raw>"
+            );
+            assert_eq!(
+                Markup::new(&canonical).unwrap().dump_tree(),
+                markup.dump_tree()
+            );
+
+            let mapped = markup.map_styles(|style| style.italic());
+            let reparsed = Markup::new(mapped.to_markup_string()).unwrap();
+            assert_eq!(reparsed.render(), mapped.render());
+
+            let built = Markup::builder().text("</raw>\\<").text("\\").build();
+            assert_eq!(built.to_markup_string(), "\\</raw>\\\\\\<\\\\");
+            assert_eq!(
+                Markup::new(built.to_markup_string())
+                    .unwrap()
+                    .render_plain(),
+                "</raw>\\<\\"
+            );
+        }
+
+        #[test]
+        fn fragments() {
+            use crate::{error::ParsingError, markup::Fragments};
+
+            let render = |src| Markup::new(src).unwrap().render();
+            let fragments = Fragments::new()
+                .with("ok", Markup::new("<f g>✔</> done").unwrap())
+                .with(
+                    "hot",
+                    Markup::new("x")
+                        .unwrap()
+                        .map_styles(|_| Style::new().bold()),
+                );
+
+            let markup =
+                Markup::new_with_fragments("<f r>a <include ok></><include hot>", &fragments)
+                    .unwrap();
+            assert_eq!(markup.render(), render("<f r>a <f g>✔</> done</><m b>x</>"));
+
+            assert!(matches!(
+                Markup::new_with_fragments("a<include nope>", &fragments),
+                Err(ParsingError::UnknownFragment(name, span)) if name == "nope" && span == (1..15)
+            ));
+            assert!(matches!(
+                Markup::new("<include ok>"),
+                Err(ParsingError::UnknownFragment(_, span)) if span == (0..12)
+            ));
+            assert!(MarkupStream::new().feed("a<include ok>").is_err());
+
+            let (markup, warnings) = Markup::new_lenient("a<include ok>b");
+            assert_eq!(markup.render_plain(), "a<include ok>b");
+            assert!(matches!(warnings[..], [ParsingError::UnknownFragment(..)]));
+
+            let mut joined = Markup::new("<f r>a</>").unwrap();
+            joined.append(Markup::new("b<m b>c</>").unwrap());
+            joined.append(fragments.get("hot").unwrap().clone());
+            assert_eq!(joined.render(), render("<f r>a</>b<m b>c</><m b>x</>"));
+            assert_eq!(joined.render_plain(), "abcx");
+
+            let concat =
+                Markup::concat(["<f r>a</>", "b", ""].map(|src| Markup::new(src).unwrap()));
+            assert_eq!(concat.dump_tree(), "<f r>\n  \"a\"\n\"b\"\n");
+            assert_eq!(Markup::concat([]), Markup::new("").unwrap());
+        }
+
+        #[test]
+        fn conditional_sections() {
+            use crate::{caps::Condition, error::ParsingError, style::ColorLevel};
+
+            let src = "<f r>a<if truecolor>T</if><if !truecolor>t</><if no-color>-</if></>";
+            let markup = Markup::new(src).unwrap();
+
+            assert_eq!(
+                markup.render_for(ColorLevel::TrueColor),
+                Markup::new("<f r>a</><f r>T</>").unwrap().render()
+            );
+            assert_eq!(
+                markup.render_for(ColorLevel::Ansi256),
+                Markup::new("<f r>a</><f r>t</>").unwrap().render()
+            );
+            assert_eq!(markup.render_plain(), "at-");
+            assert_eq!(
+                markup.render_html(),
+                Markup::new("<f r>a</><f r>T</>").unwrap().render_html()
+            );
+
+            assert_eq!(
+                markup.to_markup_string(),
+                "<f r>a<if truecolor>T</><if !truecolor>t</><if no-color>-</></>"
+            );
+            assert_eq!(
+                markup.dump_tree(),
+                "<f r>\n  \"a\"\n  <if truecolor>\n    \"T\"\n  <if !truecolor>\n    \"t\"\n  <if no-color>\n    \"-\"\n"
+            );
+
+            let mut stream = MarkupStream::new();
+            let mut output = stream.feed("<f r>a<if no-").unwrap();
+            output += &stream
+                .feed("color>-<m b>x</></if><if color>c</></>")
+                .unwrap();
+            output += &stream.finish().unwrap();
+            assert_eq!(
+                output,
+                Markup::new("<f r>a<if no-color>-<m b>x</></if><if color>c</></>")
+                    .unwrap()
+                    .render()
+            );
+
+            assert!(Condition::Ansi256.holds(ColorLevel::TrueColor));
+            assert!(!Condition::NotAnsi256.holds(ColorLevel::Ansi256));
+            assert_eq!(Condition::from_name("!color"), Some(Condition::NoColor));
+
+            assert!(matches!(
+                Markup::new("<if blink>x</if>"),
+                Err(ParsingError::UnknownCondition(name, span)) if name == "blink" && span == (0..10)
+            ));
+            assert!(matches!(
+                Markup::new("<f r><if color>x</f r></>"),
+                Err(ParsingError::MismatchedClosingTag(..))
+            ));
+            assert!(matches!(
+                Markup::new("<if color>x</if></if>"),
+                Err(ParsingError::UnexpectedClosingTag(..))
+            ));
+        }
+
+        #[test]
+        fn shortcodes() {
+            let src = "<f r>:warning:</> a::check::y: :nope: <raw>:tada:</raw> 12:30 :";
+            let markup = Markup::new(src).unwrap();
+
+            // Shortcodes are kept in the tree, only resolved when rendering
+            assert_eq!(
+                markup.render_plain(),
+                ":warning: a::check::y: :nope: :tada: 12:30 :"
+            );
+            assert_eq!(
+                markup.to_markup_string(),
+                "<f r>:warning:</> a::check::y: :nope: <raw>:tada:</raw> 12:30 :"
+            );
+        }
+
+        #[test]
+        #[cfg(feature = "syntect")]
+        fn code_sections() {
+            use crate::{error::ParsingError, highlight};
+
+            let markup =
+                Markup::new("a<code rust>fn x<T>() {}</code><code nope>\\<</code>").unwrap();
+
+            assert_eq!(markup.render_plain(), "afn x<T>() {}\\<");
+            assert_eq!(
+                markup.render(),
+                format!(
+                    "a{}{}",
+                    highlight::highlight("fn x<T>() {}", "rust"),
+                    highlight::highlight("\\<", "nope")
+                )
+            );
+            assert!(
+                markup.spans()[1..]
+                    .iter()
+                    .all(|(_, input, _)| *input == (1..31) || *input == (31..51))
+            );
+
+            let mut stream = MarkupStream::new();
+            let mut output = stream.feed("<f r>x</><code rs>let a").unwrap();
+            output += &stream.feed(" = 1;</code>!").unwrap();
+            output += &stream.finish().unwrap();
+            assert_eq!(
+                output,
+                Markup::new("<f r>x</><code rs>let a = 1;</code>!")
+                    .unwrap()
+                    .render()
+            );
+
+            // A section which doesn't hold hides its code too, `no-color` while colors are on
+            let hidden = match cli_styler_spec::__private::render_level() {
+                ColorLevel::None => "color",
+                _ => "no-color",
+            };
+            let mut stream = MarkupStream::new();
+            let mut output = stream
+                .feed(&format!("a<if {hidden}><code rust>let x = 1;</code></if>b"))
+                .unwrap();
+            output += &stream.finish().unwrap();
+            assert_eq!(output, "ab");
+
+            assert!(matches!(
+                Markup::new("<code rust>x"),
+                Err(ParsingError::Eof(..))
+            ));
+        }
+
+        #[test]
+        fn placeholders() {
+            let template = Markup::new("<f r>{level}:</> {msg} {{x}} {missing} {open").unwrap();
+            let vars = HashMap::from([("level", "error"), ("msg", "{level}")]);
+
+            assert_eq!(
+                template.render_with(&vars),
+                Markup::new("<f r>error:</> {level} {x} {missing} {open")
+                    .unwrap()
+                    .render()
+            );
+            assert_eq!(
+                template.render_with(&HashMap::new()),
+                template.render().replace("{{x}}", "{x}")
+            );
+        }
+
+        #[test]
+        fn render_for_level() {
+            let markup = Markup::new("<f #ff8800 m b>a<b 0,0,0>b</></>c").unwrap();
+
+            assert_eq!(
+                markup.render_for(ColorLevel::Ansi256),
+                Markup::new("<f 208 m b>a<b 16>b</></>c").unwrap().render()
+            );
+            assert_eq!(
+                markup.render_for(ColorLevel::None),
+                Markup::new("<m b>a<>b</></>c").unwrap().render()
+            );
+            assert_eq!(markup.render_for(ColorLevel::TrueColor), markup.render());
+        }
+
+        #[test]
+        fn plain_output() {
+            let markup = Markup::new("a<f r>b<m b>c</>\n<>d</></>e").unwrap();
+
+            assert_eq!(markup.render_plain(), "abc\nde");
+            assert_eq!(Markup::new("").unwrap().render_plain(), "");
+        }
+
+        #[test]
+        fn html_output() {
+            let markup = Markup::new("<f r m b>x <f b bb r m r>\\<\"y\"></> &</>'z'").unwrap();
+
+            assert_eq!(
+                markup.render_html(),
+                "<span style=\"color:#cd0000;font-weight:bold\">x </span>\
+                 <span style=\"color:#0000ee;background-color:#ff0000\">&lt;&quot;y&quot;&gt;</span>\
+                 <span style=\"color:#cd0000;font-weight:bold\"> &amp;</span>&#39;z&#39;"
+            );
+
+            // Like on a terminal, nested tags don't inherit the style around them
+            assert_eq!(
+                Markup::new("<f r>a<m b>b</>c</>").unwrap().render_html(),
+                "<span style=\"color:#cd0000\">a</span>\
+                 <span style=\"font-weight:bold\">b</span>\
+                 <span style=\"color:#cd0000\">c</span>"
+            );
+            assert_eq!(
+                Markup::new("<m o m u:dashed>a</>").unwrap().render_html(),
+                "<span style=\"text-decoration:underline overline;text-decoration-style:dashed\">a</span>"
+            );
+        }
+
+        #[test]
+        fn error_diagnostics() {
+            let src = "<m b>ok</>\n<f r m z>bad</>";
+            let err = Markup::new(src).unwrap_err();
+
+            assert_eq!(err.span(), 18..19);
+            assert_eq!(
+                err.render_diagnostic(src),
+                "error: Invalid modifier: z\n  |\n2 | <f r m z>bad</>\n  |        ^"
+            );
+
+            assert_eq!(Markup::new("a</>").unwrap_err().span(), 1..4);
+            assert_eq!(Markup::new("<m b>a<>b").unwrap_err().span(), 6..8);
+            assert_eq!(Markup::new("<f r").unwrap_err().span(), 0..4);
+            assert_eq!(Markup::new("<f r;>").unwrap_err().span(), 4..5);
+        }
+
+        #[test]
+        fn multiline() {
+            let red = Style::new().bg(Color::Red).compile();
+            let bold = Style::new().bold().compile();
+
+            assert_eq!(
+                Markup::new("<b r>a\nb<m b>c\n</></>")
+                    .unwrap()
+                    .render_multiline(),
+                format!(
+                    "{}\n{}{}\n",
+                    red.style("a"),
+                    red.style("b"),
+                    bold.style("c")
+                )
+            );
+            assert_eq!(
+                red.style_multiline("\nx\n"),
+                format!("\n{}\n", red.style("x"))
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod ansi_test {
+        use super::*;
+        use crate::ansi::display_width;
+
+        #[test]
+        fn width() {
+            assert_eq!(display_width(""), 0);
+            assert_eq!(display_width("plain"), 5);
+            assert_eq!(
+                display_width(&Style::new().fg_rgb(1, 2, 3).style("héllo")),
+                5
+            );
+            assert_eq!(
+                display_width("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"),
+                4
+            );
+            assert_eq!(display_width("a\x1b[1mb\x1b[0m日"), 4);
+            assert_eq!(display_width("trailing\x1b["), 8);
+        }
+
+        #[test]
+        fn parse() {
+            colored();
+
+            use crate::ansi;
+
+            let original = Style::new()
+                .fg_rgb(1, 2, 3)
+                .bg(Color::Cyan)
+                .bg_brighten()
+                .italic();
+            assert_eq!(
+                ansi::parse(&format!("{}!", original.style("hi"))),
+                [
+                    ("hi".to_string(), original),
+                    ("!".to_string(), Style::new())
+                ]
+            );
+
+            assert_eq!(
+                ansi::parse("\x1b[1;2mA\x1b[22;2mB\x1b[38;5;99mC\x1b[48:2::4:5:6;39mD"),
+                [
+                    ("A".to_string(), Style::new().bold().dim()),
+                    ("B".to_string(), Style::new().dim()),
+                    ("C".to_string(), Style::new().dim().fg_index(99)),
+                    ("D".to_string(), Style::new().dim().bg_rgb(4, 5, 6)),
+                ]
+            );
+
+            assert_eq!(
+                ansi::parse("\x1b[2Ja\x1b[1m\x1b[0mb"),
+                [("ab".to_string(), Style::new())]
+            );
+        }
+
+        #[test]
+        fn wrap() {
+            use crate::ansi::{strip_ansi, wrap, wrap_lines};
+
+            assert_eq!(wrap("the quick brown fox", 10), "the quick\nbrown fox");
+            assert_eq!(wrap("abcdefgh", 3), "abc\ndef\ngh");
+            assert_eq!(wrap("a\n\nb  c", 4), "a\n\nb  c");
+            assert_eq!(wrap("", 4), "");
+            assert_eq!(wrap("  ab cd", 5), "  ab\ncd");
+
+            let ul = Style::new().underline();
+            let text = format!("{} {}", ul.style("alpha beta"), "gamma");
+            let lines = wrap_lines(&text, 6);
+
+            assert_eq!(
+                lines,
+                [ul.style("alpha"), ul.style("beta"), "gamma".to_string()]
+            );
+            assert_eq!(
+                lines.iter().map(|l| strip_ansi(l)).collect::<Vec<_>>(),
+                ["alpha", "beta", "gamma"]
+            );
+        }
+
+        #[test]
+        fn strip() {
+            use crate::ansi::{StripWriter, strip_ansi};
+            use std::io::Write;
+
+            let styled = format!(
+                "{} \x1b]8;;url\x1b\\link\x1b]8;;\x1b\\ {}",
+                "a".red().bold(),
+                "b".on_blue()
+            );
+            assert_eq!(strip_ansi(&styled), "a link b");
+
+            let mut writer = StripWriter::new(Vec::new());
+            for chunk in styled.as_bytes().chunks(3) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(writer.into_inner(), b"a link b");
+        }
+    }
+
+    #[cfg(test)]
+    mod spans_test {
+        use super::*;
+        use crate::spans::StyledString;
+
+        #[test]
+        fn push_and_merge() {
+            colored();
+
+            let red = Style::new().fg(Color::Red);
+            let mut line = StyledString::new().with("a", red).with("b", red);
+            line.push_plain("");
+            line.append("c".into());
+
+            assert_eq!(line.len(), 2);
+            assert_eq!(
+                line.iter().collect::<Vec<_>>(),
+                [("ab", &red), ("c", &Style::new())]
+            );
+            assert_eq!(line.to_string(), format!("{}c", red.style("ab")));
+            assert_eq!(
+                StyledString::concat([line.clone(), "d".red().into()]).plain_text(),
+                "abcd"
+            );
+            assert_eq!(line.adapt(ColorLevel::None).to_string(), "abc");
+            assert_eq!(line.width(), 3);
+            assert_eq!(StyledString::from_ansi(&line.to_string()), line);
+        }
+    }
+
+    #[cfg(test)]
+    mod theme_test {
+        use crate::{error::StylerError, theme::Theme};
+
+        #[test]
+        fn from_specs_reports_key() {
+            let err = Theme::from_specs([("ok", "f r"), ("broken", "f")]).unwrap_err();
+            assert!(matches!(err, StylerError::ThemeEntry(key, _) if key == "broken"));
+        }
+
+        #[test]
+        fn style_level() {
+            super::colored();
+
+            let theme = Theme::logging();
+            assert_eq!(theme.style_level("WARN", "w"), theme.style("warning", "w"));
+            assert_eq!(theme.style_level("debug", "d"), theme.style("debug", "d"));
+            assert_ne!(theme.style_level("ERROR", "e"), "e");
+            assert_eq!(Theme::new().style_level("ERROR", "e"), "e");
+        }
+
+        #[test]
+        #[cfg(feature = "toml")]
+        fn toml_round_trip() {
+            use super::*;
+
+            let theme = Theme::from_toml_str("error = \"fb r m b\"\npath = \"f c m u\"").unwrap();
+            assert_eq!(
+                theme.get("error"),
+                Some(&Style::new().fg(Color::Red).fg_brighten().bold())
+            );
+            assert_eq!(
+                Theme::from_toml_str(&theme.to_toml_string().unwrap()).unwrap(),
+                theme
+            );
+            assert!(matches!(
+                Theme::from_toml_str("error = 1"),
+                Err(StylerError::ThemeFormat(_))
+            ));
+        }
+
+        #[test]
+        #[cfg(feature = "json")]
+        fn json_round_trip() {
+            let theme = Theme::builtin();
+            assert_eq!(
+                Theme::from_json_str(&theme.to_json_string().unwrap()).unwrap(),
+                theme
+            );
+            assert!(matches!(
+                Theme::from_json_str(r#"{"warning": "m z"}"#),
+                Err(StylerError::ThemeEntry(key, _)) if key == "warning"
+            ));
+        }
+    }
+
+    #[cfg(test)]
+    mod table_test {
+        use super::*;
+        use crate::{
+            border::Border,
+            table::{Align, Cell, Column, Table},
+        };
+
+        #[test]
+        fn styled_cells_keep_alignment() {
+            let table = Table::new()
+                .column(Column::new("k").align(Align::Center))
+                .column(Column::new("value"))
+                .row([Cell::new("a").style(Style::new().bold()), "1".into()])
+                .row(["bbb", "22"]);
+
+            let bold = Style::new().bold().style("a");
+            assert_eq!(
+                table.render(),
+                format!(
+                    "┌─────┬───────┐\n\
+                     │  k  │ value │\n\
+                     ├─────┼───────┤\n\
+                     │  {bold}  │ 1     │\n\
+                     │ bbb │ 22    │\n\
+                     └─────┴───────┘"
+                )
+            );
+        }
+
+        #[test]
+        fn borderless_without_header() {
+            let table = Table::new()
+                .border(Border::None)
+                .row(["日本", "x"])
+                .row(["a", "yy", "z"]);
+
+            assert_eq!(table.render(), " 日本  x\n a     yy  z");
+            assert!(Cell::new_with_spec("a", "f").is_err());
+            assert_eq!("heavy".parse::<Border>(), Ok(Border::Heavy));
+        }
+    }
+
+    #[cfg(test)]
+    mod panel_test {
+        use super::*;
+        use crate::{border::Border, panel::Panel, table::Align};
+
+        #[test]
+        fn styled_content_and_title() {
+            let red = Style::new().fg(Color::Red);
+            let panel = Panel::new(format!("{}\nok", red.style("日本")))
+                .title("a long title")
+                .border(Border::Double)
+                .align(Align::Right)
+                .padding(0);
+
+            assert_eq!(
+                panel.render(),
+                format!(
+                    "╔═ a long title ═╗\n\
+                     ║{:>12}{}║\n\
+                     ║{:>16}║\n\
+                     ╚════════════════╝",
+                    "",
+                    red.style("日本"),
+                    "ok"
+                )
+            );
+        }
+
+        #[test]
+        fn padding_and_no_border() {
+            assert_eq!(
+                Panel::new("x")
+                    .border(Border::Heavy)
+                    .padding(2)
+                    .vertical_padding(1)
+                    .render(),
+                "┏━━━━━┓\n┃     ┃\n┃  x  ┃\n┃     ┃\n┗━━━━━┛"
+            );
+            assert_eq!(
+                Panel::new("a\nbb").title("T").border(Border::None).render(),
+                "T\n a\n bb"
+            );
+        }
+    }
+
+    #[cfg(test)]
+    #[cfg(any(
+        feature = "anstyle",
+        feature = "crossterm",
+        feature = "ratatui",
+        feature = "termcolor"
+    ))]
+    mod interop_test {
+        use super::*;
+
+        #[test]
+        #[cfg(feature = "anstyle")]
+        fn anstyle_round_trip() {
+            let style = Style::new()
+                .fg(Color::Red)
+                .fg_brighten()
+                .bg_rgb(1, 2, 3)
+                .bold()
+                .double_ul();
+
+            let converted = anstyle::Style::from(style);
+            assert_eq!(
+                converted,
+                anstyle::AnsiColor::BrightRed
+                    .on(anstyle::RgbColor(1, 2, 3))
+                    .effects(anstyle::Effects::BOLD | anstyle::Effects::DOUBLE_UNDERLINE)
+            );
+            assert_eq!(Style::from(converted), style);
+            assert_eq!(
+                Style::from(anstyle::Style::from(Style::new().fg_index(42).overline())),
+                Style::new().fg_index(42)
+            );
+
+            let curly = Style::new().underline_style(crate::style::UnderlineStyle::Curly);
+            assert_eq!(
+                anstyle::Style::from(curly),
+                anstyle::Style::new().effects(anstyle::Effects::CURLY_UNDERLINE)
+            );
+            assert_eq!(Style::from(anstyle::Style::from(curly)), curly);
+        }
+
+        #[test]
+        #[cfg(feature = "crossterm")]
+        fn crossterm_round_trip() {
+            use crossterm::style::{Attribute, Color as CtColor, ContentStyle, Stylize};
+
+            let style = Style::new().fg(Color::Red).bg_default().bold().no_italic();
+            let converted = ContentStyle::from(style);
+
+            assert_eq!(converted.foreground_color, Some(CtColor::DarkRed));
+            assert_eq!(converted.background_color, Some(CtColor::Reset));
+            assert!(converted.attributes.has(Attribute::NoItalic));
+            assert_eq!(Style::from(converted), style);
+            assert_eq!(
+                Style::from(ContentStyle::new().white().on_dark_grey()),
+                Style::new()
+                    .fg(Color::White)
+                    .fg_brighten()
+                    .bg(Color::Black)
+                    .bg_brighten()
+            );
+
+            let dotted = Style::new().underline_style(crate::style::UnderlineStyle::Dotted);
+            assert!(
+                ContentStyle::from(dotted)
+                    .attributes
+                    .has(Attribute::Underdotted)
+            );
+            assert_eq!(Style::from(ContentStyle::from(dotted)), dotted);
+        }
+
+        #[test]
+        #[cfg(feature = "ratatui")]
+        fn ratatui_style() {
+            use ratatui::style::{Color as RtColor, Modifier as RtModifier, Style as RtStyle};
+
+            assert_eq!(
+                RtStyle::from(
+                    Style::new()
+                        .fg(Color::Cyan)
+                        .fg_brighten()
+                        .bold()
+                        .no_italic()
+                ),
+                RtStyle::new()
+                    .fg(RtColor::LightCyan)
+                    .add_modifier(RtModifier::BOLD)
+                    .remove_modifier(RtModifier::ITALIC)
+            );
+            assert_eq!(RtColor::from(Color::White), RtColor::Gray);
+            assert_eq!(RtModifier::from(Modifier::NoBold), RtModifier::empty());
+        }
+
+        #[test]
+        #[cfg(all(feature = "ratatui", feature = "markup"))]
+        fn ratatui_markup() {
+            use crate::markup::Markup;
+            use ratatui::text::{Line, Span, Text};
+
+            let markup = Markup::new("a<f r m b>b\n<f #0a0b0c>c</></>").unwrap();
+            let red = Style::new().fg(Color::Red).bold();
+
+            assert_eq!(
+                Line::from(&markup),
+                Line::from(vec![
+                    Span::raw("a"),
+                    Span::styled("b\n", red),
+                    Span::styled("c", Style::new().fg_rgb(10, 11, 12)),
+                ])
+            );
+            assert_eq!(
+                Text::from(&markup),
+                Text::from(vec![
+                    Line::from(vec![Span::raw("a"), Span::styled("b", red)]),
+                    Line::from(vec![Span::styled("c", Style::new().fg_rgb(10, 11, 12))]),
+                ])
+            );
+
+            // Converted from the tree's styles, so off modifiers aren't lost on the way
+            assert_eq!(
+                Line::from(&Markup::new("<m b!i>x</>").unwrap()),
+                Line::from(vec![Span::styled("x", Style::new().bold().no_italic())])
+            );
+        }
+
+        #[test]
+        #[cfg(feature = "termcolor")]
+        fn termcolor_spec() {
+            colored();
+
+            use crate::{spans::StyledString, termcolor::StyledWriter};
+            use termcolor::{Ansi, Color as TcColor, ColorSpec};
+
+            let spec = ColorSpec::from(Style::new().bg(Color::Blue).bg_brighten().italic());
+            assert_eq!(spec.bg(), Some(&TcColor::Blue));
+            assert!(spec.intense() && spec.italic() && !spec.bold());
+
+            let mut w = StyledWriter::new(Ansi::new(Vec::new()));
+            w.write_spans(
+                &StyledString::new()
+                    .with("a", Style::new().fg_index(9))
+                    .with("b", Style::new()),
+            )
+            .unwrap();
+            assert_eq!(w.into_inner().into_inner(), b"\x1b[0m\x1b[38;5;9ma\x1b[0mb");
+        }
+    }
+
+    #[cfg(test)]
+    #[cfg(feature = "tracing")]
+    mod tracing_test {
+        use std::sync::{Arc, Mutex};
+
+        use super::*;
+        use crate::{theme::Theme, tracing::EventFormatter};
+
+        /// Writer sharing its buffer with the test
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn themed_event() {
+            let theme = Theme::new()
+                .with("warning", Style::new().fg(Color::Yellow))
+                .with("field", Style::new().bold());
+            let format = EventFormatter::with_theme(theme.clone()).with_target(false);
+
+            let buffer = Buffer::default();
+            let writer = buffer.clone();
+            let subscriber = tracing_subscriber::fmt()
+                .event_format(format)
+                .with_writer(move || writer.clone())
+                .finish();
+
+            ::tracing::subscriber::with_default(subscriber, || {
+                ::tracing::warn!(code = 3, path = "a b", "disk low");
+                ::tracing::info!("plain");
+            });
+
+            assert_eq!(
+                String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+                format!(
+                    "{} disk low {}=3 {}=\"a b\"\n INFO plain\n",
+                    theme.style("warning", " WARN"),
+                    theme.style("field", "code"),
+                    theme.style("field", "path"),
+                )
+            );
+            assert!(EventFormatter::new().with_spec("debug", "f").is_err());
+        }
+    }
+
+    #[cfg(test)]
+    #[cfg(feature = "log")]
+    mod log_test {
+        use super::*;
+        use crate::{
+            log::{LogFormatter, rfc3339},
+            theme::Theme,
+        };
+
+        #[test]
+        fn themed_record() {
+            let theme = Theme::new().with("warning", Style::new().fg(Color::Yellow));
+            let format = LogFormatter::with_theme(theme.clone()).with_timestamp(false);
+
+            let mut out = Vec::new();
+            format
+                .format(
+                    &mut out,
+                    &::log::Record::builder()
+                        .args(format_args!("disk {}% full", 95))
+                        .level(::log::Level::Warn)
+                        .target("app::disk")
+                        .build(),
+                )
+                .unwrap();
+
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                format!(
+                    "[{} app::disk] disk 95% full\n",
+                    theme.style("warning", "WARN ")
+                )
+            );
+        }
+
+        #[test]
+        fn timestamps() {
+            assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+            assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+            assert_eq!(rfc3339(1_791_979_199), "2026-10-14T11:59:59Z");
+        }
+    }
+
+    #[cfg(test)]
+    mod palette_test {
+        use super::*;
+        use crate::palette::Palette;
+
+        #[test]
+        fn ordered_lookup() {
+            let mut palette = Palette::new()
+                .with("fg", Color::White)
+                .with("accent", Color::Red)
+                .with("muted", Color::Indexed(244));
+
+            assert_eq!(palette.insert("accent", Color::Cyan), Some(Color::Red));
+            assert_eq!(
+                palette.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+                ["fg", "accent", "muted"]
+            );
+            assert_eq!(palette.get_index(1), Some(("accent", Color::Cyan)));
+            assert_eq!(palette.remove("fg"), Some(Color::White));
+            assert_eq!(palette.get("fg"), None);
+            assert_eq!(palette.len(), 2);
+
+            assert_eq!(
+                palette.nearest(Color::RGB(10, 200, 190)),
+                Some(("accent", Color::Cyan))
+            );
+            assert_eq!(
+                palette.nearest(Color::Indexed(243)),
+                Some(("muted", Color::Indexed(244)))
+            );
+            assert_eq!(Palette::new().nearest(Color::Red), None);
+        }
+
+        #[test]
+        fn hashed_colors() {
+            assert_eq!(
+                Color::from_hash("alice"),
+                Color::from_hash(String::from("alice"))
+            );
+            assert_eq!(Color::from_hash(b"alice"), Color::from_hash("alice"));
+            assert_eq!(
+                Color::from_hash(7_u32.to_le_bytes()),
+                Color::from_hash([7, 0, 0, 0])
+            );
+
+            let hues = ["api", "db", "worker-1", "worker-2", "worker-3", "cache"]
+                .map(|key| Color::from_hash(key).to_hsl().0.round() as u32);
+            for (i, hue) in hues.iter().enumerate() {
+                assert!(!hues[..i].contains(hue), "{hues:?}");
+            }
+
+            let palette = Palette::from_iter([("a", Color::Red), ("b", Color::Green)]);
+            let picks = (0..32)
+                .filter_map(|id: u32| palette.pick_for(id.to_le_bytes()))
+                .collect::<Vec<_>>();
+            assert!(picks.contains(&("a", Color::Red)));
+            assert!(picks.contains(&("b", Color::Green)));
+        }
+
+        #[test]
+        #[cfg(feature = "json")]
+        fn json_keeps_order() {
+            let palette: Palette =
+                serde_json::from_str(r##"{"z": "r", "a": "orange", "m": "#010203"}"##).unwrap();
+
+            assert_eq!(
+                palette.iter().collect::<Vec<_>>(),
+                [
+                    ("z", Color::Red),
+                    ("a", Color::from_name("orange").unwrap()),
+                    ("m", Color::RGB(1, 2, 3)),
+                ]
+            );
+            assert_eq!(
+                serde_json::to_string(&palette).unwrap(),
+                r##"{"z":"r","a":"#ffa500","m":"#010203"}"##
+            );
+            assert!(serde_json::from_str::<Palette>(r#"{"bad": "nope"}"#).is_err());
+        }
+    }
+
+    #[cfg(test)]
+    #[cfg(feature = "cli")]
+    mod cli_test {
+        use std::{ffi::OsString, io};
+
+        use clap::error::ErrorKind;
+
+        use crate::cli::{self, command, with_default_subcommand};
+
+        #[test]
+        fn subcommands() {
+            command().debug_assert();
+
+            let parse = |args: &[&str]| command().try_get_matches_from(args);
+
+            let matches = parse(&["style", "style", "hi", "f", "r", "m", "-u"]).unwrap();
+            let (name, args) = matches.subcommand().unwrap();
+            assert_eq!(name, "style");
+            assert_eq!(
+                args.get_many::<String>("spec").unwrap().collect::<Vec<_>>(),
+                ["f", "r", "m", "-u"]
+            );
+
+            let matches = parse(&["style", "--no-color", "convert", "--to", "css", "f=r"]).unwrap();
+            assert!(matches.get_flag("no-color"));
+            let (_, args) = matches.subcommand().unwrap();
+            assert_eq!(args.get_one::<String>("to").unwrap(), "css");
+            assert_eq!(args.get_many::<String>("spec").unwrap().count(), 1);
+
+            let matches = parse(&["style", "style", "--filter", "f", "r", "m", "-u"]).unwrap();
+            let (_, args) = matches.subcommand().unwrap();
+            assert_eq!(
+                args.get_many::<String>("filter")
+                    .unwrap()
+                    .collect::<Vec<_>>(),
+                ["f", "r", "m", "-u"]
+            );
+            assert!(!args.contains_id("text"));
+            assert!(parse(&["style", "style", "--filter"]).is_ok());
+            assert!(parse(&["style", "markup", "--filter", "<f r>{line}</>"]).is_ok());
+
+            // Only the global flag disables colors, not text that looks like it
+            let matches = parse(&["style", "style", "--", "--no-color", "f", "r"]).unwrap();
+            assert!(!matches.get_flag("no-color"));
+            let (_, args) = matches.subcommand().unwrap();
+            assert_eq!(args.get_one::<String>("text").unwrap(), "--no-color");
+
+            let kind = |args: &[&str]| parse(args).unwrap_err().kind();
+            assert_eq!(kind(&["style", "stlye", "x"]), ErrorKind::InvalidSubcommand);
+            assert_eq!(
+                kind(&["style", "preview"]),
+                ErrorKind::MissingRequiredArgument
+            );
+            assert_eq!(
+                kind(&["style", "convert", "--to", "html", "f r"]),
+                ErrorKind::InvalidValue
+            );
+            assert_eq!(
+                kind(&["style", "table", "--border", "nope"]),
+                ErrorKind::InvalidValue
+            );
+        }
+
+        #[test]
+        fn filter_lines() {
+            let input = io::Cursor::new(b"a\nb\xff\r\n\nc".to_vec());
+            let mut out = Vec::new();
+
+            cli::filter_lines(input, &mut out, |line| format!("[{line}]")).unwrap();
+
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                "[a]\n[b\u{fffd}]\n[]\n[c]\n"
+            );
+        }
+
+        #[test]
+        fn default_subcommand() {
+            let rewrite = |args: &[&str]| {
+                with_default_subcommand(args.iter().map(OsString::from))
+                    .into_iter()
+                    .map(|arg| arg.into_string().unwrap())
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                rewrite(&["style", "hi", "f", "r"]),
+                ["style", "style", "hi", "f", "r"]
+            );
+            assert_eq!(
+                rewrite(&["style", "--no-color", "--markup", "<f r>x</>"]),
+                ["style", "--no-color", "markup", "<f r>x</>"]
+            );
+            assert_eq!(
+                rewrite(&["style", "--filter", "f", "r"]),
+                ["style", "style", "--filter", "f", "r"]
+            );
+            assert_eq!(
+                rewrite(&["style", "convert", "f", "r"]),
+                ["style", "convert", "f", "r"]
+            );
+            assert_eq!(rewrite(&["style", "--help"]), ["style", "--help"]);
+            assert_eq!(rewrite(&["style"]), ["style"]);
+
+            let matches = command()
+                .try_get_matches_from(rewrite(&["style", "hi", "--no-color", "f", "r"]))
+                .unwrap();
+            assert_eq!(matches.subcommand_name(), Some("style"));
+        }
+    }
+
+    #[cfg(test)]
+    #[cfg(feature = "term")]
+    mod term_test {
+        use super::*;
+        use crate::term::parse_reply;
+
+        #[test]
+        fn parses_replies() {
+            assert_eq!(
+                parse_reply("\x1b]11;rgb:1e1e/2020/ffff\x07"),
+                Some(Color::RGB(30, 32, 255))
+            );
+            assert_eq!(
+                parse_reply("\x1b]4;1;rgb:cd/0/00\x1b\\"),
+                Some(Color::RGB(205, 0, 0))
+            );
+            assert_eq!(
+                parse_reply("\x1b]10;rgb:8/80/800\x07"),
+                Some(Color::RGB(136, 128, 128))
+            );
+            assert_eq!(parse_reply("\x1b]11;rgb:1e1e/2020\x07"), None);
+            assert_eq!(parse_reply("\x1b]11;rgb:12345/0/0\x07"), None);
+            assert_eq!(parse_reply("\x1b]11;?\x07"), None);
+            assert_eq!(parse_reply("rgb:0/0/0"), None);
+        }
+
+        #[test]
+        fn no_terminal_no_color() {
+            // Tests run without a controlling terminal, or one that stays silent
+            if std::fs::File::open("/dev/tty").is_err() {
+                assert_eq!(crate::term::query_background(), None);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod style_test {
+        use super::*;
+        use cli_styler_spec::__private::{apply_sgr, sgr_params};
+
+        #[test]
+        fn merge() {
+            let base = Style::new().fg(Color::Red).bg(Color::Black).bold();
+            let over = Style::new().fg(Color::Blue).bold().italic();
+
+            assert_eq!(
+                base.merge(&over),
+                Style::new()
+                    .fg(Color::Blue)
+                    .bg(Color::Black)
+                    .bold()
+                    .italic()
+            );
+
+            assert_eq!(base | over, base.merge(&over));
+            assert_eq!(base | Style::new(), base);
+            assert_eq!(Style::new() | base, base);
+        }
+
+        #[test]
+        fn modifier_set() {
+            colored();
+
+            assert_eq!(Style::new().bold().bold(), Style::new().bold());
+            assert_eq!(Style::new().bold().bold().style("x"), "\x1b[1mx\x1b[0m");
+            assert_eq!(
+                Style::new().underline().bold().style("x"),
+                "\x1b[1;4mx\x1b[0m"
+            );
+        }
+
+        #[test]
+        fn modifier_order() {
+            colored();
+
+            assert_eq!(Style::new().italic().bold(), Style::new().bold().italic());
+            assert_eq!(Style::new().bold().reset(), Style::new().reset());
+            assert_eq!(Style::new().bold().reset().style("x"), "\x1b[0mx\x1b[0m");
+            assert_eq!(Style::new().reset().bold().style("x"), "\x1b[0;1mx\x1b[0m");
+            assert_eq!(Style::new_from_cli_spec("m ub").unwrap().to_spec(), "m bu");
+        }
+
+        #[test]
+        #[cfg(feature = "json")]
+        fn modifier_serde() {
+            let style = Style::new().fg(Color::Red).bold().underline();
+            let json = serde_json::to_string(&style).unwrap();
+
+            assert_eq!(
+                json,
+                r#"{"fg":["Red","Fg"],"bg":null,"mdfs":["Bold","Underline"]}"#
+            );
+            assert_eq!(serde_json::from_str::<Style>(&json).unwrap(), style);
+        }
+
+        #[test]
+        fn accessors() {
+            let style = Style::new_from_cli_spec("fb r b 99 m bu").unwrap();
+
+            assert_eq!(style.fg_color(), Some(Color::Red));
+            assert_eq!(style.bg_color(), Some(Color::Indexed(99)));
+            assert!(style.is_bright_fg());
+            assert!(!style.is_bright_bg());
+            assert!(style.has_modifier(Modifier::Bold));
+            assert!(!style.has_modifier(Modifier::Italic));
+            assert_eq!(
+                style.modifiers().collect::<Vec<_>>(),
+                [Modifier::Bold, Modifier::Underline]
+            );
+
+            assert_eq!(Style::new().fg_color(), None);
+        }
+
+        #[test]
+        fn shorthands() {
+            assert_eq!(
+                "error".red().bold().on_yellow().to_string(),
+                Style::new()
+                    .fg(Color::Red)
+                    .bold()
+                    .bg(Color::Yellow)
+                    .style("error")
+            );
+
+            assert_eq!(
+                "hi".cyan().bright().to_string(),
+                Style::new_from_cli_spec("fb c").unwrap().style("hi")
+            );
+        }
+
+        #[test]
+        fn spec_round_trip() {
+            for spec in [
+                "",
+                "f r",
+                "fb w b 99",
+                "bb k m r",
+                "f #0a0b0c b ,128,255 m ilobu",
+            ] {
+                let style = Style::new_from_cli_spec(spec).unwrap();
+                assert_eq!(Style::new_from_cli_spec(style.to_spec()).unwrap(), style);
+            }
+
+            assert_eq!(Style::new().fg_brighten().to_spec(), "fb w");
+            assert_eq!(Style::new().reset().bold().to_spec(), "m rb");
+
+            // Negated modifiers come out as `!x`, which has to parse back
+            let style = Style::new()
+                .fg(Color::Red)
+                .no_bold()
+                .no_underline()
+                .italic();
+            assert_eq!(style.to_spec(), "f r m i!b!u");
+            assert_eq!(Style::new_from_cli_spec(style.to_spec()).unwrap(), style);
+        }
+
+        #[test]
+        fn normalize() {
+            assert_eq!(sgr_params(&Style::new().bold().bold()), "1");
+            assert_eq!(sgr_params(&Style::new().bold().italic().bold()), "1;3");
+
+            // A later modifier wins over the one it overrides
+            assert_eq!(sgr_params(&Style::new().bold().reset()), "0");
+            assert_eq!(sgr_params(&Style::new().bold().dim().no_bold()), "22");
+            assert_eq!(sgr_params(&Style::new().no_underline().double_ul()), "21");
+
+            // Reset comes first and doesn't cancel the colors of the same style
+            assert_eq!(
+                sgr_params(&Style::new().fg(Color::Red).reset().bold()),
+                "0;31;1"
+            );
+            assert_eq!(
+                Style::new().reset().no_italic().fg_default().normalize(),
+                Style::new().reset()
+            );
+
+            let left = Style::new().bold().no_italic();
+            let right = Style::new().italic();
+            assert_eq!(sgr_params(&(left | right)), "1;3");
+            assert_eq!(
+                left.normalize(),
+                Style::new().no_italic().bold().normalize()
+            );
+        }
+
+        #[test]
+        fn validate() {
+            use crate::{error::StylerError, style::StyleWarning};
+
+            assert!(
+                Style::new()
+                    .bold()
+                    .underline()
+                    .fg(Color::Red)
+                    .validate()
+                    .is_empty()
+            );
+            assert!(Style::new().reset().validate().is_empty());
+            assert!(Style::new().hide().validate().is_empty());
+            assert!(
+                Style::new()
+                    .fg(Color::Red)
+                    .bg_brighten()
+                    .bg(Color::Red)
+                    .validate()
+                    .is_empty()
+            );
+
+            assert_eq!(
+                Style::new_from_cli_spec("f r b r m rdbhu")
+                    .unwrap()
+                    .validate(),
+                [
+                    StyleWarning::BoldDim,
+                    StyleWarning::HiddenWithOthers,
+                    StyleWarning::ResetWithOthers,
+                    StyleWarning::SameColors,
+                ]
+            );
+            assert_eq!(
+                Style::new().underline().double_ul().validate(),
+                [StyleWarning::UnderlineDoubleUnderline]
+            );
+
+            assert!(Style::new_from_cli_spec_strict("f r m bu").is_ok());
+            assert!(matches!(
+                Style::new_from_cli_spec_strict("m bd"),
+                Err(StylerError::ConflictingStyle(StyleWarning::BoldDim))
+            ));
+        }
+
+        #[test]
+        fn style_stack() {
+            colored();
+
+            use crate::stream::StyleStack;
+
+            let mut out = StyleStack::new(Vec::new());
+            let red = Style::new().fg(Color::Red);
+
+            out.write("plain ").unwrap();
+            out.push(red);
+            out.push(Style::new().bg(Color::Blue).bold());
+            assert_eq!(out.current(), red.bg(Color::Blue).bold());
+            out.write("a").unwrap();
+            assert_eq!(out.pop(), Some(Style::new().bg(Color::Blue).bold()));
+            out.write("b").unwrap();
+            out.push(Style::new());
+            out.write("c").unwrap();
+            assert_eq!(out.depth(), 2);
+            out.pop();
+            out.pop();
+            assert_eq!(out.pop(), None);
+
+            assert_eq!(
+                String::from_utf8(out.into_inner().unwrap()).unwrap(),
+                "plain \x1b[31;44;1ma\x1b[49;22mbc\x1b[39m"
+            );
+        }
+
+        #[test]
+        fn styled_writer() {
+            colored();
+
+            use std::io::Write;
+
+            use crate::stream::StyledWriter;
+
+            let style = CompiledStyle::new_from_cli_spec("f r").unwrap();
+
+            let mut out = StyledWriter::new(Vec::new(), style.clone());
+            out.write_all(b"a\n\nb").unwrap();
+            out.write_all(b"c\n").unwrap();
+            out.flush().unwrap();
+            out.write_all(b"d").unwrap();
+            assert_eq!(
+                String::from_utf8(out.into_inner().unwrap()).unwrap(),
+                "\x1b[31ma\x1b[0m\n\n\x1b[31mbc\x1b[0m\n\x1b[31md\x1b[0m"
+            );
+
+            let mut buf = Vec::new();
+            {
+                let mut out = StyledWriter::new(&mut buf, style);
+                out.write_all(b"dropped").unwrap();
+            }
+            assert_eq!(buf, b"\x1b[31mdropped\x1b[0m");
+
+            let mut out = StyledWriter::new(Vec::new(), CompiledStyle::from_static(""));
+            out.write_all(b"plain\n").unwrap();
+            assert_eq!(out.get_ref(), b"plain\n");
+        }
+
+        #[test]
+        fn extra_modifiers() {
+            colored();
+
+            let style = Style::new_from_cli_spec("m qfepn").unwrap();
+            assert_eq!(
+                style,
+                Style::new()
+                    .rapid_blink()
+                    .framed()
+                    .encircled()
+                    .superscript()
+                    .subscript()
+            );
+            assert_eq!(sgr_params(&style), "6;51;52;73;74");
+            assert_eq!(
+                [Modifier::NoFramed, Modifier::NoOverline, Modifier::NoScript].map(|m| m as u8),
+                [54, 55, 75]
+            );
+            assert_eq!(style.to_spec(), "m qfepn");
+            assert_eq!("x".superscript().to_string(), "\x1b[73mx\x1b[0m");
+
+            assert_eq!(
+                sgr_params(&Style::new().blink().rapid_blink().no_blink()),
+                "25"
+            );
+            assert_eq!(style.transition_to(&Style::new()), "\x1b[25;54;75m");
+
+            let mut parsed = Style::new();
+            apply_sgr(&mut parsed, "1;6;51;73;54");
+            assert_eq!(parsed, Style::new_from_cli_spec("m bqp").unwrap());
+        }
+
+        #[test]
+        fn underline_styles() {
+            use crate::style::UnderlineStyle;
+
+            let curly = Style::new().underline_style(UnderlineStyle::Curly);
+            assert_eq!(sgr_params(&curly), "4:3");
+            assert_eq!(curly.to_spec(), "m u:curly");
+            assert_eq!(
+                sgr_params(&Style::new_from_cli_spec("m u:DASHED").unwrap()),
+                "4:5"
+            );
+            assert!(Style::new_from_cli_spec("m u:wavy").is_err());
+
+            // The shape and the underline modifiers replace each other
+            assert_eq!(sgr_params(&curly.underline()), "4");
+            assert_eq!(
+                sgr_params(
+                    &Style::new()
+                        .double_ul()
+                        .underline_style(UnderlineStyle::Single)
+                ),
+                "4:1"
+            );
+            assert_eq!(
+                sgr_params(&Style::new_from_cli_spec("m u:curly m u").unwrap()),
+                "4"
+            );
+            assert!(curly.no_underline().normalize() == Style::new().no_underline());
+
+            // Merging follows the same rule as adding them one by one
+            assert_eq!(sgr_params(&curly.merge(&Style::new().no_underline())), "24");
+            assert_eq!(sgr_params(&curly.merge(&Style::new().underline())), "4");
+            assert_eq!(sgr_params(&curly.merge(&Style::new().reset())), "0");
+            assert_eq!(sgr_params(&Style::new().underline().merge(&curly)), "4:3");
+
+            assert_eq!(curly.transition_to(&Style::new()), "\x1b[24m");
+            assert_eq!(
+                Style::new().underline().transition_to(&curly),
+                "\x1b[24;4:3m"
+            );
+            assert_eq!(curly.transition_to(&Style::new().underline()), "\x1b[4m");
+
+            let mut parsed = Style::new();
+            apply_sgr(&mut parsed, "1;4:4");
+            assert_eq!(
+                parsed,
+                Style::new().bold().underline_style(UnderlineStyle::Dotted)
+            );
+            apply_sgr(&mut parsed, "4:0");
+            assert_eq!(parsed, Style::new().bold());
+        }
+
+        #[test]
+        fn lerp() {
+            let from = Style::new().fg(Color::Red).bg_rgb(0, 0, 0).italic();
+            let to = Style::new()
+                .fg(Color::Blue)
+                .fg_brighten()
+                .bg_rgb(100, 200, 50)
+                .bold();
+
+            assert_eq!(from.lerp(&to, -1.0), from);
+            assert_eq!(from.lerp(&to, 1.0), to);
+            assert_eq!(
+                from.lerp(&to, 0.5),
+                Style::new().fg_rgb(149, 46, 128).bg_rgb(50, 100, 25).bold()
+            );
+            assert!(from.lerp(&to, 0.49).has_modifier(Modifier::Italic));
+
+            // Colors set on one side only snap with the modifiers
+            let plain = Style::new().underline();
+            assert_eq!(from.lerp(&plain, 0.4).fg_color(), Some(Color::Red));
+            assert_eq!(from.lerp(&plain, 0.6), plain);
+        }
+
+        #[test]
+        fn raw_sgr() {
+            let style = Style::new().fg(Color::Red).raw_sgr("60").unwrap();
+
+            assert_eq!(sgr_params(&style), "31;60");
+            assert_eq!(style.raw_params(), [60]);
+            assert!(!Style::new().raw_sgr("4;58,2").unwrap().is_empty());
+            assert!(Style::new().raw_sgr("").is_err());
+            assert!(Style::new().raw_sgr("256").is_err());
+            assert!(Style::new().raw_sgr("1;2;3;4;5;6;7;8;9").is_err());
+
+            let spec = Style::new_from_cli_spec("f r m raw:60").unwrap();
+            assert_eq!(spec, style);
+            assert_eq!(spec.to_spec(), "f r m raw:60");
+            assert_eq!(Style::new_from_cli_spec(spec.to_spec()).unwrap(), spec);
+            assert!(Style::new_from_cli_spec("m raw:x").is_err());
+
+            // Merged raw parameters replace each other, and can only be removed by a reset
+            let other = Style::new().raw_sgr("61").unwrap();
+            assert_eq!(sgr_params(&(style | other)), "31;61");
+            assert_eq!(
+                style.transition_to(&Style::new().fg(Color::Red)),
+                "\x1b[0;31m"
+            );
+            assert_eq!(Style::new().transition_to(&other), "\x1b[61m");
+
+            // The reset must not take the modifiers of the next style with it
+            let bold = Style::new().bold();
+            assert_eq!(other.bold().transition_to(&bold), "\x1b[0;1m");
+            assert_eq!(other.transition_to(&Style::new()), "\x1b[0m");
+        }
+
+        #[test]
+        fn conditional() {
+            colored();
+
+            use crate::style::Stylize;
+
+            let style = Style::new().bold();
+            let compiled = style.compile();
+
+            assert_eq!(style.style_if(true, "hi"), style.style("hi"));
+            assert_eq!(style.style_if(false, "hi"), "hi");
+            assert_eq!(compiled.style_if(true, "hi"), "\x1b[1mhi\x1b[0m");
+            assert_eq!("hi".apply_if(false, &compiled), "hi");
+            assert_eq!("hi".apply_if(true, &compiled), "hi".apply(&style));
+        }
+
+        #[test]
+        fn clear_parts() {
+            let base = Style::new_from_cli_spec("f r b b m u:curly")
+                .unwrap()
+                .bold();
+
+            assert_eq!(base.clear_fg().fg_color(), None);
+            assert_eq!(base.clear_bg().bg_color(), None);
+            assert!(base.clear_fg().clear_bg().clear_modifiers().is_empty());
+            assert_eq!(
+                base.without(Modifier::Underline),
+                Style::new().fg(Color::Red).bg(Color::Blue).bold()
+            );
+            assert_eq!(base.without(Modifier::Italic), base);
+            assert_eq!(
+                Style::new().bold().no_italic().without(Modifier::NoItalic),
+                Style::new().bold()
+            );
+        }
+
+        #[test]
+        fn collect_parts() {
+            use crate::style::ClrTarget;
+
+            let mut style: Style = [Modifier::Bold, Modifier::Underline, Modifier::NoBold]
+                .into_iter()
+                .collect();
+            assert_eq!(style, Style::new().underline().no_bold());
+
+            style.extend([
+                (Color::Red, ClrTarget::Fg),
+                (Color::Indexed(4), ClrTarget::Bg),
+            ]);
+            style.extend([(Color::Green, ClrTarget::Fg)]);
+            style.extend([Modifier::Reset]);
+            assert_eq!(style, Style::new().fg(Color::Green).bg_index(4).reset());
+        }
+
+        #[test]
+        fn color_names() {
+            assert_eq!(Color::RGB(1, 2, 3).distance(Color::RGB(1, 2, 3)), 0.0);
+            assert!((Color::Black.distance(Color::RGB(255, 255, 255)) - 764.8).abs() < 0.1);
+            assert!(
+                Color::RGB(255, 0, 0).distance(Color::RGB(255, 60, 0))
+                    > Color::RGB(255, 0, 0).distance(Color::RGB(255, 0, 60))
+            );
+
+            assert_eq!(Color::RGB(0, 0, 0).approximate_name(), "black");
+            assert_eq!(
+                Color::RGB(100, 149, 230).approximate_name(),
+                "cornflowerblue"
+            );
+            assert_eq!(Color::Indexed(244).approximate_name(), "gray");
+            assert_eq!(Color::TerminalDefault.approximate_name(), "default");
+        }
+
+        #[test]
+        fn alpha_colors() {
+            assert_eq!(
+                "#f808".parse::<Color>().unwrap(),
+                Color::RGBA(255, 136, 0, 136)
+            );
+            assert_eq!(
+                "0x11223344".parse::<Color>().unwrap(),
+                Color::RGBA(17, 34, 51, 68)
+            );
+            assert_eq!(
+                Style::new().fg(Color::RGBA(17, 34, 51, 68)).to_spec(),
+                "f #11223344"
+            );
+            assert_eq!(Color::RGBA(1, 2, 3, 4).alpha(), 4);
+            assert_eq!(Color::Red.alpha(), 255);
+
+            let white = Color::RGB(255, 255, 255);
+            assert_eq!(
+                Color::RGBA(0, 0, 0, 255).composite_over(white),
+                Color::RGB(0, 0, 0)
+            );
+            assert_eq!(Color::RGBA(0, 0, 0, 0).composite_over(white), white);
+
+            // Rendered without its alpha
+            assert_eq!(
+                Style::new().fg(Color::RGBA(1, 2, 3, 4)).style("x"),
+                Style::new().fg_rgb(1, 2, 3).style("x")
+            );
+        }
+
+        #[test]
+        fn hsl_hsv() {
+            assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::RGB(255, 0, 0));
+            assert_eq!(Color::from_hsl(210.0, 1.0, 0.5), Color::RGB(0, 128, 255));
+            assert_eq!(Color::from_hsl(-120.0, 1.0, 0.5), Color::RGB(0, 0, 255));
+            assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::RGB(0, 255, 0));
+            assert_eq!(Color::from_hsv(0.0, 0.0, 0.5), Color::RGB(128, 128, 128));
+
+            let (h, s, l) = Color::RGB(0, 128, 255).to_hsl();
+            assert!((h - 209.9).abs() < 0.1 && (s - 1.0).abs() < 1e-3 && (l - 0.5).abs() < 1e-3);
+
+            let (h, s, v) = Color::RGB(255, 255, 0).to_hsv();
+            assert_eq!((h, s, v), (60.0, 1.0, 1.0));
+
+            assert_eq!(Color::Red.to_rgb(), (205, 0, 0));
+            assert_eq!(Color::Indexed(196).to_rgb(), (255, 0, 0));
+            assert_eq!(Color::Indexed(232).to_rgb(), (8, 8, 8));
+        }
+
+        #[test]
+        fn lighten_darken() {
+            let base = Color::RGB(64, 159, 191);
+            let close = |a: f32, b: f32| (a - b).abs() < 0.01;
+
+            assert!(close(base.lighten(0.2).to_hsl().2, 0.7));
+            assert!(close(base.darken(0.2).to_hsl().2, 0.3));
+            assert!(close(base.saturate(0.3).to_hsl().1, 0.8));
+            assert_eq!(base.desaturate(1.0), Color::RGB(128, 128, 128));
+            assert_eq!(Color::Red.lighten(1.0), Color::RGB(255, 255, 255));
+            assert!(Color::Indexed(21).darken(0.1).is_rgb());
+        }
+
+        #[test]
+        fn mix() {
+            let black = Color::RGB(0, 0, 0);
+            let white = Color::RGB(255, 255, 255);
+
+            assert_eq!(black.mix(white, 0.0), black);
+            assert_eq!(black.mix(white, 1.0), white);
+            assert_eq!(black.mix(white, 0.5), Color::RGB(128, 128, 128));
+            assert_eq!(black.mix(white, 2.0), white);
+            assert_eq!(
+                Color::RGB(255, 0, 0).mix(Color::RGB(0, 0, 255), 0.25),
+                Color::RGB(191, 0, 64)
+            );
+        }
+
+        #[test]
+        fn contrast() {
+            assert_eq!(Color::RGB(255, 255, 0).contrast_fg(), Color::RGB(0, 0, 0));
+            assert_eq!(
+                Color::RGB(0, 0, 128).contrast_fg(),
+                Color::RGB(255, 255, 255)
+            );
+            assert!(
+                (Color::RGB(0, 0, 0).contrast_ratio(Color::RGB(255, 255, 255)) - 21.0).abs() < 0.01
+            );
+
+            let unreadable = Style::new().fg_rgb(90, 90, 90).bg_rgb(70, 70, 70).bold();
+            let fixed = unreadable.ensure_contrast(4.5);
+
+            assert!(
+                fixed
+                    .fg_color()
+                    .unwrap()
+                    .contrast_ratio(Color::RGB(70, 70, 70))
+                    >= 4.5
+            );
+            assert_eq!(fixed.bg_color(), unreadable.bg_color());
+            assert!(fixed.has_modifier(Modifier::Bold));
+
+            let readable = Style::new().fg_rgb(255, 255, 255).bg_rgb(0, 0, 0);
+            assert_eq!(readable.ensure_contrast(4.5), readable);
+            assert_eq!(
+                Style::new().fg(Color::Red).ensure_contrast(21.0),
+                Style::new().fg(Color::Red)
+            );
+        }
+
+        #[test]
+        fn quantize() {
+            assert_eq!(Color::RGB(255, 0, 0).quantize_to_256(), Color::Indexed(196));
+            assert_eq!(Color::RGB(0, 95, 135).quantize_to_256(), Color::Indexed(24));
+            assert_eq!(
+                Color::RGB(128, 128, 128).quantize_to_256(),
+                Color::Indexed(244)
+            );
+            assert_eq!(Color::Red.quantize_to_256(), Color::Red);
+
+            assert_eq!(Color::RGB(250, 5, 5).quantize_to_16(), (Color::Red, true));
+            assert_eq!(Color::RGB(0, 0, 200).quantize_to_16(), (Color::Blue, false));
+            assert_eq!(Color::Indexed(11).quantize_to_16(), (Color::Yellow, true));
+            assert_eq!(Color::Cyan.quantize_to_16(), (Color::Cyan, false));
+
+            let style = Style::new().fg_rgb(250, 5, 5).bg_index(4).bold();
+            assert_eq!(style.adapt(ColorLevel::TrueColor), style);
+            assert_eq!(
+                style.adapt(ColorLevel::Ansi256),
+                Style::new().fg_index(196).bg_index(4).bold()
+            );
+            assert_eq!(
+                style.adapt(ColorLevel::Ansi16),
+                Style::new()
+                    .fg(Color::Red)
+                    .fg_brighten()
+                    .bg(Color::Blue)
+                    .bold()
+            );
+            assert_eq!(style.adapt(ColorLevel::None), Style::new().bold());
+        }
+
+        #[test]
+        fn off_modifiers() {
+            colored();
+
+            assert_eq!(
+                Style::new().no_bold().no_underline().style("x"),
+                "\x1b[22;24mx\x1b[0m"
+            );
+            assert_eq!(Modifier::Dim.negate(), Some(Modifier::NoBold));
+            assert!(Modifier::NoStrike.is_off());
+            assert!(!Modifier::Reset.is_off());
+            assert_eq!(Style::new().italic().no_overline().to_spec(), "m i!o");
+        }
+
+        #[test]
+        fn default_colors() {
+            colored();
+
+            let style = Style::new().fg_default().bg_default().bold();
+
+            assert_eq!(style.style("x"), "\x1b[39;49;1mx\x1b[0m");
+            assert_eq!(
+                style,
+                Style::new_from_cli_spec("f default b default m b").unwrap()
+            );
+            assert_eq!(Style::new_from_cli_spec(style.to_spec()).unwrap(), style);
+            assert_eq!(style.adapt(ColorLevel::Ansi16), style);
+        }
+
+        #[test]
+        fn transitions() {
+            let plain = Style::new();
+            let bold_dim = Style::new().bold().dim();
+            let dim = Style::new().dim();
+
+            assert_eq!(plain.transition_to(&plain), "");
+            assert_eq!(plain.transition_to(&bold_dim), "\x1b[1;2m");
+            assert_eq!(bold_dim.transition_to(&dim), "\x1b[22;2m");
+            assert_eq!(
+                Style::new()
+                    .fg(Color::Red)
+                    .transition_to(&Style::new().bg(Color::Blue)),
+                "\x1b[39;44m"
+            );
+            assert_eq!(
+                Style::new().underline().double_ul().transition_to(&plain),
+                "\x1b[24m"
+            );
+            assert_eq!(
+                bold_dim.transition_to(&Style::new().reset().italic()),
+                "\x1b[0;3m"
+            );
+        }
+
+        #[test]
+        fn writers() {
+            let style = Style::new().fg(Color::Red).underline();
+
+            let mut io_out = Vec::new();
+            style.write_styled(&mut io_out, "Hi").unwrap();
+            assert_eq!(io_out, style.style("Hi").into_bytes());
+
+            let mut fmt_out = String::new();
+            style
+                .compile()
+                .write_styled_fmt(&mut fmt_out, "Hi")
+                .unwrap();
+            assert_eq!(fmt_out, style.style("Hi"));
+        }
+
+        #[test]
+        fn const_styles() {
+            colored();
+
+            const HEADER: Style = Style::new().fg_rgb(1, 2, 3).bg_index(4).bold().no_italic();
+            const RESET: Style = Style::new().reset().bold();
+            static PLAIN: CompiledStyle = CompiledStyle::from_static("");
+
+            assert_eq!(
+                HEADER,
+                Style::new_from_cli_spec("f 1,2,3 b 4 m b")
+                    .unwrap()
+                    .no_italic()
+            );
+            assert_eq!(RESET.style("x"), "\x1b[0;1mx\x1b[0m");
+            assert_eq!(PLAIN, Style::new().compile());
+            assert_eq!(PLAIN.style("x"), "x");
+            assert_eq!((PLAIN.prefix(), PLAIN.suffix()), ("", ""));
+
+            let compiled = HEADER.compile();
+            assert_eq!(
+                format!("{}x{}", compiled.prefix(), compiled.suffix()),
+                compiled.style("x")
+            );
+
+            // `from_static` takes the parameters, not the whole sequence
+            static ERROR: CompiledStyle = CompiledStyle::from_static("31;1");
+            assert_eq!(ERROR, Style::new().fg(Color::Red).bold().compile());
+            assert_eq!(ERROR.style("x"), "\x1b[31;1mx\x1b[0m");
+            assert_eq!(ERROR.prefix(), "\x1b[31;1m");
+        }
+
+        #[test]
+        #[cfg(feature = "json")]
+        fn compiled_serde() {
+            let compiled = Style::new().fg(Color::Red).bold().compile();
+            let json = serde_json::to_string(&compiled).unwrap();
+
+            assert_eq!(json, r#""31;1""#);
+            assert_eq!(
+                serde_json::from_str::<CompiledStyle>(&json).unwrap(),
+                compiled
+            );
+        }
+
+        #[test]
+        fn map_keys() {
+            use std::collections::{BTreeSet, HashMap};
+
+            let red = Style::new().fg(Color::Red);
+            let mut cache = HashMap::new();
+            cache.insert(red, red.compile());
+            cache.insert(Style::new_from_cli_spec("f r").unwrap(), red.compile());
+            assert_eq!(cache.len(), 1);
+            assert_eq!(cache[&red].style("x"), red.style("x"));
+
+            let ordered = [Modifier::Bold, Modifier::Reset, Modifier::Bold]
+                .into_iter()
+                .collect::<BTreeSet<_>>();
+            assert_eq!(
+                ordered.into_iter().collect::<Vec<_>>(),
+                [Modifier::Reset, Modifier::Bold]
+            );
+            assert!(Color::Black < Color::Indexed(0));
+            assert!(
+                BTreeSet::from([red.compile(), Style::new().compile()]).contains(&red.compile())
+            );
+        }
     }
 }
@@ -0,0 +1,139 @@
+//! [`log`](::log) record formatting themed with a [`Theme`], ready to plug into `env_logger`
+//!
+//! [`LogFormatter::into_fn`] gives the closure `env_logger::Builder::format` expects. The
+//! formatter writes its own escapes, so `env_logger`'s `write_style` setting doesn't apply
+//! to it; turn colors off with [`caps::set_color_choice`](crate::caps::set_color_choice).
+
+use std::{
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ::log::Record;
+
+use crate::{error::StylerError, theme::Theme};
+
+/// Formats records as `[timestamp LEVEL target] message`, styled by theme entries
+///
+/// The entries used are `error`, `warning`, `info`, `debug` and `trace` for the
+/// levels, `timestamp` and `target` (usually the module path). Missing entries
+/// leave that part unstyled.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::log::LogFormatter;
+///
+/// let format = LogFormatter::new().with_spec("target", "f c").unwrap();
+///
+/// env_logger::Builder::new()
+///     .filter_level(log::LevelFilter::Info)
+///     .format(format.into_fn())
+///     .init();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogFormatter {
+    /// Styles of the level, timestamp and target
+    theme: Theme,
+    /// Whether to print a UTC timestamp
+    timestamp: bool,
+    /// Whether to print the record target
+    target: bool,
+}
+
+impl Default for LogFormatter {
+    fn default() -> Self {
+        Self {
+            theme: Theme::logging(),
+            timestamp: true,
+            target: true,
+        }
+    }
+}
+
+impl LogFormatter {
+    /// Creates a formatter with the [logging theme](Theme::logging)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a formatter using only the entries of `theme`
+    pub fn with_theme(theme: Theme) -> Self {
+        Self {
+            theme,
+            ..Self::default()
+        }
+    }
+
+    /// Set the style of one entry from a spec
+    pub fn with_spec(mut self, name: &str, spec: impl AsRef<str>) -> Result<Self, StylerError> {
+        self.theme = self.theme.with_spec(name, spec)?;
+        Ok(self)
+    }
+
+    /// Set whether a UTC timestamp is printed
+    pub fn with_timestamp(mut self, timestamp: bool) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set whether the record target is printed
+    pub fn with_target(mut self, target: bool) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Write one formatted record, including the trailing newline
+    pub fn format<W: io::Write + ?Sized>(&self, w: &mut W, record: &Record<'_>) -> io::Result<()> {
+        let mut header = Vec::new();
+
+        if self.timestamp {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs());
+            header.push(self.theme.style("timestamp", rfc3339(secs)));
+        }
+
+        let level = record.level();
+        header.push(
+            self.theme
+                .style_level(level.as_str(), format!("{level:<5}")),
+        );
+
+        if self.target {
+            header.push(self.theme.style("target", record.target()));
+        }
+
+        writeln!(w, "[{}] {}", header.join(" "), record.args())
+    }
+
+    /// Turn the formatter into a closure for [`env_logger::Builder::format`]
+    pub fn into_fn(
+        self,
+    ) -> impl Fn(&mut env_logger::fmt::Formatter, &Record<'_>) -> io::Result<()> + Sync + Send + 'static
+    {
+        move |w, record| self.format(w, record)
+    }
+}
+
+/// Format seconds since the UNIX epoch as an RFC 3339 UTC timestamp
+pub(crate) fn rfc3339(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Days to civil date, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
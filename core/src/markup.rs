@@ -1,103 +1,1551 @@
+use std::{borrow::Cow, collections::HashMap, fmt, fs::File, io, ops::Range, path::Path};
+
+use cli_styler_spec::__private::{
+    self, COMMENT, COMMENT_END, IF, Mk, RAW, RAW_END, is_tag_char, shortcoded, tokenize,
+    tokenize_lenient,
+};
+use unicode_width::UnicodeWidthStr;
+
 use crate::{
-    error::ParsingError,
-    parser::{Mk, ParsingMode, Token, tokenize},
-    style::{CompiledStyle, Stylable, Style},
+    caps::{self, Condition},
+    error::{Limit, ParsingError, StylerError},
+    parser::{Dialect, Token},
+    style::{Color, ColorLevel, CompiledStyle, ParseOptions, Shortcodes, Style},
+    theme::Theme,
 };
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "syntect")]
+use cli_styler_spec::__private::{CODE, CODE_END, argument_of};
+
 /// Markup AST
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(clippy::missing_docs_in_private_items)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 enum AstTk {
-    Text(String),
+    /// Text with the byte range of the markup it was parsed from
+    Text(String, Range<usize>),
+    /// Text rendered as written, from a `<raw>` section or code, with its byte range
+    Raw(String, Range<usize>),
     Tree(Markup),
 }
 
 /// Markup Tree parent struct
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(clippy::missing_docs_in_private_items)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Markup {
-    /// Style portion
-    st: CompiledStyle,
+    /// Style of the tag, compiled when rendering
+    style: Style,
+    /// Condition of an `<if ...>` section, which has the style of the tag around it
+    when: Option<Condition>,
     children: Vec<AstTk>,
 }
 
 impl Markup {
+    /// Start building a [`Markup`] in code, see [`MarkupBuilder`]
+    pub fn builder() -> MarkupBuilder {
+        MarkupBuilder::new()
+    }
+
     /// Parse markup text and return a new [`Markup`] struct.
+    ///
+    /// Between `<raw>` and `</raw>` nothing is parsed, for code snippets and paths.
+    /// Comments (`<!-- note -->`) are skipped. `<include name>` is only allowed by
+    /// [`Markup::new_with_fragments`]. The content of `<if truecolor>`,
+    /// `<if ansi256>`, `<if color>` or `<if no-color>` (closed by `</if>` or `</>`) is
+    /// only rendered when the terminal has that capability, see [`Condition`]. With
+    /// the `syntect` feature, `<code rust>...</code>` is highlighted like raw text in
+    /// that language, see [`crate::highlight`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let markup = Markup::new(r"<m b>path:</> <raw>C:\<dir>\</raw>").unwrap();
+    /// assert_eq!(markup.render_plain(), r"path: C:\<dir>\");
+    /// ```
     pub fn new(s: impl AsRef<str>) -> Result<Self, ParsingError> {
-        Self::markup_parser(s, Mk)
+        Self::markup_parser(s, &Mk, None, &ParseOptions::new())
+    }
+
+    /// Parse markup text whose tag specs are parsed with non-default options, see [`ParseOptions`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{markup::Markup, prelude::*, style::ParseOptions};
+    ///
+    /// let opts = ParseOptions::new().with_alias("brand", Color::RGB(0x12, 0x34, 0x56));
+    /// let markup = Markup::new_with_options("<f brand>cli-styler</>", &opts).unwrap();
+    ///
+    /// assert_eq!(markup.render(), Style::new().fg_rgb(0x12, 0x34, 0x56).style("cli-styler"));
+    /// ```
+    pub fn new_with_options(s: impl AsRef<str>, opts: &ParseOptions) -> Result<Self, ParsingError> {
+        Self::markup_parser(s, &Mk, None, opts)
+    }
+
+    /// Parse markup text whose tag specs are written in a custom [`Dialect`]
+    pub fn new_with_dialect(
+        s: impl AsRef<str>,
+        dialect: &dyn Dialect,
+        opts: &ParseOptions,
+    ) -> Result<Self, ParsingError> {
+        Self::markup_parser(s, dialect, None, opts)
+    }
+
+    /// Parse markup text whose tags may name entries of `theme` (`<error>failed</error>`).
+    ///
+    /// Tags which aren't theme entries are parsed as regular specs, a single unknown
+    /// name is a [`ParsingError::UnknownTag`] suggesting similar entries.
+    pub fn new_with_theme(s: impl AsRef<str>, theme: &Theme) -> Result<Self, ParsingError> {
+        Self::markup_parser(s, &Mk, Some(theme), &ParseOptions::new())
+    }
+
+    /// Parse markup text whose `<include name>` tags are replaced by the named `fragments`
+    ///
+    /// Text at the top of a fragment keeps the style of the tag around the include.
+    /// A name missing from `fragments` is a [`ParsingError::UnknownFragment`], any other
+    /// constructor rejects includes that way.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::{Fragments, Markup};
+    ///
+    /// let fragments = Fragments::new().with("ok", Markup::new("<f g>✔</> done").unwrap());
+    /// let src = "build <include ok>, <m b>test <include ok></>";
+    /// let markup = Markup::new_with_fragments(src, &fragments).unwrap();
+    ///
+    /// assert_eq!(markup.render_plain(), "build ✔ done, test ✔ done");
+    /// ```
+    pub fn new_with_fragments(
+        s: impl AsRef<str>,
+        fragments: &Fragments,
+    ) -> Result<Self, ParsingError> {
+        let tokens = tokenize(s, &Mk, None, &ParseOptions::new())?;
+        Self::from_tokens(tokens, Some(fragments))
+    }
+
+    /// Parse markup read from `reader`, without reading all of it into a `String` first
+    ///
+    /// The input is read in chunks and tokenized as it arrives, only the text after
+    /// the last complete tag is held back. Fails with [`StylerError::Io`] if reading
+    /// fails or the input isn't UTF-8, with [`StylerError::ParsingError`] if it isn't
+    /// valid markup.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let markup = Markup::from_reader("<f r>error:</> disk full".as_bytes()).unwrap();
+    /// assert_eq!(markup, Markup::new("<f r>error:</> disk full").unwrap());
+    /// ```
+    pub fn from_reader(mut reader: impl io::Read) -> Result<Self, StylerError> {
+        let mut stream = MarkupStream::new();
+        let mut tokens = Vec::new();
+        let mut buf = vec![0; READ_CHUNK];
+        // Input read so far which isn't tokenized yet, it may end inside a char
+        let mut bytes = Vec::new();
+
+        loop {
+            let read = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(StylerError::Io(e)),
+            };
+            bytes.extend_from_slice(&buf[..read]);
+
+            let chunk = match std::str::from_utf8(&bytes) {
+                Ok(chunk) => chunk,
+                // Only a char split across reads may be incomplete
+                Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()])
+                    .expect("the input is valid up to there"),
+                Err(e) => return Err(StylerError::Io(invalid_utf8(e))),
+            };
+            let valid = chunk.len();
+
+            tokens.extend(
+                stream
+                    .next_tokens(chunk, true)
+                    .map_err(StylerError::ParsingError)?,
+            );
+            bytes.drain(..valid);
+        }
+
+        if let Err(e) = std::str::from_utf8(&bytes) {
+            return Err(StylerError::Io(invalid_utf8(e)));
+        }
+
+        tokens.extend(stream.last_tokens().map_err(StylerError::ParsingError)?);
+        Self::from_tokens(tokens, None).map_err(StylerError::ParsingError)
+    }
+
+    /// Parse the markup file at `path`, see [`Markup::from_reader`]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, StylerError> {
+        Self::from_reader(File::open(path).map_err(StylerError::Io)?)
+    }
+
+    /// Parse markup whose text may also use markdown-like emphasis, see [`MarkdownStyles`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::{MarkdownStyles, Markup};
+    ///
+    /// let help = Markup::new_markdown("Run `cargo test` **before** pushing", &MarkdownStyles::new());
+    /// assert_eq!(help.unwrap().render_plain(), "Run cargo test before pushing");
+    /// ```
+    pub fn new_markdown(s: impl AsRef<str>, styles: &MarkdownStyles) -> Result<Self, ParsingError> {
+        let s = s.as_ref();
+        let mut tokens = Vec::new();
+
+        for (token, span) in tokenize(s, &Mk, None, &ParseOptions::new())? {
+            match token {
+                Token::Text(text) => push_markdown(&text, &span, styles, &mut tokens),
+                token => tokens.push((token, span)),
+            }
+        }
+
+        Self::from_tokens(tokens, None)
+    }
+
+    /// Parse markup from untrusted sources, recovering from errors instead of failing
+    ///
+    /// Invalid tags are kept as literal text, unclosed tags are closed at the end and
+    /// input over the length limit is cut off. Returns the best-effort markup along
+    /// with every error that was recovered from.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let (markup, warnings) = Markup::new_lenient("<f r>1 <3 you <m z>!");
+    ///
+    /// assert_eq!(markup.render_plain(), "1 <3 you <m z>!");
+    /// assert_eq!(warnings.len(), 3);
+    /// ```
+    pub fn new_lenient(s: impl AsRef<str>) -> (Self, Vec<ParsingError>) {
+        Self::new_lenient_with(s, None, &ParseOptions::new())
+    }
+
+    /// Parse markup leniently with an optional theme and non-default options, see [`Markup::new_lenient`]
+    pub fn new_lenient_with(
+        s: impl AsRef<str>,
+        theme: Option<&Theme>,
+        opts: &ParseOptions,
+    ) -> (Self, Vec<ParsingError>) {
+        let s = s.as_ref();
+        let (tokens, mut warnings) = tokenize_lenient(s, &Mk, theme, opts);
+
+        // There are no fragments to include, the tags are kept as text
+        let tokens = tokens
+            .into_iter()
+            .map(|(token, span)| match token {
+                Token::Include(name) => {
+                    warnings.push(ParsingError::UnknownFragment(name, span.clone()));
+                    (Token::Text(s[span.clone()].to_string()), span)
+                }
+                token => (token, span),
+            })
+            .collect();
+
+        let markup = Self::from_tokens(tokens, None).expect("includes are kept as text");
+        (markup, warnings)
     }
 
     /// Parse markup text with CLI mode and return a new [`Markup`] struct.
     #[cfg(feature = "cli")]
     pub(crate) fn new_cli(s: impl AsRef<str>) -> Result<Self, ParsingError> {
-        use crate::parser::Cli;
+        use cli_styler_spec::__private::Cli;
 
-        Self::markup_parser(s, Cli)
+        Self::markup_parser(s, &Cli, None, &ParseOptions::new())
+    }
+
+    /// Add the nodes of `other` after the ones of this markup
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let mut markup = Markup::new("<f r>error:</>").unwrap();
+    /// markup.append(Markup::new(" <m b>disk full</>").unwrap());
+    ///
+    /// assert_eq!(markup.render(), Markup::new("<f r>error:</> <m b>disk full</>").unwrap().render());
+    /// ```
+    pub fn append(&mut self, other: Markup) {
+        // Its text keeps its own style
+        if other.style == self.style && other.when.is_none() {
+            self.children.extend(other.children);
+        } else {
+            self.children.push(AstTk::Tree(other));
+        }
+    }
+
+    /// Join `markups` into one, in order, see [`Markup::append`]
+    pub fn concat(markups: impl IntoIterator<Item = Markup>) -> Self {
+        markups
+            .into_iter()
+            .fold(MarkupBuilder::new().build(), |mut joined, markup| {
+                joined.append(markup);
+                joined
+            })
     }
 
     /// Collect and merge the input into the final output
-    pub fn render(self) -> String {
+    ///
+    /// The markup isn't consumed, so parsed markup can be kept and rendered repeatedly.
+    ///
+    /// `<if ...>` sections are evaluated against the detected color level, or
+    /// [`ColorLevel::None`] when colors are disabled.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{caps::{self, ColorChoice}, markup::Markup};
+    ///
+    /// let status = Markup::new("<f g><if color>●</if><if no-color>[ok]</if></> done").unwrap();
+    ///
+    /// caps::set_color_choice(ColorChoice::Never);
+    /// assert_eq!(status.render(), "[ok] done");
+    /// # caps::set_color_choice(ColorChoice::Auto);
+    /// ```
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        // Writing into a `String` never fails
+        let _ = self.write_styled_fmt(&mut output);
+        output
+    }
+
+    /// Like [`Markup::render`], but styles every line of the text nodes separately
+    ///
+    /// See [`Stylable::style_multiline`](crate::style::Stylable::style_multiline).
+    pub fn render_multiline(&self) -> String {
+        let mut output = String::new();
+        // Writing into a `String` never fails
+        let _ = self.write_nodes_fmt(&mut output, true, __private::render_level());
+        output
+    }
+
+    /// Like [`Markup::render`], with `{name}` placeholders in the text replaced by `vars`
+    ///
+    /// `{{` and `}}` render literal braces, placeholders missing from `vars` are kept
+    /// as written. Tags are parsed once, so a template can be filled per event.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let template = Markup::new("<f r>error:</> {msg} {{code}}").unwrap();
+    /// let vars = HashMap::from([("msg", "disk full")]);
+    ///
+    /// assert_eq!(
+    ///     template.render_with(&vars),
+    ///     Markup::new("<f r>error:</> disk full {code}").unwrap().render()
+    /// );
+    /// ```
+    pub fn render_with(&self, vars: &HashMap<&str, &str>) -> String {
+        let mut output = String::new();
+        // Writing into a `String` never fails
+        let _ = self.write_filled_fmt(&mut output, vars, __private::render_level());
+        output
+    }
+
+    /// Write the nodes with their placeholders filled, see [`Markup::render_with`]
+    fn write_filled_fmt<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        vars: &HashMap<&str, &str>,
+        level: ColorLevel,
+    ) -> fmt::Result {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => self
+                    .style
+                    .write_styled_fmt(w, &fill_placeholders(&shortcoded(text), vars))?,
+                AstTk::Raw(text, _) => self
+                    .style
+                    .write_styled_fmt(w, &fill_placeholders(text, vars))?,
+                AstTk::Tree(ast) if !ast.shown_for(level) => {}
+                AstTk::Tree(ast) => ast.write_filled_fmt(w, vars, level)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like [`Markup::render`], with the colors downgraded for `level` first, see [`Markup::adapt`]
+    ///
+    /// `<if ...>` sections are evaluated against `level` too.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{caps, markup::Markup, style::ColorLevel};
+    ///
+    /// let markup = Markup::new("<f #ff8800>warning</>").unwrap();
+    ///
+    /// let ci = markup.render_for(ColorLevel::Ansi16);
+    /// let here = markup.render_for(caps::detect());
+    /// # let _ = (ci, here);
+    /// ```
+    pub fn render_for(&self, level: ColorLevel) -> String {
+        let mut output = String::new();
+        // Writing into a `String` never fails
+        let _ = self.adapt(level).write_nodes_fmt(&mut output, false, level);
+        output
+    }
+
+    /// Rewrite the colors of every tag for the given [`ColorLevel`], see [`Style::adapt`]
+    pub fn adapt(&self, level: ColorLevel) -> Self {
+        self.map_styles(|style| style.adapt(level))
+    }
+
+    /// Rebuild the tree with every tag's style replaced by `f(style)`
+    ///
+    /// Tags are visited in source order, outer tags before the ones nested in them.
+    /// The text outside of any tag comes first, with an empty style. `<if ...>`
+    /// sections aren't visited, they keep the style of the tag around them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{markup::Markup, style::Modifier};
+    ///
+    /// let markup = Markup::new("<f r m bk>alert</>").unwrap();
+    /// let calm = markup.map_styles(|style| style.without(Modifier::Blink));
+    ///
+    /// assert_eq!(calm.render(), Markup::new("<f r m b>alert</>").unwrap().render());
+    /// ```
+    pub fn map_styles(&self, mut f: impl FnMut(&Style) -> Style) -> Self {
+        let style = f(&self.style);
+        self.map_styles_with(style, &mut f)
+    }
+
+    /// Recursive part of [`Markup::map_styles`], `style` is this tree's mapped style
+    fn map_styles_with(&self, style: Style, f: &mut impl FnMut(&Style) -> Style) -> Self {
+        Self {
+            style,
+            when: self.when,
+            children: self
+                .children
+                .iter()
+                .map(|tk| match tk {
+                    AstTk::Tree(ast) if ast.when.is_some() => {
+                        AstTk::Tree(ast.map_styles_with(style, f))
+                    }
+                    AstTk::Tree(ast) => AstTk::Tree(ast.map_styles_with(f(&ast.style), f)),
+                    text => text.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Walk the tree in source order, see [`MarkupVisitor`]
+    ///
+    /// The content of every `<if ...>` section is visited, as if it wasn't there.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{markup::{Markup, MarkupVisitor}, prelude::*};
+    ///
+    /// /// Collects every foreground color used
+    /// struct Colors(Vec<Color>);
+    ///
+    /// impl MarkupVisitor for Colors {
+    ///     fn enter(&mut self, style: &Style) {
+    ///         self.0.extend(style.fg_color());
+    ///     }
+    /// }
+    ///
+    /// let mut colors = Colors(Vec::new());
+    /// Markup::new("<f r>a<f #ff8800>b</></> <m b>c</>").unwrap().walk(&mut colors);
+    ///
+    /// assert_eq!(colors.0, [Color::Red, Color::RGB(255, 136, 0)]);
+    /// ```
+    pub fn walk(&self, visitor: &mut impl MarkupVisitor) {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) | AstTk::Raw(text, _) => visitor.text(text, &self.style),
+                AstTk::Tree(ast) if ast.when.is_some() => ast.walk(visitor),
+                AstTk::Tree(ast) => {
+                    visitor.enter(&ast.style);
+                    ast.walk(visitor);
+                    visitor.exit(&ast.style);
+                }
+            }
+        }
+    }
+
+    /// Concatenate the text nodes without any styling, e.g. for log files
+    ///
+    /// Unlike stripping the escapes from [`Markup::render`], no ANSI output is built.
+    /// `<if ...>` sections are evaluated for [`ColorLevel::None`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let markup = Markup::new("<f r>error:</> <m b>disk \\<full></>").unwrap();
+    /// assert_eq!(markup.render_plain(), "error: disk <full>");
+    /// ```
+    pub fn render_plain(&self) -> String {
         let mut output = String::new();
+        self.write_plain(&mut output, ColorLevel::None);
+        output
+    }
+
+    /// Append the text nodes to `out`, see [`Markup::render_plain`]
+    fn write_plain(&self, out: &mut String, level: ColorLevel) {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => out.push_str(&shortcoded(text)),
+                AstTk::Raw(text, _) => out.push_str(text),
+                AstTk::Tree(ast) if !ast.shown_for(level) => {}
+                AstTk::Tree(ast) => ast.write_plain(out, level),
+            }
+        }
+    }
+
+    /// Render as HTML, the text of every styled tag becomes a `<span>` with inline CSS
+    ///
+    /// Spans aren't nested, so text keeps exactly the style of its innermost tag like
+    /// on a terminal. Text is escaped, see [`Style::to_css`] for how styles translate. `<if ...>`
+    /// sections are evaluated for [`ColorLevel::TrueColor`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// assert_eq!(
+    ///     Markup::new("<f #ff8800 m b>a \\< b</> & c").unwrap().render_html(),
+    ///     "<span style=\"color:#ff8800;font-weight:bold\">a &lt; b</span> &amp; c"
+    /// );
+    /// ```
+    pub fn render_html(&self) -> String {
+        let mut output = String::new();
+        self.write_html(&mut output, ColorLevel::TrueColor);
+        output
+    }
 
-        for tk in self.children {
-            let fragment = match tk {
-                AstTk::Text(text) => self.st.style(text),
-                AstTk::Tree(ast) => ast.render(),
+    /// Write the nodes as HTML, each text node in a span of its own tree's style
+    ///
+    /// Spans aren't nested: CSS would let a nested tag inherit the properties of the
+    /// tags around it, while on a terminal it starts over from its own style.
+    fn write_html(&self, out: &mut String, level: ColorLevel) {
+        let css = self.style.to_css();
+
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => write_html_text(&shortcoded(text), &css, out),
+                AstTk::Raw(text, _) => write_html_text(text, &css, out),
+                AstTk::Tree(ast) if !ast.shown_for(level) => {}
+                AstTk::Tree(ast) => ast.write_html(out, level),
+            }
+        }
+    }
+
+    /// Stream the rendered output directly into an [`io::Write`]
+    pub fn write_styled<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_nodes(w, __private::render_level())
+    }
+
+    /// Write the rendered nodes into an [`io::Write`], see [`Markup::write_styled`]
+    fn write_nodes<W: io::Write>(&self, w: &mut W, level: ColorLevel) -> io::Result<()> {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => self.style.write_styled(w, &shortcoded(text))?,
+                AstTk::Raw(text, _) => self.style.write_styled(w, text)?,
+                AstTk::Tree(ast) if !ast.shown_for(level) => {}
+                AstTk::Tree(ast) => ast.write_nodes(w, level)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stream the rendered output directly into a [`fmt::Write`]
+    pub fn write_styled_fmt<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        self.write_nodes_fmt(w, false, __private::render_level())
+    }
+
+    /// Write the rendered nodes, closing and re-opening the styles around `\n` if `per_line`
+    ///
+    /// Only the `<if ...>` sections holding for `level` are written.
+    fn write_nodes_fmt<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        per_line: bool,
+        level: ColorLevel,
+    ) -> fmt::Result {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => self.write_text_fmt(w, &shortcoded(text), per_line)?,
+                AstTk::Raw(text, _) => self.write_text_fmt(w, text, per_line)?,
+                AstTk::Tree(ast) if !ast.shown_for(level) => {}
+                AstTk::Tree(ast) => ast.write_nodes_fmt(w, per_line, level)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write a text node with this tree's style, see [`Markup::write_nodes_fmt`]
+    fn write_text_fmt<W: fmt::Write>(&self, w: &mut W, text: &str, per_line: bool) -> fmt::Result {
+        if !per_line {
+            return self.style.write_styled_fmt(w, text);
+        }
+
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                w.write_char('\n')?;
+            }
+
+            self.style.write_styled_fmt(w, line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Call `f` with every text node and the style it is rendered with, in order
+    ///
+    /// Only the `<if ...>` sections holding for `level` are visited.
+    #[cfg(any(feature = "ratatui", feature = "termcolor"))]
+    pub(crate) fn for_each_text(&self, level: ColorLevel, f: &mut impl FnMut(&str, &Style)) {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => f(&shortcoded(text), &self.style),
+                AstTk::Raw(text, _) => f(text, &self.style),
+                AstTk::Tree(ast) if !ast.shown_for(level) => {}
+                AstTk::Tree(ast) => ast.for_each_text(level, f),
+            }
+        }
+    }
+
+    /// Map every text node of [`Markup::render`]'s output back to the markup it came from
+    ///
+    /// Each entry is the byte range of the text in the output, the byte range in the
+    /// input (escapes included) and the style of the innermost tag around it. Text
+    /// split by [markdown emphasis](Markup::new_markdown) points at the whole text
+    /// between the tags, highlighted code at its whole section, text added by a
+    /// [`MarkupBuilder`] at `0..0`. Included and appended markup keeps the ranges
+    /// of its own input. `<if ...>` sections are evaluated like
+    /// [`Markup::render`] does.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{markup::Markup, prelude::*};
+    ///
+    /// let markup = Markup::new("hi <f r>you</>").unwrap();
+    /// let (output, input, style) = markup.spans().remove(1);
+    ///
+    /// assert_eq!(&markup.render()[output], "you");
+    /// assert_eq!(input, 8..11);
+    /// assert_eq!(style, Style::new().fg(Color::Red));
+    /// ```
+    pub fn spans(&self) -> Vec<(Range<usize>, Range<usize>, Style)> {
+        let mut spans = Vec::new();
+        self.collect_spans(&mut 0, &mut spans, __private::render_level());
+        spans
+    }
+
+    /// Recursive part of [`Markup::spans`], `pos` is the length of the output so far
+    fn collect_spans(
+        &self,
+        pos: &mut usize,
+        spans: &mut Vec<(Range<usize>, Range<usize>, Style)>,
+        level: ColorLevel,
+    ) {
+        let st = self.style.compile();
+
+        for tk in &self.children {
+            let (text, span) = match tk {
+                AstTk::Text(text, span) => (shortcoded(text), span),
+                AstTk::Raw(text, span) => (Cow::Borrowed(text.as_str()), span),
+                AstTk::Tree(ast) if !ast.shown_for(level) => continue,
+                AstTk::Tree(ast) => {
+                    ast.collect_spans(pos, spans, level);
+                    continue;
+                }
             };
 
-            output.push_str(&fragment);
+            if !text.is_empty() {
+                *pos += st.prefix().len();
+            }
+
+            spans.push((*pos..*pos + text.len(), span.clone(), self.style));
+
+            if !text.is_empty() {
+                *pos += text.len() + st.suffix().len();
+            }
+        }
+    }
+
+    /// Write the tree back as markup source, tags as the canonical spec of their style
+    ///
+    /// Parsing the result gives the same tree, apart from source ranges and comments:
+    /// `<` and backslashes are escaped.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{markup::Markup, prelude::*};
+    ///
+    /// let built = Markup::builder()
+    ///     .styled(Style::new().bold().fg(Color::Red), |b| b.text("a < b"))
+    ///     .text(r"C:\")
+    ///     .build();
+    ///
+    /// assert_eq!(built.to_markup_string(), r"<f r m b>a \< b</>C:\\");
+    /// ```
+    pub fn to_markup_string(&self) -> String {
+        let mut output = String::new();
+
+        if self.style == Style::new() {
+            self.write_markup_nodes(&mut output);
+        } else {
+            self.write_markup(&mut output);
         }
 
         output
     }
 
+    /// Write this tree as a tag around its children, see [`Markup::to_markup_string`]
+    fn write_markup(&self, out: &mut String) {
+        match self.when {
+            Some(condition) => out.push_str(&format!("<{IF} {}>", condition.name())),
+            None => out.push_str(&format!("<{}>", self.style.to_spec())),
+        }
+
+        self.write_markup_nodes(out);
+        out.push_str("</>");
+    }
+
+    /// Write the children as markup source
+    fn write_markup_nodes(&self, out: &mut String) {
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) => escape_markup(text, out),
+                AstTk::Raw(text, _) => {
+                    out.push_str(&format!("<{RAW}>"));
+                    out.push_str(text);
+                    out.push_str(RAW_END);
+                }
+                AstTk::Tree(ast) => ast.write_markup(out),
+            }
+        }
+    }
+
+    /// Describe the tree with one line per node, nested nodes indented by two spaces
+    ///
+    /// Tags are shown with the spec of their style, text quoted with its escapes
+    /// resolved, like the renderer sees it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::markup::Markup;
+    ///
+    /// let markup = Markup::new("a\\<b<f r>c<m b>d</></><>\n</>").unwrap();
+    ///
+    /// assert_eq!(
+    ///     markup.dump_tree(),
+    ///     "\"a<b\"\n<f r>\n  \"c\"\n  <m b>\n    \"d\"\n<>\n  \"\\n\"\n"
+    /// );
+    /// ```
+    pub fn dump_tree(&self) -> String {
+        let mut output = String::new();
+        self.dump_nodes(0, &mut output);
+        output
+    }
+
+    /// Append the lines of the children at `depth`, see [`Markup::dump_tree`]
+    fn dump_nodes(&self, depth: usize, out: &mut String) {
+        let indent = "  ".repeat(depth);
+
+        for tk in &self.children {
+            match tk {
+                AstTk::Text(text, _) | AstTk::Raw(text, _) => {
+                    out.push_str(&format!("{indent}{text:?}\n"))
+                }
+                AstTk::Tree(ast) => {
+                    match ast.when {
+                        Some(condition) => {
+                            out.push_str(&format!("{indent}<{IF} {}>\n", condition.name()))
+                        }
+                        None => out.push_str(&format!("{indent}<{}>\n", ast.style.to_spec())),
+                    }
+
+                    ast.dump_nodes(depth + 1, out);
+                }
+            }
+        }
+    }
+
+    /// Get the number of terminal columns the rendered output occupies
+    pub fn width(&self) -> usize {
+        self.width_for(__private::render_level())
+    }
+
+    /// Get the width of the nodes rendered for `level`, see [`Markup::width`]
+    fn width_for(&self, level: ColorLevel) -> usize {
+        self.children
+            .iter()
+            .map(|tk| match tk {
+                AstTk::Text(text, _) => shortcoded(text).width(),
+                AstTk::Raw(text, _) => text.width(),
+                AstTk::Tree(ast) if !ast.shown_for(level) => 0,
+                AstTk::Tree(ast) => ast.width_for(level),
+            })
+            .sum()
+    }
+
+    /// Check whether this tree is rendered for `level`, only `<if ...>` sections may not be
+    fn shown_for(&self, level: ColorLevel) -> bool {
+        self.when.is_none_or(|condition| condition.holds(level))
+    }
+
     /// Parses markup spec
-    fn markup_parser(s: impl AsRef<str>, mode: ParsingMode) -> Result<Self, ParsingError> {
-        let tokens = tokenize(s, mode)?;
+    fn markup_parser(
+        s: impl AsRef<str>,
+        dialect: &dyn Dialect,
+        theme: Option<&Theme>,
+        opts: &ParseOptions,
+    ) -> Result<Self, ParsingError> {
+        Self::from_tokens(tokenize(s, dialect, theme, opts)?, None)
+    }
 
+    /// Build the tree from balanced tokens, `<include name>` tags are replaced from `fragments`
+    fn from_tokens(
+        tokens: Vec<(Token, Range<usize>)>,
+        fragments: Option<&Fragments>,
+    ) -> Result<Self, ParsingError> {
         let mut stack = Vec::new();
         let mut current_nodes = Vec::new();
 
-        for token in tokens {
+        for (token, span) in tokens {
             match token {
-                Token::Text(text) => current_nodes.push(AstTk::Text(text)),
+                Token::Text(text) => current_nodes.push(AstTk::Text(text, span)),
+                Token::Raw(text) => current_nodes.push(AstTk::Raw(text, span)),
 
                 Token::Fmt(style) => {
-                    stack.push((style, current_nodes));
+                    stack.push((style, None, current_nodes));
                     current_nodes = Vec::new();
                 }
 
                 Token::Empty => {
-                    stack.push((Style::new(), current_nodes));
+                    stack.push((Style::new(), None, current_nodes));
+                    current_nodes = Vec::new();
+                }
+
+                #[cfg(feature = "syntect")]
+                Token::Code(lang, code) => {
+                    current_nodes.push(AstTk::Tree(Self::from_code(&lang, &code, span)))
+                }
+
+                // Like an `<if ...>` section, unstyled text keeps the style around it
+                Token::Include(name) => {
+                    let Some(fragment) = fragments.and_then(|fragments| fragments.get(&name))
+                    else {
+                        return Err(ParsingError::UnknownFragment(name, span));
+                    };
+
+                    if fragment.style == Style::new() && fragment.when.is_none() {
+                        current_nodes.extend(fragment.children.iter().cloned());
+                    } else {
+                        current_nodes.push(AstTk::Tree(fragment.clone()));
+                    }
+                }
+
+                // The section's text keeps the style of the tag around it
+                Token::If(condition) => {
+                    let style = stack.last().map_or(Style::new(), |&(style, _, _)| style);
+                    stack.push((style, Some(condition), current_nodes));
                     current_nodes = Vec::new();
                 }
 
                 Token::End => {
-                    let (style, mut parent_nodes) =
-                        stack.pop().ok_or(ParsingError::UnexpectedClosingTag)?;
+                    let (style, when, mut parent_nodes) =
+                        stack.pop().expect("tokenize balances the tags");
                     let ast = Markup {
-                        st: style.compile(),
+                        style,
+                        when,
                         children: current_nodes,
                     };
 
                     parent_nodes.push(AstTk::Tree(ast));
                     current_nodes = parent_nodes;
                 }
-            }
-        }
 
-        if !stack.is_empty() {
-            Err(ParsingError::UnclosedTags)?
+                // `<code lang>` sections without the `syntect` feature
+                _ => {}
+            }
         }
 
         Ok(Self {
-            st: Style::new().compile(),
+            style: Style::new(),
+            when: None,
             children: current_nodes,
         })
     }
+
+    /// Build the tree of a highlighted `<code lang>` section, every text points at `span`
+    #[cfg(feature = "syntect")]
+    fn from_code(lang: &str, code: &str, span: Range<usize>) -> Self {
+        let children = crate::highlight::highlight(code, lang)
+            .into_iter()
+            .map(|(text, style)| {
+                AstTk::Tree(Markup {
+                    style,
+                    when: None,
+                    children: vec![AstTk::Raw(text, span.clone())],
+                })
+            })
+            .collect();
+
+        Self {
+            style: Style::new(),
+            when: None,
+            children,
+        }
+    }
+}
+
+/// Number of bytes [`Markup::from_reader`] reads at once
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Wrap invalid input of [`Markup::from_reader`] like [`io::Read::read_to_string`] does
+fn invalid_utf8(err: std::str::Utf8Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Renders markup fed in chunks, e.g. piped logs, without buffering all of it
+///
+/// Text outside of tags is returned as soon as it's fed, styled text once another
+/// tag starts or its tag closes. Only a tag or escape split across chunks is held
+/// back. All the output together displays the same as [`Markup::render`] of the
+/// whole input, but isn't always byte for byte equal: text the tree keeps in
+/// separate nodes, like a `<raw>` section and the text around it, may be styled in
+/// one piece here. Error spans count from the start of the first chunk. After an
+/// error the stream can't be used any further.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::markup::{Markup, MarkupStream};
+///
+/// let mut stream = MarkupStream::new();
+///
+/// let mut output = stream.feed("plain <f r>re").unwrap();
+/// assert_eq!(output, "plain ");
+///
+/// output += &stream.feed("d</> <m").unwrap();
+/// output += &stream.feed(" b>bold</>").unwrap();
+/// output += &stream.finish().unwrap();
+///
+/// assert_eq!(output, Markup::new("plain <f r>red</> <m b>bold</>").unwrap().render());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MarkupStream {
+    /// Theme whose entries tags may name
+    theme: Option<Theme>,
+    /// Options tag specs are parsed with
+    opts: ParseOptions,
+    /// Input not tokenized yet, it ends inside a tag or an escape
+    pending: String,
+    /// Number of bytes tokenized so far
+    consumed: usize,
+    /// Contents and spans of the open tags, see `resume_tokens`
+    open: Vec<(String, Range<usize>)>,
+    /// Number of opening tags so far
+    tags: usize,
+    /// Styles of the open tags, innermost last
+    styles: Vec<CompiledStyle>,
+    /// Number of open tags inside an `<if ...>` section which doesn't hold, itself included
+    hidden: usize,
+    /// Text of the innermost open tag not written yet
+    text: String,
+}
+
+impl MarkupStream {
+    /// Creates a new [`MarkupStream`] for the default markup syntax
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolve tag names through `theme` first, see [`Markup::new_with_theme`]
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Parse tag specs with non-default options, see [`Markup::new_with_options`]
+    pub fn with_options(mut self, opts: ParseOptions) -> Self {
+        self.opts = opts;
+        self
+    }
+
+    /// Add the next chunk of input, returning the output that is complete so far
+    pub fn feed(&mut self, chunk: &str) -> Result<String, ParsingError> {
+        let tokens = self.next_tokens(chunk, false)?;
+        let mut output = self.render_tokens(tokens);
+
+        // Whichever tag follows, it ends the text node, comments don't
+        let comment = self.pending.starts_with(COMMENT) || COMMENT.starts_with(&self.pending);
+        if self.pending.starts_with('<') && !comment {
+            Self::write_text(&mut self.text, &self.styles, &mut output);
+        }
+
+        Ok(output)
+    }
+
+    /// End the input, returning the rest of the output
+    ///
+    /// Fails if the input ends inside a tag or with tags still open.
+    pub fn finish(mut self) -> Result<String, ParsingError> {
+        let tokens = self.last_tokens()?;
+        Ok(self.render_tokens(tokens))
+    }
+
+    /// Add `chunk` and tokenize the input up to where it can be cut, see [`text_end`]
+    fn next_tokens(
+        &mut self,
+        chunk: &str,
+        whole_nodes: bool,
+    ) -> Result<Vec<(Token, Range<usize>)>, ParsingError> {
+        self.pending.push_str(chunk);
+
+        let len = self.consumed + self.pending.len();
+        if let Some(max) = __private::max_len(&self.opts).filter(|&max| len > max) {
+            return Err(ParsingError::LimitExceeded(Limit::Length, max, max..len));
+        }
+
+        // A shortcode split across chunks is only replaced as a whole
+        let mut end = text_end(
+            &self.pending,
+            __private::escape_char(&self.opts),
+            whole_nodes,
+        );
+        if !whole_nodes && caps::shortcodes() != Shortcodes::Off {
+            end = __private::partial_start(&self.pending[..end]).unwrap_or(end);
+        }
+
+        let rest = self.pending.split_off(end);
+        let ready = std::mem::replace(&mut self.pending, rest);
+        self.tokenize(&ready)
+    }
+
+    /// Tokenize the rest of the input, failing if it ends inside a tag or with tags open
+    fn last_tokens(&mut self) -> Result<Vec<(Token, Range<usize>)>, ParsingError> {
+        let rest = std::mem::take(&mut self.pending);
+        let tokens = self.tokenize(&rest)?;
+
+        match self.open.pop() {
+            Some((_, span)) => Err(ParsingError::UnclosedTags(span)),
+            None => Ok(tokens),
+        }
+    }
+
+    /// Tokenize `chunk`, which starts and ends in text, spans count from the start of the input
+    fn tokenize(&mut self, chunk: &str) -> Result<Vec<(Token, Range<usize>)>, ParsingError> {
+        let mut tokens = __private::resume_tokens(
+            chunk,
+            &Mk,
+            self.theme.as_ref(),
+            &self.opts,
+            std::mem::take(&mut self.open),
+            self.tags,
+        );
+
+        let offset = self.consumed;
+        let collected = tokens
+            .by_ref()
+            .map(|token| match token {
+                // There are no fragments to include
+                Ok((Token::Include(name), span)) => Err(ParsingError::UnknownFragment(name, span)),
+                token => token,
+            })
+            .map(|token| {
+                token
+                    .map(|(token, span)| (token, span.start + offset..span.end + offset))
+                    .map_err(|e| __private::offset_error(e, offset))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        (self.open, self.tags) = __private::suspend_tokens(tokens, self.consumed);
+        self.consumed += chunk.len();
+        Ok(collected)
+    }
+
+    /// Render tokens of the input, holding back the text of the innermost tag
+    fn render_tokens(&mut self, tokens: Vec<(Token, Range<usize>)>) -> String {
+        let mut output = String::new();
+
+        for (token, _) in tokens {
+            match token {
+                Token::Fmt(_) | Token::Empty | Token::If(_) if self.hidden > 0 => self.hidden += 1,
+                Token::End if self.hidden > 0 => self.hidden -= 1,
+                Token::Text(_) | Token::Raw(_) if self.hidden > 0 => {}
+                Token::Text(text) if self.styles.is_empty() => output.push_str(&shortcoded(&text)),
+                Token::Text(text) => self.text.push_str(&shortcoded(&text)),
+                Token::Raw(text) if self.styles.is_empty() => output.push_str(&text),
+                Token::Raw(text) => self.text.push_str(&text),
+                Token::Fmt(style) => {
+                    Self::write_text(&mut self.text, &self.styles, &mut output);
+                    self.styles.push(style.compile());
+                }
+                Token::Empty => {
+                    Self::write_text(&mut self.text, &self.styles, &mut output);
+                    self.styles.push(Style::new().compile());
+                }
+                #[cfg(feature = "syntect")]
+                Token::Code(..) if self.hidden > 0 => {}
+                #[cfg(feature = "syntect")]
+                Token::Code(lang, code) => {
+                    Self::write_text(&mut self.text, &self.styles, &mut output);
+
+                    for (text, style) in crate::highlight::highlight(&code, &lang) {
+                        // Writing into a `String` never fails
+                        let _ = style.compile().write_styled_fmt(&mut output, &text);
+                    }
+                }
+                // Like in the tree, the section keeps the style around it
+                Token::If(condition) => {
+                    Self::write_text(&mut self.text, &self.styles, &mut output);
+
+                    if condition.holds(__private::render_level()) {
+                        let st = self.styles.last().cloned();
+                        self.styles
+                            .push(st.unwrap_or_else(|| Style::new().compile()));
+                    } else {
+                        self.hidden = 1;
+                    }
+                }
+                Token::End => {
+                    Self::write_text(&mut self.text, &self.styles, &mut output);
+                    self.styles.pop();
+                }
+                // Rejected while tokenizing
+                Token::Include(_) => {}
+                // `<code lang>` sections without the `syntect` feature
+                _ => {}
+            }
+        }
+
+        output
+    }
+
+    /// Write the held back `text` with the style of the innermost tag
+    fn write_text(text: &mut String, styles: &[CompiledStyle], output: &mut String) {
+        if let (false, Some(st)) = (text.is_empty(), styles.last()) {
+            // Writing into a `String` never fails
+            let _ = st.write_styled_fmt(output, text);
+            text.clear();
+        }
+    }
+}
+
+/// Get the length of the longest prefix of `s` ending in text, outside of tags, escapes,
+/// raw sections and comments
+///
+/// With `whole_nodes` the prefix ends in front of or after a tag instead, so no text
+/// node is split.
+fn text_end(s: &str, escape: char, whole_nodes: bool) -> usize {
+    let (mut end, mut in_tag, mut escaped) = (0, false, false);
+    let (mut tag_start, mut skip_to) = (0, 0);
+    // Text in front of a tag is a whole node
+    let mut node_end = 0;
+    let cut = |end: usize, node_end: usize| if whole_nodes { node_end } else { end };
+
+    for (i, c) in s.char_indices() {
+        let next = i + c.len_utf8();
+
+        match c {
+            _ if i < skip_to => {}
+            _ if escaped => {
+                escaped = false;
+                end = next;
+            }
+            '>' if in_tag => {
+                in_tag = false;
+
+                match verbatim_end(&s[tag_start + 1..i]) {
+                    Some(close) => match s[next..].find(close) {
+                        Some(len) => {
+                            skip_to = next + len + close.len();
+                            end = skip_to;
+                        }
+                        None => return cut(end, node_end),
+                    },
+                    None => end = next,
+                }
+            }
+            // Hand the invalid tag to the tokenizer right away
+            c if in_tag && c != '/' && !is_tag_char(c) => return s.len(),
+            _ if in_tag => {}
+            c if c == escape => escaped = true,
+            // The comment's dashes can't end it, like in the tokenizer
+            '<' if s[i..].starts_with(COMMENT) => {
+                match s[i + COMMENT.len()..].find(COMMENT_END) {
+                    Some(len) => skip_to = i + COMMENT.len() + len + COMMENT_END.len(),
+                    None => return cut(end, node_end),
+                }
+
+                end = skip_to;
+            }
+            '<' => {
+                in_tag = true;
+                tag_start = i;
+                node_end = i;
+            }
+            _ => end = next,
+        }
+    }
+
+    cut(end, node_end)
+}
+
+/// Get the closing tag of the section opened by the tag content `tag`, if its content isn't parsed
+fn verbatim_end(tag: &str) -> Option<&'static str> {
+    #[cfg(feature = "syntect")]
+    if argument_of(tag, CODE).is_some() {
+        return Some(CODE_END);
+    }
+
+    (tag.trim() == RAW).then_some(RAW_END)
+}
+
+/// Styles of the emphasis [`Markup::new_markdown`] recognizes in text
+///
+/// `**bold**`, `*italic*`, `__underline__` and `` `code` `` may be nested, except
+/// inside code, and can't span across tags. Raw sections are left alone. Markers without a closing one, or
+/// escaped with a backslash (`\*`), are kept as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MarkdownStyles {
+    /// Style of `**bold**`
+    bold: Style,
+    /// Style of `*italic*`
+    italic: Style,
+    /// Style of `__underline__`
+    underline: Style,
+    /// Style of `` `code` ``
+    code: Style,
+}
+
+impl Default for MarkdownStyles {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownStyles {
+    /// Creates the default styles: bold, italic, underlined and cyan code
+    pub const fn new() -> Self {
+        Self {
+            bold: Style::new().bold(),
+            italic: Style::new().italic(),
+            underline: Style::new().underline(),
+            code: Style::new().fg(Color::Cyan),
+        }
+    }
+
+    /// Set the style of `**bold**`
+    pub const fn with_bold(mut self, style: Style) -> Self {
+        self.bold = style;
+        self
+    }
+
+    /// Set the style of `*italic*`
+    pub const fn with_italic(mut self, style: Style) -> Self {
+        self.italic = style;
+        self
+    }
+
+    /// Set the style of `__underline__`
+    pub const fn with_underline(mut self, style: Style) -> Self {
+        self.underline = style;
+        self
+    }
+
+    /// Set the style of `` `code` ``
+    pub const fn with_code(mut self, style: Style) -> Self {
+        self.code = style;
+        self
+    }
+}
+
+/// Split `text` along its markdown-like emphasis into `out`, see [`MarkdownStyles`]
+///
+/// Every token gets the `span` of the whole text.
+fn push_markdown(
+    text: &str,
+    span: &Range<usize>,
+    styles: &MarkdownStyles,
+    out: &mut Vec<(Token, Range<usize>)>,
+) {
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // Markers are ASCII, the escaped one is the second byte
+        let escaped = (c == '\\')
+            .then(|| rest[1..].strip_prefix(['*', '_', '`']))
+            .flatten();
+        if let Some(after) = escaped {
+            plain.push_str(&rest[1..2]);
+            rest = after;
+            continue;
+        }
+
+        let (marker, style) = match c {
+            '`' => ("`", styles.code),
+            '*' if rest.starts_with("**") => ("**", styles.bold),
+            '*' => ("*", styles.italic),
+            '_' if rest.starts_with("__") => ("__", styles.underline),
+            _ => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+        };
+
+        let inner = &rest[marker.len()..];
+        match closing_marker(inner, marker).filter(|&end| end > 0) {
+            Some(end) => {
+                if !plain.is_empty() {
+                    out.push((Token::Text(std::mem::take(&mut plain)), span.clone()));
+                }
+
+                out.push((Token::Fmt(style), span.clone()));
+                match marker {
+                    "`" => out.push((Token::Raw(inner[..end].to_string()), span.clone())),
+                    _ => push_markdown(&inner[..end], span, styles, out),
+                }
+                out.push((Token::End, span.clone()));
+
+                rest = &inner[end + marker.len()..];
+            }
+            None => {
+                plain.push_str(marker);
+                rest = inner;
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        out.push((Token::Text(plain), span.clone()));
+    }
+}
+
+/// Find where the span opened by `marker` closes in `s`, skipping escapes and nested spans
+fn closing_marker(s: &str, marker: &str) -> Option<usize> {
+    let mut chars = s.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if marker != "`" => {
+                chars.next();
+            }
+            // A single `*` doesn't close italics when it's part of `**`
+            '*' if marker == "*" && s[i..].starts_with("**") => {
+                chars.next();
+            }
+            _ if s[i..].starts_with(marker) => return Some(i),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Callbacks for [`Markup::walk`], every method defaults to doing nothing
+pub trait MarkupVisitor {
+    /// Called when entering a tag, with the tag's own style
+    fn enter(&mut self, _style: &Style) {}
+
+    /// Called for every text node, with the style of the innermost tag around it
+    fn text(&mut self, _text: &str, _style: &Style) {}
+
+    /// Called when leaving a tag, with the same style [`MarkupVisitor::enter`] got
+    fn exit(&mut self, _style: &Style) {}
+}
+
+/// Builds a [`Markup`] tree in code instead of parsing it
+///
+/// Nested styles work like nested tags, parsed fragments can be mixed in with
+/// [`MarkupBuilder::markup`].
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{markup::{Markup, MarkupBuilder}, prelude::*};
+///
+/// let built = MarkupBuilder::new()
+///     .styled(Style::new().fg(Color::Red), |b| {
+///         b.text("error: ").styled(Style::new().bold(), |b| b.text("disk full"))
+///     })
+///     .markup(Markup::new(" <m i>(retrying)</>").unwrap())
+///     .build();
+///
+/// assert_eq!(built.render_plain(), "error: disk full (retrying)");
+/// assert_eq!(
+///     built.render(),
+///     Markup::new("<f r>error: <m b>disk full</></> <m i>(retrying)</>").unwrap().render()
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MarkupBuilder {
+    /// Nodes added so far
+    children: Vec<AstTk>,
+}
+
+impl MarkupBuilder {
+    /// Creates a new, empty [`MarkupBuilder`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append unstyled text
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.children.push(AstTk::Text(text.into(), 0..0));
+        self
+    }
+
+    /// Append the nodes added by `build`, styled with `style` like a tag
+    pub fn styled(mut self, style: Style, build: impl FnOnce(Self) -> Self) -> Self {
+        let inner = build(Self::new()).children;
+
+        self.children.push(AstTk::Tree(Markup {
+            style,
+            when: None,
+            children: inner,
+        }));
+        self
+    }
+
+    /// Append a parsed (or previously built) [`Markup`]
+    pub fn markup(mut self, markup: Markup) -> Self {
+        self.children.push(AstTk::Tree(markup));
+        self
+    }
+
+    /// Finish the tree
+    pub fn build(self) -> Markup {
+        Markup {
+            style: Style::new(),
+            when: None,
+            children: self.children,
+        }
+    }
+}
+
+/// Named markup fragments replacing `<include name>` tags, see [`Markup::new_with_fragments`]
+///
+/// Fragments are parsed on their own, so templates can be split into reusable parts.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Fragments {
+    /// Fragments by name
+    fragments: HashMap<String, Markup>,
+}
+
+impl Fragments {
+    /// Creates an empty set of [`Fragments`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `markup` as the fragment `name`, returning the one it replaces
+    pub fn insert(&mut self, name: impl Into<String>, markup: Markup) -> Option<Markup> {
+        self.fragments.insert(name.into(), markup)
+    }
+
+    /// Register `markup` as the fragment `name`, see [`Fragments::insert`]
+    pub fn with(mut self, name: impl Into<String>, markup: Markup) -> Self {
+        self.insert(name, markup);
+        self
+    }
+
+    /// Get the fragment `name`
+    pub fn get(&self, name: &str) -> Option<&Markup> {
+        self.fragments.get(name)
+    }
+}
+
+/// Replace the `{name}` placeholders of `text` found in `vars` and unescape `{{` and `}}`
+fn fill_placeholders(text: &str, vars: &HashMap<&str, &str>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        output.push_str(&rest[..i]);
+        let tail = &rest[i..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let value = tail
+            .strip_prefix('{')
+            .and_then(|inner| inner.split_once('}'))
+            .and_then(|(name, after)| vars.get(name).map(|value| (*value, after)));
+
+        match value {
+            Some((value, after)) => {
+                output.push_str(value);
+                rest = after;
+            }
+            None => {
+                output.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Append `text` to `out` as markup source, see [`Markup::to_markup_string`]
+fn escape_markup(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '<' => out.push_str("\\<"),
+            '\\' => out.push_str("\\\\"),
+            ch => out.push(ch),
+        }
+    }
+}
+
+/// Append a text node as HTML, in a span styled by `css` unless it's empty
+fn write_html_text(text: &str, css: &str, out: &mut String) {
+    if text.is_empty() {
+        return;
+    }
+
+    if css.is_empty() {
+        return escape_html(text, out);
+    }
+
+    out.push_str("<span style=\"");
+    escape_html(css, out);
+    out.push_str("\">");
+    escape_html(text, out);
+    out.push_str("</span>");
+}
+
+/// Append `text` to `out` with the HTML special characters escaped
+fn escape_html(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            ch => out.push(ch),
+        }
+    }
+}
+
+impl fmt::Display for Markup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_styled_fmt(f)
+    }
 }
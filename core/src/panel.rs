@@ -0,0 +1,185 @@
+//! Boxes drawn around multi-line, possibly styled, content
+
+use std::fmt;
+
+use crate::{
+    ansi::display_width,
+    border::Border,
+    style::{Stylable, Style},
+    table::Align,
+};
+
+/// A bordered block of text with an optional title
+///
+/// Content lines may contain escape sequences, they don't count towards the width.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::panel::Panel;
+///
+/// let panel = Panel::new("first line\nsecond").title("Note");
+///
+/// assert_eq!(
+///     panel.render(),
+///     "╭─ Note ─────╮\n\
+///      │ first line │\n\
+///      │ second     │\n\
+///      ╰────────────╯"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panel {
+    /// Content, split into lines on render
+    content: String,
+    /// Title drawn into the top border
+    title: Option<String>,
+    /// Line style of the border
+    border: Border,
+    /// Style applied to the border lines
+    border_style: Style,
+    /// Style applied to the title
+    title_style: Style,
+    /// Alignment of the content lines
+    align: Align,
+    /// Spaces between the border and the content on the left and right
+    padding: usize,
+    /// Empty lines between the border and the content at the top and bottom
+    vertical_padding: usize,
+}
+
+impl Default for Panel {
+    fn default() -> Self {
+        Self {
+            content: String::new(),
+            title: None,
+            border: Border::Rounded,
+            border_style: Style::new(),
+            title_style: Style::new(),
+            align: Align::Left,
+            padding: 1,
+            vertical_padding: 0,
+        }
+    }
+}
+
+impl Panel {
+    /// Creates a new [`Panel`] with a [`Border::Rounded`] border and a padding of 1
+    pub fn new(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the title drawn into the top border
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the line style of the border
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Set the style of the border lines
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Set the style of the title
+    pub fn title_style(mut self, style: Style) -> Self {
+        self.title_style = style;
+        self
+    }
+
+    /// Set the alignment of the content lines
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Set the number of spaces left and right of the content
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Set the number of empty lines above and below the content
+    pub fn vertical_padding(mut self, padding: usize) -> Self {
+        self.vertical_padding = padding;
+        self
+    }
+
+    /// Render the panel, lines are separated by `\n` without a trailing newline
+    pub fn render(&self) -> String {
+        // The title is drawn as `─ title ─`, the trailing line is kept at least 1 column wide
+        let title_width = self.title.as_deref().map_or(0, |t| display_width(t) + 3);
+
+        let blank = std::iter::repeat_n("", self.vertical_padding);
+        let content = blank
+            .clone()
+            .chain(self.content.lines())
+            .chain(blank)
+            .collect::<Vec<_>>();
+
+        let inner = content
+            .iter()
+            .map(|line| display_width(line))
+            .max()
+            .unwrap_or(0)
+            .max((title_width + 1).saturating_sub(self.padding * 2));
+        let full = inner + self.padding * 2;
+
+        let pad = " ".repeat(self.padding);
+        let body = content.iter().map(|line| {
+            format!(
+                "{pad}{}{pad}",
+                self.align.pad(line, display_width(line), inner)
+            )
+        });
+
+        let Some(chars) = self.border.chars() else {
+            let title = self.title.iter().map(|t| self.title_style.style(t));
+            return title
+                .chain(body.map(|line| line.trim_end().to_string()))
+                .collect::<Vec<_>>()
+                .join("\n");
+        };
+
+        let h = |n: usize| chars.h.to_string().repeat(n);
+        let [top_left, _, top_right] = chars.top;
+        let [bottom_left, _, bottom_right] = chars.bottom;
+
+        let top = match &self.title {
+            Some(title) => format!(
+                "{} {} {}",
+                self.border_style.style(format!("{top_left}{}", h(1))),
+                self.title_style.style(title),
+                self.border_style
+                    .style(format!("{}{top_right}", h(full - title_width))),
+            ),
+            None => self
+                .border_style
+                .style(format!("{top_left}{}{top_right}", h(full))),
+        };
+        let bottom = self
+            .border_style
+            .style(format!("{bottom_left}{}{bottom_right}", h(full)));
+        let v = self.border_style.style(chars.v.to_string());
+
+        std::iter::once(top)
+            .chain(body.map(|line| format!("{v}{line}{v}")))
+            .chain([bottom])
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for Panel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
@@ -0,0 +1,173 @@
+//! Styled text built from `(text, Style)` segments
+
+use std::fmt;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    ansi,
+    style::{ColorLevel, Style, Styled},
+};
+
+/// A line of rich text made of `(text, Style)` spans
+///
+/// Keeping the spans structured lets callers re-target the text later (strip,
+/// downgrade colors) without re-parsing ANSI escape sequences.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, spans::StyledString};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let mut line = StyledString::new();
+/// line.push("error", Style::new().fg(Color::Red).bold());
+/// line.push_plain(": file not found");
+///
+/// assert_eq!(line.plain_text(), "error: file not found");
+/// assert_eq!(line.to_string(), "\u{1b}[31;1merror\u{1b}[0m: file not found");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StyledString {
+    /// Text segments with their styles
+    spans: Vec<(String, Style)>,
+}
+
+impl StyledString {
+    /// Creates a new, empty [`StyledString`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a styled string from text containing SGR escape sequences, see [`ansi::parse`]
+    pub fn from_ansi(s: &str) -> Self {
+        ansi::parse(s).into_iter().collect()
+    }
+
+    /// Append `text` with the given style
+    ///
+    /// Adjacent spans sharing a style are merged.
+    pub fn push(&mut self, text: impl Into<String>, style: Style) {
+        let text = text.into();
+
+        if text.is_empty() {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some((last, last_style)) if *last_style == style => last.push_str(&text),
+            _ => self.spans.push((text, style)),
+        }
+    }
+
+    /// Append unstyled text
+    pub fn push_plain(&mut self, text: impl Into<String>) {
+        self.push(text, Style::new());
+    }
+
+    /// Builder variant of [`StyledString::push`]
+    pub fn with(mut self, text: impl Into<String>, style: Style) -> Self {
+        self.push(text, style);
+        self
+    }
+
+    /// Append all spans of `other`
+    pub fn append(&mut self, other: StyledString) {
+        self.extend(other.spans);
+    }
+
+    /// Concatenate several styled strings into one
+    pub fn concat(parts: impl IntoIterator<Item = StyledString>) -> Self {
+        let mut out = Self::new();
+        for part in parts {
+            out.append(part);
+        }
+        out
+    }
+
+    /// Iterate over the spans
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Style)> {
+        self.spans
+            .iter()
+            .map(|(text, style)| (text.as_str(), style))
+    }
+
+    /// Iterate over the spans as [`Styled`] display adapters
+    pub fn styled(&self) -> impl Iterator<Item = Styled<'_>> {
+        self.spans.iter().map(|(text, style)| style.paint(text))
+    }
+
+    /// Number of spans
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Check whether there are no spans
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Concatenate the text of every span, dropping all styling
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|(text, _)| text.as_str()).collect()
+    }
+
+    /// Get the number of terminal columns the text occupies
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(|(text, _)| text.width()).sum()
+    }
+
+    /// Rewrite every span's colors for the given [`ColorLevel`], see [`Style::adapt`]
+    pub fn adapt(&self, level: ColorLevel) -> Self {
+        self.spans
+            .iter()
+            .map(|(text, style)| (text.clone(), style.adapt(level)))
+            .collect()
+    }
+}
+
+impl fmt::Display for StyledString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for styled in self.styled() {
+            write!(f, "{styled}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Into<String>> FromIterator<(T, Style)> for StyledString {
+    fn from_iter<I: IntoIterator<Item = (T, Style)>>(iter: I) -> Self {
+        let mut out = Self::new();
+        out.extend(iter);
+        out
+    }
+}
+
+impl<T: Into<String>> Extend<(T, Style)> for StyledString {
+    fn extend<I: IntoIterator<Item = (T, Style)>>(&mut self, iter: I) {
+        for (text, style) in iter {
+            self.push(text, style);
+        }
+    }
+}
+
+impl IntoIterator for StyledString {
+    type Item = (String, Style);
+    type IntoIter = std::vec::IntoIter<(String, Style)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.spans.into_iter()
+    }
+}
+
+impl From<Styled<'_>> for StyledString {
+    fn from(styled: Styled<'_>) -> Self {
+        Self::new().with(styled.text(), *styled.get_style())
+    }
+}
+
+impl From<&str> for StyledString {
+    fn from(text: &str) -> Self {
+        Self::new().with(text, Style::new())
+    }
+}
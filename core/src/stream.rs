@@ -0,0 +1,231 @@
+//! Styled output written incrementally to an [`io::Write`]
+
+use std::io;
+
+use cli_styler_spec::__private;
+
+use crate::style::{CompiledStyle, Style};
+
+/// A stack of nested styles for output emitted piece by piece (REPLs, loggers)
+///
+/// Pushed styles are merged on top of the current one, popping restores the
+/// previous style. Escape sequences are only written together with text, and
+/// only the codes needed to go from the last written style to the current one.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, stream::StyleStack};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let mut out = StyleStack::new(Vec::new());
+///
+/// out.push(Style::new().fg(Color::Red));
+/// out.write("error: ").unwrap();
+/// out.push(Style::new().bold());
+/// out.write("disk full").unwrap();
+/// out.pop();
+/// out.pop();
+/// out.write("\n").unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out.into_inner().unwrap()).unwrap(),
+///     "\x1b[31merror: \x1b[1mdisk full\x1b[39;22m\n"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct StyleStack<W: io::Write> {
+    /// Underlying writer
+    inner: W,
+    /// Pushed styles with the effective style each of them produced
+    stack: Vec<(Style, Style)>,
+    /// Style the terminal was last switched to
+    written: Style,
+}
+
+impl<W: io::Write> StyleStack<W> {
+    /// Creates a new, empty [`StyleStack`] writing into `inner`
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            stack: Vec::new(),
+            written: Style::new(),
+        }
+    }
+
+    /// Merge `style` on top of the current style
+    pub fn push(&mut self, style: Style) {
+        let effective = self.current().merge(&style);
+        self.stack.push((style, effective));
+    }
+
+    /// Restore the style active before the last [`push`](Self::push), returning the pushed style
+    pub fn pop(&mut self) -> Option<Style> {
+        self.stack.pop().map(|(style, _)| style)
+    }
+
+    /// Get the effective style text is currently written with
+    pub fn current(&self) -> Style {
+        self.stack
+            .last()
+            .map_or(Style::new(), |&(_, effective)| effective)
+    }
+
+    /// Get the number of pushed styles
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Write `text` with the current style
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        self.switch_to(self.current())?;
+        self.inner.write_all(text.as_bytes())
+    }
+
+    /// Switch the terminal back to the default style, the stack itself is kept
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.switch_to(Style::new())
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the underlying writer
+    ///
+    /// Escape sequences written directly aren't tracked by the stack.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Reset the style and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.reset()?;
+        Ok(self.inner)
+    }
+
+    /// Emit the transition from the last written style to `style`
+    fn switch_to(&mut self, style: Style) -> io::Result<()> {
+        if !__private::should_colorize() {
+            return Ok(());
+        }
+
+        self.inner
+            .write_all(self.written.transition_to(&style).as_bytes())?;
+        self.written = style;
+        Ok(())
+    }
+}
+
+/// Applies one [`CompiledStyle`] to everything written through it
+///
+/// The style is closed before and re-opened after every `\n`, so backgrounds
+/// don't bleed into the rest of the line and every line keeps the style. The
+/// style is also closed on [`flush`](io::Write::flush) and when dropped.
+///
+/// # Example
+/// ```rust
+/// use std::io::Write;
+///
+/// use cli_styler::{style::CompiledStyle, stream::StyledWriter};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let mut out = StyledWriter::new(Vec::new(), CompiledStyle::new_from_cli_spec("b b").unwrap());
+/// write!(out, "one\ntwo").unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(out.into_inner().unwrap()).unwrap(),
+///     "\x1b[44mone\x1b[0m\n\x1b[44mtwo\x1b[0m"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct StyledWriter<W: io::Write> {
+    /// Underlying writer, only taken by [`StyledWriter::into_inner`]
+    inner: Option<W>,
+    /// Style applied to the written text
+    style: CompiledStyle,
+    /// Whether the style was opened and not closed yet
+    open: bool,
+}
+
+impl<W: io::Write> StyledWriter<W> {
+    /// Creates a new [`StyledWriter`] applying `style` to everything written into `inner`
+    pub fn new(inner: W, style: CompiledStyle) -> Self {
+        Self {
+            inner: Some(inner),
+            style,
+            open: false,
+        }
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        self.inner
+            .as_ref()
+            .expect("writer is present until into_inner")
+    }
+
+    /// Get a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        self.inner
+            .as_mut()
+            .expect("writer is present until into_inner")
+    }
+
+    /// Close the style and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.close()?;
+        Ok(self
+            .inner
+            .take()
+            .expect("writer is present until into_inner"))
+    }
+
+    /// Write the closing sequence if the style is open
+    fn close(&mut self) -> io::Result<()> {
+        if let (true, Some(inner)) = (self.open, &mut self.inner) {
+            inner.write_all(self.style.suffix().as_bytes())?;
+        }
+
+        self.open = false;
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for StyledWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (i, line) in buf.split(|&b| b == b'\n').enumerate() {
+            if i > 0 {
+                self.close()?;
+                self.get_mut().write_all(b"\n")?;
+            }
+
+            if !line.is_empty() {
+                if let (false, Some(inner)) = (self.open, &mut self.inner) {
+                    inner.write_all(self.style.prefix().as_bytes())?;
+                    self.open = true;
+                }
+
+                self.get_mut().write_all(line)?;
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.close()?;
+        self.get_mut().flush()
+    }
+}
+
+impl<W: io::Write> Drop for StyledWriter<W> {
+    fn drop(&mut self) {
+        // Errors can't be reported from `drop`
+        let _ = self.close();
+    }
+}
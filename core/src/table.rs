@@ -0,0 +1,311 @@
+//! Tables with ANSI-aware column widths
+
+use std::fmt;
+
+use crate::{
+    ansi::display_width,
+    border::Border,
+    error::StylerError,
+    style::{Stylable, Style},
+};
+
+/// Horizontal alignment of the text inside a column
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Pad on the right
+    #[default]
+    Left,
+    /// Pad evenly on both sides, extra space goes to the right
+    Center,
+    /// Pad on the left
+    Right,
+}
+
+impl Align {
+    /// Pad `text`, which occupies `width` columns, to `target` columns
+    pub(crate) fn pad(self, text: &str, width: usize, target: usize) -> String {
+        let gap = target.saturating_sub(width);
+        let (left, right) = match self {
+            Align::Left => (0, gap),
+            Align::Center => (gap / 2, gap - gap / 2),
+            Align::Right => (gap, 0),
+        };
+
+        format!("{}{text}{}", " ".repeat(left), " ".repeat(right))
+    }
+}
+
+/// A table column: header text, alignment and a style applied to all of its cells
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Column {
+    /// Header text
+    header: String,
+    /// Alignment of the header and cells
+    align: Align,
+    /// Base style of the cells, merged under each cell's own style
+    style: Style,
+}
+
+impl Column {
+    /// Creates a new left-aligned, unstyled column
+    pub fn new(header: impl Into<String>) -> Self {
+        Self {
+            header: header.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the alignment of the column
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Set the base style of the column's cells
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+/// A single table cell
+///
+/// The text may already contain escape sequences, they don't count towards the width.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cell {
+    /// Cell text
+    text: String,
+    /// Style of the cell, takes precedence over the column style
+    style: Style,
+}
+
+impl Cell {
+    /// Creates a new unstyled cell
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            style: Style::new(),
+        }
+    }
+
+    /// Creates a new cell styled by the provided spec
+    pub fn new_with_spec(
+        text: impl Into<String>,
+        spec: impl AsRef<str>,
+    ) -> Result<Self, StylerError> {
+        Ok(Self::new(text).style(Style::new_from_cli_spec(spec)?))
+    }
+
+    /// Set the style of the cell
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// A table of styled cells
+///
+/// Column widths are measured with [`display_width`], so pre-styled and wide
+/// text lines up correctly.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{border::Border, table::{Align, Column, Table}};
+///
+/// let table = Table::new()
+///     .border(Border::Ascii)
+///     .column(Column::new("name"))
+///     .column(Column::new("size").align(Align::Right))
+///     .row(["a.txt", "12"])
+///     .row(["b.txt", "1024"]);
+///
+/// assert_eq!(
+///     table.render(),
+///     "+-------+------+\n\
+///      | name  | size |\n\
+///      +-------+------+\n\
+///      | a.txt |   12 |\n\
+///      | b.txt | 1024 |\n\
+///      +-------+------+"
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// Column definitions, rows may be wider than this
+    columns: Vec<Column>,
+    /// Body rows
+    rows: Vec<Vec<Cell>>,
+    /// Line style of the borders
+    border: Border,
+    /// Style applied to the border lines
+    border_style: Style,
+    /// Style applied to the header cells
+    header_style: Style,
+}
+
+impl Table {
+    /// Creates a new, empty [`Table`] with [`Border::Light`] borders
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the line style of the borders
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Set the style of the border lines
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Set the style of the header row
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    /// Add a column
+    pub fn column(mut self, column: Column) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Add a row of cells
+    pub fn row<I>(mut self, cells: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Cell>,
+    {
+        self.push_row(cells);
+        self
+    }
+
+    /// Add a row of cells in place
+    pub fn push_row<I>(&mut self, cells: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Cell>,
+    {
+        self.rows.push(cells.into_iter().map(Into::into).collect());
+    }
+
+    /// Render the table, lines are separated by `\n` without a trailing newline
+    pub fn render(&self) -> String {
+        let count = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain([self.columns.len()])
+            .max()
+            .unwrap_or(0);
+
+        if count == 0 {
+            return String::new();
+        }
+
+        let column = |i: usize| self.columns.get(i).cloned().unwrap_or_default();
+        let has_header = self.columns.iter().any(|col| !col.header.is_empty());
+
+        let mut widths = vec![0; count];
+        for (i, width) in widths.iter_mut().enumerate() {
+            let header = has_header.then(|| display_width(&column(i).header));
+            let cells = self
+                .rows
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| display_width(&cell.text));
+
+            *width = header.into_iter().chain(cells).max().unwrap_or(0);
+        }
+
+        let chars = self.border.chars();
+        let rule = |[left, mid, right]: [char; 3], h: char| {
+            let segments = widths
+                .iter()
+                .map(|&w| h.to_string().repeat(w + 2))
+                .collect::<Vec<_>>();
+
+            self.border_style
+                .style(format!("{left}{}{right}", segments.join(&mid.to_string())))
+        };
+        let line = |cells: Vec<(String, Align)>| {
+            let cells = cells
+                .into_iter()
+                .zip(&widths)
+                .map(|((text, align), &w)| {
+                    format!(" {} ", align.pad(&text, display_width(&text), w))
+                })
+                .collect::<Vec<_>>();
+
+            match chars {
+                Some(chars) => {
+                    let v = self.border_style.style(chars.v.to_string());
+                    format!("{v}{}{v}", cells.join(&v))
+                }
+                None => cells.join("").trim_end().to_string(),
+            }
+        };
+
+        let mut lines = Vec::new();
+
+        if let Some(chars) = chars {
+            lines.push(rule(chars.top, chars.h));
+        }
+
+        if has_header {
+            lines.push(line(
+                (0..count)
+                    .map(|i| {
+                        let col = column(i);
+                        (self.header_style.style(&col.header), col.align)
+                    })
+                    .collect(),
+            ));
+
+            if let Some(chars) = chars {
+                lines.push(rule(chars.mid, chars.h));
+            }
+        }
+
+        for row in &self.rows {
+            lines.push(line(
+                (0..count)
+                    .map(|i| {
+                        let col = column(i);
+                        let text = row.get(i).map_or(String::new(), |cell| {
+                            (col.style | cell.style).style(&cell.text)
+                        });
+                        (text, col.align)
+                    })
+                    .collect(),
+            ));
+        }
+
+        if let Some(chars) = chars {
+            lines.push(rule(chars.bottom, chars.h));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render())
+    }
+}
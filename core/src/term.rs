@@ -0,0 +1,198 @@
+//! Query the terminal for its actual colors with OSC 10/11/4
+//!
+//! The queries switch the controlling terminal (`/dev/tty`) into raw mode for the
+//! duration of the round trip and give up after [`TIMEOUT`]. A primary device
+//! attributes request is sent along, so terminals which don't answer color queries
+//! are detected without waiting for the timeout.
+//!
+//! Without a controlling terminal, on non-Unix platforms, or when the terminal
+//! doesn't answer, every query returns `None`.
+
+use std::time::Duration;
+
+use crate::style::Color;
+
+/// Maximum time to wait for the terminal's reply
+pub const TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Get the terminal's default foreground color (OSC 10)
+pub fn query_foreground() -> Option<Color> {
+    query("10")
+}
+
+/// Get the terminal's default background color (OSC 11)
+///
+/// # Example
+/// ```rust,no_run
+/// use cli_styler::term;
+///
+/// let dark = term::query_background().is_none_or(|bg| bg.luminance() < 0.5);
+/// # let _ = dark;
+/// ```
+pub fn query_background() -> Option<Color> {
+    query("11")
+}
+
+/// Get the RGB value the terminal uses for an entry of its 256-color palette (OSC 4)
+pub fn query_palette(index: u8) -> Option<Color> {
+    query(&format!("4;{index}"))
+}
+
+/// Send the OSC color query `param` and parse the reply
+fn query(param: &str) -> Option<Color> {
+    let reply = imp::round_trip(&format!("\x1b]{param};?\x07\x1b[c"))?;
+    parse_reply(&reply)
+}
+
+/// Extract the color from an OSC color reply (`\x1b]11;rgb:1e1e/1e1e/2e2e\x07`)
+///
+/// Channels may have 1 to 4 hex digits each and are scaled to 8 bits.
+pub(crate) fn parse_reply(reply: &str) -> Option<Color> {
+    let body = reply.strip_prefix("\x1b]")?;
+    let body = body
+        .strip_suffix('\x07')
+        .or_else(|| body.strip_suffix("\x1b\\"))?;
+
+    let rgb = body.split_once("rgb:")?.1;
+    let mut channels = rgb.split('/').map(|hex| {
+        if !(1..=4).contains(&hex.len()) {
+            return None;
+        }
+
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1 << (4 * hex.len())) - 1;
+        Some(((value * 255 + max / 2) / max) as u8)
+    });
+
+    let mut next = || channels.next().flatten();
+    let color = Color::RGB(next()?, next()?, next()?);
+
+    channels.next().is_none().then_some(color)
+}
+
+/// Find the OSC reply in front of the device attributes reply
+///
+/// Returns `None` while the device attributes reply is incomplete, and `Some(None)`
+/// when the terminal answered it without answering the color query first.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn split_replies(buf: &[u8]) -> Option<Option<String>> {
+    let text = String::from_utf8_lossy(buf);
+
+    let da = text.find("\x1b[?")?;
+    text[da..].find('c')?;
+
+    let osc = text[..da].find("\x1b]").and_then(|start| {
+        let rest = &text[start..da];
+        let end = rest
+            .find('\x07')
+            .map(|i| i + 1)
+            .or_else(|| rest.find("\x1b\\").map(|i| i + 2))?;
+        Some(rest[..end].to_string())
+    });
+
+    Some(osc)
+}
+
+/// Raw-mode round trips over `/dev/tty`
+#[cfg(unix)]
+mod imp {
+    use std::{
+        fs::{File, OpenOptions},
+        io::{Read, Write},
+        os::fd::AsRawFd,
+        time::Instant,
+    };
+
+    use super::{TIMEOUT, split_replies};
+
+    /// Restores the original terminal settings when dropped
+    struct RawMode {
+        /// Descriptor of the terminal
+        fd: i32,
+        /// Settings before raw mode was entered
+        original: libc::termios,
+    }
+
+    impl RawMode {
+        /// Disable echo and line buffering on `tty`
+        fn enter(tty: &File) -> Option<Self> {
+            let fd = tty.as_raw_fd();
+
+            // SAFETY: `termios` is plain old data, it is fully written by `tcgetattr`
+            let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+            // SAFETY: `fd` is an open descriptor and `original` a valid out pointer
+            if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+                return None;
+            }
+
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 0;
+            raw.c_cc[libc::VTIME] = 0;
+
+            // SAFETY: `fd` is an open descriptor and `raw` a valid `termios`
+            if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+                return None;
+            }
+
+            Some(Self { fd, original })
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // SAFETY: restores settings read from the same, still open descriptor
+            unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.original) };
+        }
+    }
+
+    /// Write `query` to the terminal and collect its reply
+    pub(super) fn round_trip(query: &str) -> Option<String> {
+        let mut tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/tty")
+            .ok()?;
+        let _raw = RawMode::enter(&tty)?;
+
+        tty.write_all(query.as_bytes()).ok()?;
+        tty.flush().ok()?;
+
+        let deadline = Instant::now() + TIMEOUT;
+        let mut buf = Vec::new();
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut fds = libc::pollfd {
+                fd: tty.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+
+            // SAFETY: `fds` points to exactly one valid `pollfd`
+            let ready = unsafe { libc::poll(&mut fds, 1, remaining.as_millis() as i32) };
+            if ready <= 0 {
+                return None;
+            }
+
+            let mut chunk = [0; 64];
+            match tty.read(&mut chunk).ok()? {
+                0 => return None,
+                n => buf.extend_from_slice(&chunk[..n]),
+            }
+
+            if let Some(reply) = split_replies(&buf) {
+                return reply;
+            }
+        }
+    }
+}
+
+/// Fallback without raw mode support, nothing is queried
+#[cfg(not(unix))]
+mod imp {
+    /// Always `None`
+    pub(super) fn round_trip(_query: &str) -> Option<String> {
+        None
+    }
+}
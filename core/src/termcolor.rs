@@ -0,0 +1,103 @@
+//! [`termcolor`] output, for legacy Windows consoles without VT processing
+//!
+//! [`termcolor`] talks to the console API where escape sequences aren't understood,
+//! so styles are converted to a [`ColorSpec`] instead of being rendered as SGR codes.
+
+use std::io::{self, Write};
+
+use ::termcolor::{ColorSpec, WriteColor};
+use cli_styler_spec::__private;
+
+use crate::{spans::StyledString, style::Style};
+
+#[cfg(feature = "markup")]
+use crate::{markup::Markup, style::ColorLevel};
+
+/// Writes styled text through a [`WriteColor`], e.g. [`termcolor::StandardStream`](::termcolor::StandardStream)
+///
+/// Honors [`crate::set_enabled`] and [`caps::set_color_choice`](crate::caps::set_color_choice) on top of the stream's own choice.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, termcolor::StyledWriter};
+/// use termcolor::Ansi;
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let mut w = StyledWriter::new(Ansi::new(Vec::new()));
+/// w.write_styled(&Style::new().fg(Color::Red).bold(), "error").unwrap();
+///
+/// assert_eq!(w.into_inner().into_inner(), b"\x1b[0m\x1b[1m\x1b[31merror\x1b[0m");
+/// ```
+#[derive(Debug)]
+pub struct StyledWriter<W> {
+    /// Wrapped writer
+    inner: W,
+}
+
+impl<W: WriteColor> StyledWriter<W> {
+    /// Wraps `inner`
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Write `text` with `style` applied, resetting afterwards
+    pub fn write_styled(&mut self, style: &Style, text: &str) -> io::Result<()> {
+        if style.is_empty() || !__private::should_colorize() {
+            return self.inner.write_all(text.as_bytes());
+        }
+
+        self.inner.set_color(&ColorSpec::from(*style))?;
+        self.inner.write_all(text.as_bytes())?;
+        self.inner.reset()
+    }
+
+    /// Write every span of a [`StyledString`]
+    pub fn write_spans(&mut self, spans: &StyledString) -> io::Result<()> {
+        spans
+            .iter()
+            .try_for_each(|(text, style)| self.write_styled(style, text))
+    }
+
+    /// Write a [`Markup`] tree, each text node with the style of its innermost tag
+    #[cfg(feature = "markup")]
+    pub fn write_markup(&mut self, markup: &Markup) -> io::Result<()> {
+        let mut result = Ok(());
+        let level = match self.inner.supports_color() {
+            true => __private::render_level(),
+            false => ColorLevel::None,
+        };
+
+        markup.for_each_text(level, &mut |text, style| {
+            if result.is_ok() {
+                result = self.write_styled(style, text);
+            }
+        });
+
+        result
+    }
+
+    /// Get a reference to the wrapped writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: WriteColor> Write for StyledWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,156 @@
+//! [`tracing_subscriber`] event formatting themed with a [`Theme`]
+//!
+//! Colors follow [`crate::set_enabled`] and [`caps::set_color_choice`](crate::caps::set_color_choice),
+//! not the subscriber's own ANSI setting.
+
+use std::fmt::{self, Write};
+
+use tracing_core::{
+    Event, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{
+    fmt::{FmtContext, FormatEvent, FormatFields, format::Writer},
+    registry::LookupSpan,
+};
+
+use crate::{error::StylerError, theme::Theme};
+
+/// Formats `tracing` events as `LEVEL target: message key=value`, styled by theme entries
+///
+/// The entries used are `error`, `warning`, `info`, `debug` and `trace` for the
+/// levels, `target` for the event target and `field` for field names. Missing
+/// entries leave that part unstyled.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::tracing::EventFormatter;
+///
+/// let format = EventFormatter::new()
+///     .with_spec("target", "f b m i")
+///     .unwrap()
+///     .with_target(true);
+///
+/// tracing_subscriber::fmt().event_format(format).init();
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventFormatter {
+    /// Styles of the level, target and field names
+    theme: Theme,
+    /// Whether to print the event target
+    target: bool,
+}
+
+impl Default for EventFormatter {
+    fn default() -> Self {
+        Self {
+            theme: Theme::logging(),
+            target: true,
+        }
+    }
+}
+
+impl EventFormatter {
+    /// Creates a formatter with the [logging theme](Theme::logging)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a formatter using only the entries of `theme`
+    pub fn with_theme(theme: Theme) -> Self {
+        Self {
+            theme,
+            ..Self::default()
+        }
+    }
+
+    /// Set the style of one entry from a spec
+    pub fn with_spec(mut self, name: &str, spec: impl AsRef<str>) -> Result<Self, StylerError> {
+        self.theme = self.theme.with_spec(name, spec)?;
+        Ok(self)
+    }
+
+    /// Set whether the event target is printed
+    pub fn with_target(mut self, target: bool) -> Self {
+        self.target = target;
+        self
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for EventFormatter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let level = *meta.level();
+
+        write!(
+            writer,
+            "{} ",
+            self.theme
+                .style_level(level.as_str(), format!("{level:>5}"))
+        )?;
+
+        if self.target {
+            write!(writer, "{}: ", self.theme.style("target", meta.target()))?;
+        }
+
+        let mut visitor = FieldVisitor {
+            theme: &self.theme,
+            message: String::new(),
+            fields: String::new(),
+        };
+        event.record(&mut visitor);
+
+        match (visitor.message.is_empty(), visitor.fields.is_empty()) {
+            (_, true) => writeln!(writer, "{}", visitor.message),
+            (true, false) => writeln!(writer, "{}", visitor.fields),
+            (false, false) => writeln!(writer, "{} {}", visitor.message, visitor.fields),
+        }
+    }
+}
+
+/// Collects the message and the other fields as `name=value` pairs
+struct FieldVisitor<'a> {
+    /// Theme providing the `field` style
+    theme: &'a Theme,
+    /// Formatted `message` field
+    message: String,
+    /// Formatted other fields
+    fields: String,
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+            return;
+        }
+
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+
+        // Writing into a `String` never fails
+        let _ = write!(
+            self.fields,
+            "{}={value:?}",
+            self.theme.style("field", field.name())
+        );
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
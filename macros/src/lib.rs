@@ -0,0 +1,240 @@
+//! Procedural macros for `cli-styler`, use them through its `macros` feature
+
+#[cfg(feature = "markup")]
+use cli_styler_spec::parser;
+use cli_styler_spec::{__private, style};
+use proc_macro::TokenStream;
+#[cfg(feature = "markup")]
+use quote::format_ident;
+use quote::quote;
+#[cfg(feature = "markup")]
+use syn::{
+    Expr, Ident, Token,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
+use syn::{LitStr, parse_macro_input};
+
+/// Parse a style spec at compile time into a `const`-constructible `CompiledStyle`
+///
+/// Invalid specs are reported as compile errors pointing at the literal.
+#[proc_macro]
+pub fn style(input: TokenStream) -> TokenStream {
+    let spec = parse_macro_input!(input as LitStr);
+
+    match style::Style::new_from_cli_spec(spec.value()) {
+        Ok(style) => {
+            let params = __private::sgr_params(&style);
+            quote!(::cli_styler::style::CompiledStyle::from_static(#params)).into()
+        }
+        Err(err) => syn::Error::new(spec.span(), err).to_compile_error().into(),
+    }
+}
+
+/// Validate markup at compile time, expanding to the `Markup` built from it
+///
+/// Invalid markup is reported as a compile error pointing at the literal.
+#[cfg(feature = "markup")]
+#[proc_macro]
+pub fn markup(input: TokenStream) -> TokenStream {
+    let src = parse_macro_input!(input as LitStr);
+
+    match __private::tokenize(
+        src.value(),
+        &__private::Mk,
+        None,
+        &style::ParseOptions::new(),
+    ) {
+        Ok(tokens)
+            if tokens
+                .iter()
+                .any(|(token, _)| matches!(token, parser::Token::Include(_))) =>
+        {
+            let err = "`<include ...>` needs fragments, use `Markup::new_with_fragments`";
+            syn::Error::new(src.span(), err).to_compile_error().into()
+        }
+        Ok(_) => quote!(
+            ::cli_styler::markup::Markup::new(#src).expect("markup is validated at compile time")
+        )
+        .into(),
+        Err(err) => syn::Error::new(src.span(), err).to_compile_error().into(),
+    }
+}
+
+/// Input of [`styled!`]: a markup format string followed by `format!` arguments
+#[cfg(feature = "markup")]
+struct StyledInput {
+    /// Markup format string
+    fmt: LitStr,
+    /// Arguments passed on to `format!`
+    args: Punctuated<Expr, Token![,]>,
+}
+
+#[cfg(feature = "markup")]
+impl Parse for StyledInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fmt = input.parse()?;
+
+        let args = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::parse_terminated(input)?
+        };
+
+        Ok(Self { fmt, args })
+    }
+}
+
+/// `format!` with a markup format string, the markup is parsed at compile time
+///
+/// Interpolated values are never parsed as markup. Styles are only emitted while
+/// `cli_styler::is_enabled()` holds, shortcodes in the literal text are resolved like
+/// `Markup` renders them.
+#[cfg(feature = "markup")]
+#[proc_macro]
+pub fn styled(input: TokenStream) -> TokenStream {
+    let StyledInput { fmt, args } = parse_macro_input!(input as StyledInput);
+
+    let tokens = match __private::tokenize(
+        fmt.value(),
+        &__private::Mk,
+        None,
+        &style::ParseOptions::new(),
+    ) {
+        Ok(tokens) => tokens,
+        Err(err) => return syn::Error::new(fmt.span(), err).to_compile_error().into(),
+    };
+
+    // Mirror the markup tree: every text node is styled with its innermost tag only
+    let mut stack = vec![style::Style::new()];
+    let mut format = String::new();
+    let mut prefixes = Vec::new();
+    let mut texts = Vec::new();
+
+    for (token, _) in tokens {
+        let (text, raw) = match token {
+            parser::Token::Fmt(style) => {
+                stack.push(style);
+                continue;
+            }
+            parser::Token::Empty => {
+                stack.push(style::Style::new());
+                continue;
+            }
+            parser::Token::If(_) => {
+                let err = "`<if ...>` sections are only supported by `Markup`, not `styled!`";
+                return syn::Error::new(fmt.span(), err).to_compile_error().into();
+            }
+            parser::Token::Include(_) => {
+                let err = "`<include ...>` tags are only supported by `Markup`, not `styled!`";
+                return syn::Error::new(fmt.span(), err).to_compile_error().into();
+            }
+            parser::Token::End => {
+                stack.pop();
+                continue;
+            }
+            parser::Token::Text(text) => (text, false),
+            parser::Token::Raw(text) => (text, true),
+            // `<code ...>` sections, with the `syntect` feature
+            _ => {
+                let err = "`<code ...>` sections are only supported by `Markup`, not `styled!`";
+                return syn::Error::new(fmt.span(), err).to_compile_error().into();
+            }
+        };
+
+        let params = stack.last().map(__private::sgr_params).unwrap_or_default();
+        if !params.is_empty() {
+            let name = format!("__cli_styler_{}", prefixes.len());
+            format.push_str(&format!("{{{name}}}"));
+            prefixes.push((format_ident!("{name}"), format!("\x1b[{params}m")));
+        }
+
+        if raw {
+            // Nothing inside `<raw>` is a placeholder or a shortcode
+            format.push_str(&text.replace('{', "{{").replace('}', "}}"));
+        } else {
+            push_text(&text, &mut format, &mut texts);
+        }
+
+        if !params.is_empty() {
+            format.push_str("{__cli_styler_reset}");
+        }
+    }
+
+    // Keep the user's span so inline arguments (`{count}`) resolve at the call site
+    let format = LitStr::new(&format, fmt.span());
+    let args = args.iter();
+    let names = prefixes.iter().map(|(name, _)| name);
+    let codes = prefixes.iter().map(|(_, code)| code);
+    let text_names = texts.iter().map(|(name, _)| name);
+    let text_values = texts.iter().map(|(_, text)| text);
+    let styled = (!prefixes.is_empty()).then(|| {
+        (
+            quote!(let __cli_styler_on = ::cli_styler::is_enabled();),
+            quote!(__cli_styler_reset = if __cli_styler_on { "\x1b[0m" } else { "" },),
+        )
+    });
+    let (on, reset) = styled.unzip();
+
+    quote!({
+        #on
+        ::std::format!(
+            #format,
+            #(#args,)*
+            #(#names = if __cli_styler_on { #codes } else { "" },)*
+            #(#text_names = ::cli_styler::__private::shortcoded(#text_values),)*
+            #reset
+        )
+    })
+    .into()
+}
+
+/// Append markup text to a [`styled!`] format string
+///
+/// Literal parts which may hold shortcodes become named arguments, so they're
+/// resolved with the shortcode setting at the time of formatting.
+#[cfg(feature = "markup")]
+fn push_text(text: &str, format: &mut String, texts: &mut Vec<(Ident, String)>) {
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' | '}' if chars.peek() == Some(&ch) => {
+                chars.next();
+                literal.push(ch);
+            }
+            // Placeholders are kept as written, `format!` reports the invalid ones
+            '{' | '}' => {
+                push_literal(&mut literal, format, texts);
+                format.push(ch);
+
+                if ch == '{' {
+                    for ch in chars.by_ref() {
+                        format.push(ch);
+                        if ch == '}' {
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => literal.push(ch),
+        }
+    }
+
+    push_literal(&mut literal, format, texts);
+}
+
+/// Move the pending `literal` text of [`push_text`] into the format string or its arguments
+#[cfg(feature = "markup")]
+fn push_literal(literal: &mut String, format: &mut String, texts: &mut Vec<(Ident, String)>) {
+    if literal.contains(':') {
+        let name = format_ident!("__cli_styler_text_{}", texts.len());
+        format.push_str(&format!("{{{name}}}"));
+        texts.push((name, std::mem::take(literal)));
+    } else {
+        format.push_str(&literal.replace('{', "{{").replace('}', "}}"));
+        literal.clear();
+    }
+}
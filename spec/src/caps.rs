@@ -0,0 +1,400 @@
+//! Terminal capability detection
+//!
+//! Inspects `COLORTERM`, `TERM`, `TERM_PROGRAM`, the Windows console version and
+//! whether the stream is a terminal to decide which [`ColorLevel`] to target.
+//!
+//! The standard `NO_COLOR`, `CLICOLOR`, `CLICOLOR_FORCE` and `FORCE_COLOR` variables
+//! are honored, and can be overridden programmatically with [`set_color_choice`].
+
+use std::{
+    env,
+    io::{self, IsTerminal},
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, AtomicU8, Ordering},
+    },
+};
+
+use crate::style::ColorLevel;
+
+#[cfg(feature = "markup")]
+use crate::style::Shortcodes;
+
+/// Whether styled output should be produced
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum ColorChoice {
+    /// Decide from the environment (`NO_COLOR`, `CLICOLOR_FORCE`, tty detection)
+    #[default]
+    Auto,
+    /// Always emit escape sequences
+    Always,
+    /// Never emit escape sequences
+    Never,
+}
+
+/// Global [`ColorChoice`], stored as its discriminant
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// Override the environment-derived color behaviour for the whole process
+///
+/// [`ColorChoice::Never`] makes every `style()`, `paint()` and markup render emit plain text.
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Get the current global [`ColorChoice`]
+pub fn color_choice() -> ColorChoice {
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Global on/off switch of `cli_styler::set_enabled`, checked before the [`ColorChoice`]
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Flip the switch of `cli_styler::set_enabled` without touching the [`ColorChoice`]
+pub(crate) fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Get the [`ColorChoice`] in effect, [`ColorChoice::Never`] while disabled
+fn effective_choice() -> ColorChoice {
+    if ENABLED.load(Ordering::Relaxed) {
+        color_choice()
+    } else {
+        ColorChoice::Never
+    }
+}
+
+/// Check whether styling functions should emit escape sequences at all
+///
+/// Unlike [`detect`] this does not look at tty-ness, since styled strings are not
+/// necessarily written to standard output.
+pub(crate) fn should_colorize() -> bool {
+    colorize(effective_choice(), env_flags())
+}
+
+/// Decide [`should_colorize`] from a [`ColorChoice`] and the environment flags
+pub(crate) fn colorize(choice: ColorChoice, flags: EnvFlags) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !flags.no_color,
+    }
+}
+
+/// Get the color level markup is rendered for, see [`Condition`]
+///
+/// Styled output doesn't depend on tty-ness, so while [`should_colorize`] holds this
+/// is at least [`ColorLevel::Ansi16`].
+#[cfg(feature = "markup")]
+pub(crate) fn render_level() -> ColorLevel {
+    if should_colorize() {
+        detect().max(ColorLevel::Ansi16)
+    } else {
+        ColorLevel::None
+    }
+}
+
+/// Global [`Shortcodes`] setting of markup renders, stored as its discriminant
+#[cfg(feature = "markup")]
+static SHORTCODES: AtomicU8 = AtomicU8::new(Shortcodes::Off as u8);
+
+/// Choose how markup renders shortcodes like `:warning:` or `:check:` for the whole process
+///
+/// Like `<if ...>` sections, shortcodes are kept in the parsed `Markup` and only resolved
+/// when it's rendered. Unknown shortcodes and the content of `<raw>` sections are kept
+/// as written.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{caps, markup::{Markup, MarkupStream}, style::Shortcodes};
+///
+/// let markup = Markup::new(":warning: low disk :unknown: <raw>:x:</raw>").unwrap();
+/// assert_eq!(markup.render_plain(), ":warning: low disk :unknown: :x:");
+///
+/// caps::set_shortcodes(Shortcodes::Ascii);
+/// assert_eq!(markup.render_plain(), "! low disk :unknown: :x:");
+///
+/// caps::set_shortcodes(Shortcodes::Unicode);
+/// assert_eq!(markup.render_plain(), "⚠ low disk :unknown: :x:");
+/// assert_eq!(markup.to_markup_string(), ":warning: low disk :unknown: <raw>:x:</raw>");
+///
+/// // Streams hold back a shortcode split across chunks
+/// let mut stream = MarkupStream::new();
+/// let mut output = stream.feed("go :rock").unwrap();
+/// assert_eq!(output, "go ");
+///
+/// output += &stream.feed("et:").unwrap();
+/// assert_eq!(output, "go 🚀");
+/// ```
+#[cfg(feature = "markup")]
+pub fn set_shortcodes(shortcodes: Shortcodes) {
+    SHORTCODES.store(shortcodes as u8, Ordering::Relaxed);
+}
+
+/// Get the current global [`Shortcodes`] setting
+#[cfg(feature = "markup")]
+pub fn shortcodes() -> Shortcodes {
+    match SHORTCODES.load(Ordering::Relaxed) {
+        1 => Shortcodes::Unicode,
+        2 => Shortcodes::Ascii,
+        3 => Shortcodes::Auto,
+        _ => Shortcodes::Off,
+    }
+}
+
+/// Get whether markup renders shortcodes as their ASCII fallback, `None` while they're kept
+#[cfg(feature = "markup")]
+pub(crate) fn shortcodes_ascii() -> Option<bool> {
+    match shortcodes() {
+        Shortcodes::Off => None,
+        Shortcodes::Unicode => Some(false),
+        Shortcodes::Ascii => Some(true),
+        Shortcodes::Auto => Some(!supports_unicode()),
+    }
+}
+
+/// Color related environment variables, read once per process
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EnvFlags {
+    /// `NO_COLOR` is set to a non-empty value, or `CLICOLOR=0`
+    pub(crate) no_color: bool,
+    /// `CLICOLOR_FORCE` or `FORCE_COLOR` request colors regardless of tty-ness
+    pub(crate) force: bool,
+}
+
+/// Get the cached [`EnvFlags`]
+fn env_flags() -> EnvFlags {
+    static FLAGS: OnceLock<EnvFlags> = OnceLock::new();
+
+    *FLAGS.get_or_init(|| flags_from_env(|key| env::var(key).ok()))
+}
+
+/// Derive the [`EnvFlags`] from an environment lookup
+pub(crate) fn flags_from_env(var: impl Fn(&str) -> Option<String>) -> EnvFlags {
+    let enabled = |key| var(key).is_some_and(|v| !v.is_empty() && v != "0" && v != "false");
+
+    EnvFlags {
+        no_color: var("NO_COLOR").is_some_and(|v| !v.is_empty())
+            || var("CLICOLOR").as_deref() == Some("0"),
+        force: enabled("CLICOLOR_FORCE") || enabled("FORCE_COLOR"),
+    }
+}
+
+/// Check whether the terminal can be expected to render Unicode symbols
+///
+/// The locale (`LC_ALL`, `LC_CTYPE`, `LANG`) has to be UTF-8 and the terminal not the
+/// Linux console. On Windows, only Windows Terminal and terminals setting `TERM_PROGRAM`
+/// are trusted. The result is read once per process.
+pub fn supports_unicode() -> bool {
+    static UNICODE: OnceLock<bool> = OnceLock::new();
+
+    *UNICODE.get_or_init(|| unicode_from_env(|key| env::var(key).ok()))
+}
+
+/// Decide Unicode support from an environment lookup, see [`supports_unicode`]
+pub(crate) fn unicode_from_env(var: impl Fn(&str) -> Option<String>) -> bool {
+    if cfg!(windows) {
+        return var("WT_SESSION").is_some() || var("TERM_PROGRAM").is_some();
+    }
+
+    if var("TERM").as_deref() == Some("linux") {
+        return false;
+    }
+
+    // The first one set wins, like in the C library
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|key| var(key).filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+/// Detect the color level supported by standard output
+pub fn detect() -> ColorLevel {
+    detect_for(&io::stdout())
+}
+
+/// Detect the color level supported by the given stream
+pub fn detect_for(stream: &impl IsTerminal) -> ColorLevel {
+    let force = match effective_choice() {
+        ColorChoice::Never => return ColorLevel::None,
+        ColorChoice::Always => true,
+        ColorChoice::Auto => {
+            let flags = env_flags();
+
+            if flags.no_color {
+                return ColorLevel::None;
+            }
+
+            flags.force
+        }
+    };
+
+    let level = level_from_env(|key| env::var(key).ok(), force || stream.is_terminal());
+
+    // Forcing colors into a pipe or an unknown terminal still needs *some* colors
+    if force {
+        level.max(ColorLevel::Ansi16)
+    } else {
+        level
+    }
+}
+
+/// Decide the color level from an environment lookup and the tty-ness of the stream
+pub(crate) fn level_from_env(var: impl Fn(&str) -> Option<String>, is_tty: bool) -> ColorLevel {
+    if !is_tty {
+        return ColorLevel::None;
+    }
+
+    let term = var("TERM").unwrap_or_default();
+
+    if term == "dumb" {
+        return ColorLevel::None;
+    }
+
+    if matches!(var("COLORTERM").as_deref(), Some("truecolor" | "24bit"))
+        || term.ends_with("-direct")
+        || term.contains("truecolor")
+        || var("WT_SESSION").is_some()
+    {
+        return ColorLevel::TrueColor;
+    }
+
+    match var("TERM_PROGRAM").as_deref() {
+        Some("iTerm.app" | "WezTerm" | "vscode") => return ColorLevel::TrueColor,
+        Some("Apple_Terminal") => return ColorLevel::Ansi256,
+        _ => {}
+    }
+
+    if term.contains("256") {
+        return ColorLevel::Ansi256;
+    }
+
+    if !term.is_empty() {
+        return ColorLevel::Ansi16;
+    }
+
+    windows_console_level()
+}
+
+/// Color level of the native Windows console, based on the OS build number
+///
+/// Build 10586 introduced VT processing with 256 colors, 14931 added 24-bit colors.
+#[cfg(windows)]
+fn windows_console_level() -> ColorLevel {
+    match windows_build() {
+        Some(build) if build >= 14931 => ColorLevel::TrueColor,
+        Some(build) if build >= 10586 => ColorLevel::Ansi256,
+        _ => ColorLevel::Ansi16,
+    }
+}
+
+/// Without a `TERM` there is no indication of color support outside Windows
+#[cfg(not(windows))]
+fn windows_console_level() -> ColorLevel {
+    ColorLevel::None
+}
+
+/// Query the Windows build number through `RtlGetVersion`
+#[cfg(windows)]
+fn windows_build() -> Option<u32> {
+    /// Mirror of `OSVERSIONINFOW`
+    #[repr(C)]
+    #[allow(clippy::missing_docs_in_private_items)]
+    struct OsVersionInfo {
+        size: u32,
+        major: u32,
+        minor: u32,
+        build: u32,
+        platform: u32,
+        csd_version: [u16; 128],
+    }
+
+    #[link(name = "ntdll")]
+    unsafe extern "system" {
+        fn RtlGetVersion(info: *mut OsVersionInfo) -> i32;
+    }
+
+    let mut info = OsVersionInfo {
+        size: size_of::<OsVersionInfo>() as u32,
+        major: 0,
+        minor: 0,
+        build: 0,
+        platform: 0,
+        csd_version: [0; 128],
+    };
+
+    // SAFETY: `info` is a properly sized and initialised `OSVERSIONINFOW`
+    let status = unsafe { RtlGetVersion(&mut info) };
+
+    (status == 0 && info.major >= 10).then_some(info.build)
+}
+
+/// A capability an `<if ...>` markup section depends on
+///
+/// The section is only rendered when the condition holds for the color level the
+/// markup is rendered for.
+#[cfg(feature = "markup")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Condition {
+    /// 24-bit colors (`truecolor`)
+    TrueColor,
+    /// At least the 256-color palette (`ansi256`)
+    Ansi256,
+    /// Any colors at all (`color`)
+    Color,
+    /// No colors (`no-color`, `!color`)
+    NoColor,
+    /// Less than 24-bit colors (`!truecolor`)
+    NotTrueColor,
+    /// Less than 256 colors (`!ansi256`)
+    NotAnsi256,
+}
+
+#[cfg(feature = "markup")]
+impl Condition {
+    /// Parse the name of a condition, as written in `<if ...>`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "truecolor" => Self::TrueColor,
+            "ansi256" => Self::Ansi256,
+            "color" | "!no-color" => Self::Color,
+            "no-color" | "!color" => Self::NoColor,
+            "!truecolor" => Self::NotTrueColor,
+            "!ansi256" => Self::NotAnsi256,
+            _ => return None,
+        })
+    }
+
+    /// Get the canonical name of the condition
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::TrueColor => "truecolor",
+            Self::Ansi256 => "ansi256",
+            Self::Color => "color",
+            Self::NoColor => "no-color",
+            Self::NotTrueColor => "!truecolor",
+            Self::NotAnsi256 => "!ansi256",
+        }
+    }
+
+    /// Check whether the condition holds when rendering for `level`
+    pub fn holds(self, level: ColorLevel) -> bool {
+        match self {
+            Self::TrueColor => level == ColorLevel::TrueColor,
+            Self::Ansi256 => level >= ColorLevel::Ansi256,
+            Self::Color => level > ColorLevel::None,
+            Self::NoColor => level == ColorLevel::None,
+            Self::NotTrueColor => level < ColorLevel::TrueColor,
+            Self::NotAnsi256 => level < ColorLevel::Ansi256,
+        }
+    }
+}
@@ -0,0 +1,401 @@
+//! Color space conversions and arithmetic for [`Color`]
+
+use crate::{
+    names,
+    style::Color::{self, *},
+};
+
+/// Default xterm RGB values of the 16 ANSI colors (normal followed by bright)
+pub(crate) const ANSI_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel levels used by the 6x6x6 color cube of the 256-color palette
+pub(crate) const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Get the RGB value of an entry in the 256-color palette
+pub(crate) fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_PALETTE[i as usize],
+        16..=231 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+/// Squared euclidean distance between two RGB colors
+pub(crate) fn distance_sq((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// FNV-1a hash of `bytes`, whose output doesn't depend on the Rust version or platform
+///
+/// [`Hash`](std::hash::Hash) impls can't be used for this, how `str` feeds a hasher is
+/// an implementation detail of the standard library.
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    let mut z = bytes.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+    });
+
+    // Finalizer of splitmix64, so similar inputs (`worker-1`, `worker-2`) spread out
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Convert a `[0, 1]` channel to `u8`, clamping out-of-range values
+fn to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl Color {
+    /// Construct an RGB color from hue (degrees), saturation and lightness (`0.0..=1.0`)
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Self::from_hue_chroma(h, c, l - c / 2.0)
+    }
+
+    /// Construct an RGB color from hue (degrees), saturation and value (`0.0..=1.0`)
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+
+        let c = v * s;
+        Self::from_hue_chroma(h, c, v - c)
+    }
+
+    /// Pick a stable color for `input` (a username, module name, thread id, ...)
+    ///
+    /// Equal inputs always get the same color, across runs, platforms and Rust
+    /// versions, as only the bytes of the input are hashed. Numbers can be passed
+    /// as `n.to_le_bytes()`. Colors are spread around the hue wheel with a fixed
+    /// saturation and lightness, so they stay readable on dark and light backgrounds alike.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let alice = Color::from_hash("alice");
+    ///
+    /// assert_eq!(alice, Color::from_hash("alice"));
+    /// assert_ne!(alice, Color::from_hash("bob"));
+    /// ```
+    pub fn from_hash(input: impl AsRef<[u8]>) -> Self {
+        let hash = stable_hash(input.as_ref());
+        Self::from_hsl((hash % 360) as f32, 0.65, 0.6)
+    }
+
+    /// Get the RGB components of this color
+    ///
+    /// Basic and indexed colors resolve through the default xterm palette,
+    /// the terminal's default color is assumed to be the palette's white.
+    pub fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            RGB(r, g, b) | RGBA(r, g, b, _) => (r, g, b),
+            Indexed(i) => indexed_to_rgb(i),
+            TerminalDefault => ANSI_PALETTE[7],
+            color => ANSI_PALETTE[color.ansi_index() as usize],
+        }
+    }
+
+    /// Get the hue (degrees), saturation and lightness (`0.0..=1.0`) of this color
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (h, max, min) = self.hue_max_min();
+        let l = (max + min) / 2.0;
+        let d = max - min;
+
+        let s = if d == 0.0 {
+            0.0
+        } else {
+            d / (1.0 - (2.0 * l - 1.0).abs())
+        };
+
+        (h, s, l)
+    }
+
+    /// Get the hue (degrees), saturation and value (`0.0..=1.0`) of this color
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (h, max, min) = self.hue_max_min();
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        (h, s, max)
+    }
+
+    /// Increase the lightness by `amount` (`0.0..=1.0`), returning an RGB color
+    pub fn lighten(self, amount: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h, s, l + amount)
+    }
+
+    /// Decrease the lightness by `amount` (`0.0..=1.0`), returning an RGB color
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Increase the saturation by `amount` (`0.0..=1.0`), returning an RGB color
+    pub fn saturate(self, amount: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h, s + amount, l)
+    }
+
+    /// Decrease the saturation by `amount` (`0.0..=1.0`), returning an RGB color
+    pub fn desaturate(self, amount: f32) -> Self {
+        self.saturate(-amount)
+    }
+
+    /// Linearly interpolate between this color and `other`, returning an RGB color
+    ///
+    /// `t = 0.0` yields `self`, `t = 1.0` yields `other`; values outside are clamped.
+    /// Mixing a foreground at `t = 1.0 - alpha` over a known background composites it.
+    pub fn mix(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let (r1, g1, b1) = self.to_rgb();
+        let (r2, g2, b2) = other.to_rgb();
+
+        let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        RGB(lerp(r1, r2), lerp(g1, g2), lerp(b1, b2))
+    }
+
+    /// Get the alpha channel of this color, every color but [`Color::RGBA`] is opaque
+    pub fn alpha(self) -> u8 {
+        match self {
+            RGBA(_, _, _, a) => a,
+            _ => 255,
+        }
+    }
+
+    /// Blend a translucent color onto `background`, returning an opaque color
+    ///
+    /// Colors without an alpha channel are returned as-is.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let overlay: Color = "#ff000080".parse().unwrap();
+    ///
+    /// assert_eq!(overlay, Color::RGBA(255, 0, 0, 128));
+    /// assert_eq!(overlay.composite_over(Color::RGB(0, 0, 255)), Color::RGB(128, 0, 127));
+    /// assert_eq!(Color::Red.composite_over(Color::Blue), Color::Red);
+    /// ```
+    pub fn composite_over(self, background: Color) -> Self {
+        match self {
+            RGBA(r, g, b, a) => RGB(r, g, b).mix(background, 1.0 - a as f32 / 255.0),
+            color => color,
+        }
+    }
+
+    /// Get the nearest color of the 256-color palette
+    ///
+    /// RGB colors snap to the closer of the 6x6x6 cube and the grayscale ramp,
+    /// basic and indexed colors are already representable and returned as-is.
+    pub fn quantize_to_256(self) -> Self {
+        let (RGB(r, g, b) | RGBA(r, g, b, _)) = self else {
+            return self;
+        };
+
+        let level = |v: u8| {
+            (0..6)
+                .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs())
+                .unwrap_or(0) as u8
+        };
+
+        let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+        let avg = (r as u16 + g as u16 + b as u16) / 3;
+        let gray = 232 + ((avg.saturating_sub(3)) / 10).min(23) as u8;
+
+        let target = (r, g, b);
+        if distance_sq(indexed_to_rgb(gray), target) < distance_sq(indexed_to_rgb(cube), target) {
+            Indexed(gray)
+        } else {
+            Indexed(cube)
+        }
+    }
+
+    /// Get the nearest of the 16 ANSI colors, along with whether it is the bright variant
+    pub fn quantize_to_16(self) -> (Self, bool) {
+        let index = match self {
+            Indexed(i @ 0..=15) => i,
+            color if color.is_color() || color.is_default() => return (color, false),
+            color => {
+                let rgb = color.to_rgb();
+                (0..16)
+                    .min_by_key(|&i| distance_sq(ANSI_PALETTE[i as usize], rgb))
+                    .unwrap_or(0)
+            }
+        };
+
+        (Self::from_ansi_index(index % 8), index >= 8)
+    }
+
+    /// Get the basic color at the given index (`0..8`) of the ANSI palette
+    pub(crate) fn from_ansi_index(i: u8) -> Self {
+        match i {
+            0 => Black,
+            1 => Red,
+            2 => Green,
+            3 => Yellow,
+            4 => Blue,
+            5 => Magenta,
+            6 => Cyan,
+            _ => White,
+        }
+    }
+
+    /// Get the WCAG relative luminance of this color (`0.0..=1.0`)
+    pub fn luminance(self) -> f32 {
+        let channel = |v: u8| {
+            let v = v as f32 / 255.0;
+            if v <= 0.03928 {
+                v / 12.92
+            } else {
+                ((v + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        let (r, g, b) = self.to_rgb();
+        0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+    }
+
+    /// Get the WCAG contrast ratio between this color and `other` (`1.0..=21.0`)
+    pub fn contrast_ratio(self, other: Color) -> f32 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Get the foreground, pure black or pure white, most readable on top of this color
+    pub fn contrast_fg(self) -> Self {
+        let (black, white) = (RGB(0, 0, 0), RGB(255, 255, 255));
+
+        if self.contrast_ratio(black) >= self.contrast_ratio(white) {
+            black
+        } else {
+            white
+        }
+    }
+
+    /// Get the perceived distance to `other`, using the "redmean" weighted RGB distance
+    ///
+    /// `0.0` for identical colors, about `765.0` between black and white.
+    pub fn distance(self, other: Color) -> f32 {
+        let ((r1, g1, b1), (r2, g2, b2)) = (self.to_rgb(), other.to_rgb());
+        let rmean = (r1 as f32 + r2 as f32) / 2.0;
+        let (dr, dg, db) = (
+            r1 as f32 - r2 as f32,
+            g1 as f32 - g2 as f32,
+            b1 as f32 - b2 as f32,
+        );
+
+        ((2.0 + rmean / 256.0) * dr * dr
+            + 4.0 * dg * dg
+            + (2.0 + (255.0 - rmean) / 256.0) * db * db)
+            .sqrt()
+    }
+
+    /// Describe this color with the closest CSS/X11 color name (`"slateblue"`)
+    ///
+    /// Basic colors and [`Color::TerminalDefault`] keep their own names.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// assert_eq!(Color::RGB(250, 130, 10).approximate_name(), "darkorange");
+    /// assert_eq!(Color::Indexed(196).approximate_name(), "red");
+    /// assert_eq!(Color::Cyan.approximate_name(), "cyan");
+    /// ```
+    pub fn approximate_name(self) -> &'static str {
+        match self {
+            Black => "black",
+            Red => "red",
+            Green => "green",
+            Yellow => "yellow",
+            Blue => "blue",
+            Magenta => "magenta",
+            Cyan => "cyan",
+            White => "white",
+            TerminalDefault => "default",
+            Indexed(_) | RGB(..) | RGBA(..) => names::CSS_COLORS
+                .iter()
+                .min_by(|(_, a), (_, b)| {
+                    let (a, b) = (RGB(a.0, a.1, a.2), RGB(b.0, b.1, b.2));
+                    self.distance(a).total_cmp(&self.distance(b))
+                })
+                .map_or("black", |&(name, _)| name),
+        }
+    }
+
+    /// Get the index of a basic color in the 16-color palette
+    pub(crate) fn ansi_index(self) -> u8 {
+        self.to_num().saturating_sub(30)
+    }
+
+    /// Shared tail of the HSL/HSV conversions: place the chroma on the hue wheel
+    fn from_hue_chroma(h: f32, c: f32, m: f32) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        RGB(to_u8(r + m), to_u8(g + m), to_u8(b + m))
+    }
+
+    /// Get the hue (degrees) along with the max and min channels (`0.0..=1.0`)
+    fn hue_max_min(self) -> (f32, f32, f32) {
+        let (r, g, b) = self.to_rgb();
+        let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+
+        let h = if d == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        };
+
+        (h, max, min)
+    }
+}
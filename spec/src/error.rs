@@ -0,0 +1,296 @@
+//! This module contains all error types used throught the crate
+
+use std::{fmt, ops::Range};
+
+use thiserror::Error;
+
+/// Error types for the crate
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum StylerError {
+    /// Symbolises missing --text field
+    #[error("Missing required --text argument")]
+    MissingText,
+
+    /// Invalid color specifier
+    #[error("Invalid color specification: {0}")]
+    InvalidColor(String),
+
+    /// Invalid text modfier
+    #[error("Invalid modifier: {0}")]
+    InvalidModifier(char),
+
+    /// Invalid spec argument
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    /// Missing value for a parameter
+    #[error("Expected value after {0}")]
+    MissingValue(String),
+
+    /// Invalid RGB format
+    #[error("Invalid RGB format: {0}")]
+    InvalidRgbFormat(String),
+
+    /// Invalid HEX format
+    #[error("Invalid hex color: {0}")]
+    InvalidHexColor(String),
+
+    /// Signifies errors ecountered by the [`crate::parser`] module
+    #[error("Encountered an error while parsing: {0}")]
+    ParsingError(ParsingError),
+
+    /// Signifies a malformed theme file (TOML/JSON syntax or shape)
+    #[error("Invalid theme file: {0}")]
+    ThemeFormat(String),
+
+    /// Signifies an invalid style spec for the named entry of a [`crate::theme::Theme`]
+    #[error("Invalid theme entry '{0}': {1}")]
+    ThemeEntry(String, Box<StylerError>),
+
+    /// Signifies a style rejected in strict mode, see [`crate::style::Style::validate`]
+    #[error("Conflicting style attributes: {0}")]
+    ConflictingStyle(crate::style::StyleWarning),
+
+    /// Signifies a failure reading input or writing output
+    #[error("I/O error: {0}")]
+    Io(std::io::Error),
+
+    /// Signifies invalid command line arguments of the `style` binary
+    #[cfg(feature = "cli")]
+    #[error("{0}")]
+    Usage(clap::Error),
+
+    /// Signifies errors encountered by the [`crate::style::BatchStyler`] type
+    #[error("Encountered an error during batchoperation at index ({0}): {1}")]
+    BatchError(usize, Box<StylerError>),
+}
+
+/// Error type used in [`crate::parser`]
+///
+/// The last field of every variant is the byte range of the offending part of the
+/// parsed spec or markup, see [`ParsingError::span`] and [`ParsingError::render_diagnostic`].
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum ParsingError {
+    /// Signifies an invalid EOF during tag parsing
+    #[error("End of File after: {0}")]
+    Eof(String, Range<usize>),
+
+    /// Signifies an invalid charcater inside the tag/style
+    #[error("Invalid character in tag name: {0}")]
+    InvalidTagChar(char, Range<usize>),
+
+    /// Occurs when too many arguments have been passed
+    #[error("Too many arguments (<={2}): {0}:{1}")]
+    TooManyArgs(String, usize, usize, Range<usize>),
+
+    /// Occurs when a paramater is missing its value, with its 1-based argument position
+    ///
+    /// `key=value` arguments count as two positions.
+    #[error("Missing value for parameter {0} (argument {1})")]
+    MissingParamVal(String, usize, Range<usize>),
+
+    /// A parameter setting something which was already set earlier in the spec
+    #[error("Duplicate parameter: {0}")]
+    DuplicateParam(String, Range<usize>),
+
+    /// Invalid parameter name
+    #[error("Invalid paramater name: {0}")]
+    InvalidParamName(String, Range<usize>),
+
+    /// Invalid color spec
+    #[error("Invalid color alias: {0}")]
+    InvalidClrSpec(String, Range<usize>),
+
+    /// Invalid hex color spec
+    #[error("Invalid hex color length: {0}:{1}")]
+    InvalidHexClr(String, usize, Range<usize>),
+
+    /// Invalid compoenent in hex
+    #[error("Invalid Hex ({0}) component: {1}")]
+    InvalidHexComp(char, String, Range<usize>),
+
+    /// Invalid color component
+    #[error("Unknown color format: {0}")]
+    UnknownClrFmt(String, Range<usize>),
+
+    /// Component of an `r,g,b` color which isn't a number in `0..=255`
+    #[error("Invalid RGB component: {0}")]
+    InvalidRgbComponent(String, Range<usize>),
+
+    /// `rgb(...)` or `hsl(...)` without 3 or 4 arguments
+    #[error("Expected 3 or 4 arguments to {0}(), got {1}")]
+    InvalidClrFnArgs(String, usize, Range<usize>),
+
+    /// Argument of `rgb(...)` or `hsl(...)` which isn't a number in range
+    #[error("Invalid color function argument: {0}")]
+    InvalidClrFnComp(String, Range<usize>),
+
+    /// Extra/unnecessary closing tag "</>"
+    #[error("Unexpected closing tag")]
+    UnexpectedClosingTag(Range<usize>),
+
+    /// Named closing tag (`</error>`) which doesn't repeat the innermost open tag
+    #[error("Closing tag </{0}> doesn't match <{1}>")]
+    MismatchedClosingTag(String, String, Range<usize>),
+
+    /// Tag naming neither a theme entry nor a spec, with similar theme entries
+    #[error("Unknown tag: {0}{suggestions}", suggestions = did_you_mean(.1))]
+    UnknownTag(String, Vec<String>, Range<usize>),
+
+    /// `<include name>` tag naming no fragment of the `Fragments` parsed with
+    #[error("Unknown fragment: {0}")]
+    UnknownFragment(String, Range<usize>),
+
+    /// Condition of an `<if ...>` tag which isn't one of [`crate::caps::Condition`]
+    #[error("Unknown condition: {0}, expected truecolor, ansi256, color or no-color")]
+    UnknownCondition(String, Range<usize>),
+
+    /// Missing closing tag "</>", the span is the innermost unclosed tag
+    #[error("Unclosed Tags")]
+    UnclosedTags(Range<usize>),
+
+    /// Invalid text modfier
+    #[error("Invalid modifier: {0}")]
+    InvalidModifier(char, Range<usize>),
+
+    /// Unknown underline style name in `m u:<name>`
+    #[error("Invalid underline style: {0}")]
+    InvalidUnderline(String, Range<usize>),
+
+    /// Raw SGR parameters which aren't up to 8 numbers in `0..=255`
+    #[error("Invalid raw SGR parameters: {0}")]
+    InvalidRawSgr(String, Range<usize>),
+
+    /// Markup exceeding one of the limits of its [`crate::style::ParseOptions`]
+    #[error("Markup exceeds the {0} limit of {1}")]
+    LimitExceeded(Limit, usize, Range<usize>),
+}
+
+/// Format the suggestions of [`ParsingError::UnknownTag`]
+fn did_you_mean(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => format!(", did you mean {name}?"),
+        [rest @ .., last] => format!(", did you mean {} or {last}?", rest.join(", ")),
+    }
+}
+
+/// The limits of [`ParsingError::LimitExceeded`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// Length in bytes, see [`crate::style::ParseOptions::max_len`]
+    Length,
+    /// Number of opening tags, see [`crate::style::ParseOptions::max_tags`]
+    Tags,
+    /// Nesting depth of tags, see [`crate::style::ParseOptions::max_depth`]
+    Depth,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Limit::Length => "length",
+            Limit::Tags => "tag count",
+            Limit::Depth => "nesting depth",
+        })
+    }
+}
+
+/// Borrow the span field of any [`ParsingError`] variant
+macro_rules! span_of {
+    ($err:expr) => {
+        match $err {
+            ParsingError::Eof(_, span)
+            | ParsingError::InvalidTagChar(_, span)
+            | ParsingError::TooManyArgs(_, _, _, span)
+            | ParsingError::DuplicateParam(_, span)
+            | ParsingError::MissingParamVal(_, _, span)
+            | ParsingError::InvalidParamName(_, span)
+            | ParsingError::InvalidClrSpec(_, span)
+            | ParsingError::InvalidHexClr(_, _, span)
+            | ParsingError::InvalidHexComp(_, _, span)
+            | ParsingError::UnknownClrFmt(_, span)
+            | ParsingError::InvalidRgbComponent(_, span)
+            | ParsingError::InvalidClrFnArgs(_, _, span)
+            | ParsingError::InvalidClrFnComp(_, span)
+            | ParsingError::UnexpectedClosingTag(span)
+            | ParsingError::MismatchedClosingTag(_, _, span)
+            | ParsingError::UnknownTag(_, _, span)
+            | ParsingError::UnknownFragment(_, span)
+            | ParsingError::UnknownCondition(_, span)
+            | ParsingError::UnclosedTags(span)
+            | ParsingError::InvalidModifier(_, span)
+            | ParsingError::InvalidUnderline(_, span)
+            | ParsingError::InvalidRawSgr(_, span)
+            | ParsingError::LimitExceeded(_, _, span) => span,
+        }
+    };
+}
+
+impl ParsingError {
+    /// Get the byte range of the offending part of the parsed input
+    pub fn span(&self) -> Range<usize> {
+        span_of!(self).clone()
+    }
+
+    /// Render the error with the offending line of `source` and a caret underneath
+    ///
+    /// `source` has to be the spec or markup which failed to parse.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::style::Style;
+    /// use cli_styler::error::StylerError;
+    ///
+    /// let Err(StylerError::ParsingError(err)) = Style::new_from_cli_spec("f r m bz") else {
+    ///     unreachable!()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     err.render_diagnostic("f r m bz"),
+    ///     "error: Invalid modifier: z\n  |\n1 | f r m bz\n  |        ^"
+    /// );
+    /// ```
+    pub fn render_diagnostic(&self, source: &str) -> String {
+        let span = self.span();
+        let start = floor_char_boundary(source, span.start);
+        let end = floor_char_boundary(source, span.end).max(start);
+
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let line_no = source[..start].matches('\n').count() + 1;
+
+        let column = source[line_start..start].chars().count();
+        let carets = source[start..end.min(line_end)].chars().count().max(1);
+
+        let gutter = line_no.to_string().len();
+        format!(
+            "error: {self}\n{:gutter$} |\n{line_no} | {}\n{:gutter$} | {}{}",
+            "",
+            &source[line_start..line_end],
+            "",
+            " ".repeat(column),
+            "^".repeat(carets),
+        )
+    }
+
+    /// Shift the span by `by` bytes, for errors from a part of a larger input
+    pub(crate) fn offset(mut self, by: usize) -> Self {
+        let span = span_of!(&mut self);
+        *span = span.start + by..span.end + by;
+        self
+    }
+}
+
+/// Round `index` down to the closest char boundary of `s`, clamped to its length
+pub(crate) fn floor_char_boundary(s: &str, index: usize) -> usize {
+    let mut index = index.min(s.len());
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
@@ -0,0 +1,18 @@
+//! Conversions between [`Style`](crate::style::Style) and the style types of other crates
+//!
+//! Each integration lives behind the feature named after the crate.
+
+#[cfg(feature = "anstyle")]
+mod anstyle;
+
+#[cfg(feature = "crossterm")]
+mod crossterm;
+
+#[cfg(feature = "ratatui")]
+mod ratatui;
+
+#[cfg(feature = "syntect")]
+mod syntect;
+
+#[cfg(feature = "termcolor")]
+mod termcolor;
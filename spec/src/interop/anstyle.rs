@@ -0,0 +1,128 @@
+//! [`anstyle`] conversions, e.g. for styling `clap` help output
+//!
+//! `anstyle` has no overline, rapid blink, frames, super/subscript or "default color"
+//! concept: those modifiers and [`Color::TerminalDefault`] are dropped, as are the off modifiers
+//! which only matter for transitions.
+
+use ::anstyle::{Ansi256Color, AnsiColor, Effects, RgbColor};
+
+use crate::style::{ClrType, Color, Modifier, Style, UnderlineStyle};
+
+/// Modifiers with an `anstyle` equivalent
+const EFFECTS: [(Modifier, Effects); 9] = [
+    (Modifier::Bold, Effects::BOLD),
+    (Modifier::Dim, Effects::DIMMED),
+    (Modifier::Italic, Effects::ITALIC),
+    (Modifier::Underline, Effects::UNDERLINE),
+    (Modifier::Blink, Effects::BLINK),
+    (Modifier::Invert, Effects::INVERT),
+    (Modifier::Hide, Effects::HIDDEN),
+    (Modifier::Strike, Effects::STRIKETHROUGH),
+    (Modifier::DoubleUL, Effects::DOUBLE_UNDERLINE),
+];
+
+/// Underline shapes with an `anstyle` equivalent, single and double are covered by [`EFFECTS`]
+const UNDERLINES: [(UnderlineStyle, Effects); 3] = [
+    (UnderlineStyle::Curly, Effects::CURLY_UNDERLINE),
+    (UnderlineStyle::Dotted, Effects::DOTTED_UNDERLINE),
+    (UnderlineStyle::Dashed, Effects::DASHED_UNDERLINE),
+];
+
+/// Convert one of a [`Style`]'s colors
+fn to_anstyle((clr, ct): (Color, ClrType)) -> Option<::anstyle::Color> {
+    Some(match clr {
+        Color::TerminalDefault => return None,
+        Color::Indexed(i) => Ansi256Color(i).into(),
+        Color::RGB(r, g, b) | Color::RGBA(r, g, b, _) => RgbColor(r, g, b).into(),
+        basic => {
+            let ansi = match basic {
+                Color::Black => AnsiColor::Black,
+                Color::Red => AnsiColor::Red,
+                Color::Green => AnsiColor::Green,
+                Color::Yellow => AnsiColor::Yellow,
+                Color::Blue => AnsiColor::Blue,
+                Color::Magenta => AnsiColor::Magenta,
+                Color::Cyan => AnsiColor::Cyan,
+                _ => AnsiColor::White,
+            };
+
+            ansi.bright(ct.is_bright()).into()
+        }
+    })
+}
+
+/// Convert an `anstyle` color into a color and whether it is bright
+fn from_anstyle(clr: ::anstyle::Color) -> (Color, bool) {
+    match clr {
+        ::anstyle::Color::Ansi(ansi) => {
+            let clr = match ansi.bright(false) {
+                AnsiColor::Black => Color::Black,
+                AnsiColor::Red => Color::Red,
+                AnsiColor::Green => Color::Green,
+                AnsiColor::Yellow => Color::Yellow,
+                AnsiColor::Blue => Color::Blue,
+                AnsiColor::Magenta => Color::Magenta,
+                AnsiColor::Cyan => Color::Cyan,
+                _ => Color::White,
+            };
+
+            (clr, ansi.is_bright())
+        }
+        ::anstyle::Color::Ansi256(Ansi256Color(i)) => (Color::Indexed(i), false),
+        ::anstyle::Color::Rgb(RgbColor(r, g, b)) => (Color::RGB(r, g, b), false),
+    }
+}
+
+impl From<Style> for ::anstyle::Style {
+    fn from(style: Style) -> Self {
+        let effects = EFFECTS
+            .iter()
+            .filter(|(mdf, _)| style.has_modifier(*mdf))
+            .fold(Effects::new(), |acc, &(_, effect)| acc | effect);
+        let underline = match style.ul {
+            Some(UnderlineStyle::Single) => Effects::UNDERLINE,
+            Some(UnderlineStyle::Double) => Effects::DOUBLE_UNDERLINE,
+            Some(ul) => UNDERLINES
+                .iter()
+                .find(|&&(shape, _)| shape == ul)
+                .map_or(Effects::UNDERLINE, |&(_, effect)| effect),
+            None => Effects::new(),
+        };
+
+        ::anstyle::Style::new()
+            .fg_color(style.fg.and_then(to_anstyle))
+            .bg_color(style.bg.and_then(to_anstyle))
+            .effects(effects | underline)
+    }
+}
+
+impl From<::anstyle::Style> for Style {
+    fn from(style: ::anstyle::Style) -> Self {
+        let mut out = Style::new();
+
+        if let Some((clr, bright)) = style.get_fg_color().map(from_anstyle) {
+            out.fg = Some((clr, ClrType::Fg.with_bright(bright)));
+        }
+
+        if let Some((clr, bright)) = style.get_bg_color().map(from_anstyle) {
+            out.bg = Some((clr, ClrType::Bg.with_bright(bright)));
+        }
+
+        let effects = style.get_effects();
+        out.mdfs.extend(
+            EFFECTS
+                .iter()
+                .filter(|(_, effect)| effects.contains(*effect))
+                .map(|&(mdf, _)| mdf),
+        );
+
+        if let Some(&(ul, _)) = UNDERLINES
+            .iter()
+            .find(|(_, effect)| effects.contains(*effect))
+        {
+            out = out.underline_style(ul);
+        }
+
+        out
+    }
+}
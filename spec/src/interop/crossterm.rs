@@ -0,0 +1,146 @@
+//! [`crossterm`] conversions, so TUI apps can theme [`ContentStyle`]s with specs and markup
+//!
+//! Crossterm names the normal ANSI colors `Dark*` and the bright ones plainly,
+//! e.g. [`Color::Red`] is `DarkRed` while a brightened red is `Red`.
+
+use ::crossterm::style::{self as ct, Attribute, Attributes, ContentStyle};
+
+use crate::style::{ClrType, Color, Modifier, Style, UnderlineStyle};
+
+/// Every modifier with its crossterm attribute, crossterm has no superscript or subscript
+const ATTRIBUTES: [(Modifier, Attribute); 23] = [
+    (Modifier::Reset, Attribute::Reset),
+    (Modifier::Bold, Attribute::Bold),
+    (Modifier::Dim, Attribute::Dim),
+    (Modifier::Italic, Attribute::Italic),
+    (Modifier::Underline, Attribute::Underlined),
+    (Modifier::Blink, Attribute::SlowBlink),
+    (Modifier::Invert, Attribute::Reverse),
+    (Modifier::Hide, Attribute::Hidden),
+    (Modifier::Strike, Attribute::CrossedOut),
+    (Modifier::DoubleUL, Attribute::DoubleUnderlined),
+    (Modifier::Overline, Attribute::OverLined),
+    (Modifier::NoBold, Attribute::NormalIntensity),
+    (Modifier::NoItalic, Attribute::NoItalic),
+    (Modifier::NoUnderline, Attribute::NoUnderline),
+    (Modifier::NoBlink, Attribute::NoBlink),
+    (Modifier::NoInvert, Attribute::NoReverse),
+    (Modifier::NoHide, Attribute::NoHidden),
+    (Modifier::NoStrike, Attribute::NotCrossedOut),
+    (Modifier::NoOverline, Attribute::NotOverLined),
+    (Modifier::RapidBlink, Attribute::RapidBlink),
+    (Modifier::Framed, Attribute::Framed),
+    (Modifier::Encircled, Attribute::Encircled),
+    (Modifier::NoFramed, Attribute::NotFramedOrEncircled),
+];
+
+/// Every underline shape with its crossterm attribute
+const UNDERLINES: [(UnderlineStyle, Attribute); 5] = [
+    (UnderlineStyle::Single, Attribute::Underlined),
+    (UnderlineStyle::Double, Attribute::DoubleUnderlined),
+    (UnderlineStyle::Curly, Attribute::Undercurled),
+    (UnderlineStyle::Dotted, Attribute::Underdotted),
+    (UnderlineStyle::Dashed, Attribute::Underdashed),
+];
+
+/// Convert one of a [`Style`]'s colors
+fn to_crossterm((clr, ty): (Color, ClrType)) -> ct::Color {
+    match (clr, ty.is_bright()) {
+        (Color::TerminalDefault, _) => ct::Color::Reset,
+        (Color::Indexed(i), _) => ct::Color::AnsiValue(i),
+        (Color::RGB(r, g, b) | Color::RGBA(r, g, b, _), _) => ct::Color::Rgb { r, g, b },
+        (Color::Black, false) => ct::Color::Black,
+        (Color::Black, true) => ct::Color::DarkGrey,
+        (Color::Red, false) => ct::Color::DarkRed,
+        (Color::Red, true) => ct::Color::Red,
+        (Color::Green, false) => ct::Color::DarkGreen,
+        (Color::Green, true) => ct::Color::Green,
+        (Color::Yellow, false) => ct::Color::DarkYellow,
+        (Color::Yellow, true) => ct::Color::Yellow,
+        (Color::Blue, false) => ct::Color::DarkBlue,
+        (Color::Blue, true) => ct::Color::Blue,
+        (Color::Magenta, false) => ct::Color::DarkMagenta,
+        (Color::Magenta, true) => ct::Color::Magenta,
+        (Color::Cyan, false) => ct::Color::DarkCyan,
+        (Color::Cyan, true) => ct::Color::Cyan,
+        (Color::White, false) => ct::Color::Grey,
+        (Color::White, true) => ct::Color::White,
+    }
+}
+
+/// Convert a crossterm color into a color and whether it is bright
+fn from_crossterm(clr: ct::Color) -> (Color, bool) {
+    match clr {
+        ct::Color::Reset => (Color::TerminalDefault, false),
+        ct::Color::AnsiValue(i) => (Color::Indexed(i), false),
+        ct::Color::Rgb { r, g, b } => (Color::RGB(r, g, b), false),
+        ct::Color::Black => (Color::Black, false),
+        ct::Color::DarkGrey => (Color::Black, true),
+        ct::Color::DarkRed => (Color::Red, false),
+        ct::Color::Red => (Color::Red, true),
+        ct::Color::DarkGreen => (Color::Green, false),
+        ct::Color::Green => (Color::Green, true),
+        ct::Color::DarkYellow => (Color::Yellow, false),
+        ct::Color::Yellow => (Color::Yellow, true),
+        ct::Color::DarkBlue => (Color::Blue, false),
+        ct::Color::Blue => (Color::Blue, true),
+        ct::Color::DarkMagenta => (Color::Magenta, false),
+        ct::Color::Magenta => (Color::Magenta, true),
+        ct::Color::DarkCyan => (Color::Cyan, false),
+        ct::Color::Cyan => (Color::Cyan, true),
+        ct::Color::Grey => (Color::White, false),
+        ct::Color::White => (Color::White, true),
+    }
+}
+
+impl From<Style> for ContentStyle {
+    fn from(style: Style) -> Self {
+        let mut attributes = ATTRIBUTES
+            .iter()
+            .filter(|(mdf, _)| style.has_modifier(*mdf))
+            .fold(Attributes::none(), |acc, &(_, attr)| acc.with(attr));
+
+        if let Some(&(_, attr)) = UNDERLINES.iter().find(|(ul, _)| style.ul == Some(*ul)) {
+            attributes.set(attr);
+        }
+
+        ContentStyle {
+            foreground_color: style.fg.map(to_crossterm),
+            background_color: style.bg.map(to_crossterm),
+            underline_color: None,
+            attributes,
+        }
+    }
+}
+
+/// Attributes without a [`Modifier`] equivalent (e.g. `Fraktur`) are dropped
+impl From<ContentStyle> for Style {
+    fn from(style: ContentStyle) -> Self {
+        let mut out = Style::new();
+
+        if let Some((clr, bright)) = style.foreground_color.map(from_crossterm) {
+            out.fg = Some((clr, ClrType::Fg.with_bright(bright)));
+        }
+
+        if let Some((clr, bright)) = style.background_color.map(from_crossterm) {
+            out.bg = Some((clr, ClrType::Bg.with_bright(bright)));
+        }
+
+        out.mdfs.extend(
+            ATTRIBUTES
+                .iter()
+                .filter(|(_, attr)| style.attributes.has(*attr))
+                .map(|&(mdf, _)| mdf),
+        );
+
+        // Single and double underlines are already covered by the modifiers
+        if let Some(&(ul, _)) = UNDERLINES[2..]
+            .iter()
+            .find(|(_, attr)| style.attributes.has(*attr))
+        {
+            out = out.underline_style(ul);
+        }
+
+        out
+    }
+}
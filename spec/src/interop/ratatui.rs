@@ -0,0 +1,103 @@
+//! [`ratatui`] conversions, so TUI apps can theme widgets with specs
+//!
+//! Ratatui has no overline, frames or super/subscript, those modifiers are dropped and
+//! [`Modifier::DoubleUL`] and every [`UnderlineStyle`](crate::style::UnderlineStyle)
+//! become a plain underline.
+
+use ::ratatui::style as rt;
+
+use crate::style::{ClrType, Color, Modifier, Style};
+
+/// Convert a color, `bright` picks the `Light*` variant of basic colors
+fn to_ratatui(clr: Color, bright: bool) -> rt::Color {
+    match (clr, bright) {
+        (Color::TerminalDefault, _) => rt::Color::Reset,
+        (Color::Indexed(i), _) => rt::Color::Indexed(i),
+        (Color::RGB(r, g, b) | Color::RGBA(r, g, b, _), _) => rt::Color::Rgb(r, g, b),
+        (Color::Black, false) => rt::Color::Black,
+        (Color::Black, true) => rt::Color::DarkGray,
+        (Color::Red, false) => rt::Color::Red,
+        (Color::Red, true) => rt::Color::LightRed,
+        (Color::Green, false) => rt::Color::Green,
+        (Color::Green, true) => rt::Color::LightGreen,
+        (Color::Yellow, false) => rt::Color::Yellow,
+        (Color::Yellow, true) => rt::Color::LightYellow,
+        (Color::Blue, false) => rt::Color::Blue,
+        (Color::Blue, true) => rt::Color::LightBlue,
+        (Color::Magenta, false) => rt::Color::Magenta,
+        (Color::Magenta, true) => rt::Color::LightMagenta,
+        (Color::Cyan, false) => rt::Color::Cyan,
+        (Color::Cyan, true) => rt::Color::LightCyan,
+        (Color::White, false) => rt::Color::Gray,
+        (Color::White, true) => rt::Color::White,
+    }
+}
+
+/// Get the ratatui flags for the attributes a modifier turns on or off
+fn flag(mdf: Modifier) -> rt::Modifier {
+    match mdf {
+        Modifier::Bold => rt::Modifier::BOLD,
+        Modifier::Dim => rt::Modifier::DIM,
+        Modifier::NoBold => rt::Modifier::BOLD | rt::Modifier::DIM,
+        Modifier::Italic | Modifier::NoItalic => rt::Modifier::ITALIC,
+        Modifier::Underline | Modifier::DoubleUL | Modifier::NoUnderline => {
+            rt::Modifier::UNDERLINED
+        }
+        Modifier::Blink => rt::Modifier::SLOW_BLINK,
+        Modifier::RapidBlink => rt::Modifier::RAPID_BLINK,
+        Modifier::NoBlink => rt::Modifier::SLOW_BLINK | rt::Modifier::RAPID_BLINK,
+        Modifier::Invert | Modifier::NoInvert => rt::Modifier::REVERSED,
+        Modifier::Hide | Modifier::NoHide => rt::Modifier::HIDDEN,
+        Modifier::Strike | Modifier::NoStrike => rt::Modifier::CROSSED_OUT,
+        Modifier::Reset
+        | Modifier::Overline
+        | Modifier::NoOverline
+        | Modifier::Framed
+        | Modifier::Encircled
+        | Modifier::NoFramed
+        | Modifier::Superscript
+        | Modifier::Subscript
+        | Modifier::NoScript => rt::Modifier::empty(),
+    }
+}
+
+impl From<Color> for rt::Color {
+    fn from(clr: Color) -> Self {
+        to_ratatui(clr, false)
+    }
+}
+
+/// Off modifiers convert to an empty set, [`Style`] conversion moves them into `sub_modifier`
+impl From<Modifier> for rt::Modifier {
+    fn from(mdf: Modifier) -> Self {
+        if mdf.is_off() {
+            rt::Modifier::empty()
+        } else {
+            flag(mdf)
+        }
+    }
+}
+
+impl From<Style> for rt::Style {
+    fn from(style: Style) -> Self {
+        let color = |(clr, ty): (Color, ClrType)| to_ratatui(clr, ty.is_bright());
+
+        let mut out = rt::Style::new();
+        out.fg = style.fg.map(color);
+        out.bg = style.bg.map(color);
+
+        if style.ul.is_some() {
+            out = out.add_modifier(rt::Modifier::UNDERLINED);
+        }
+
+        for mdf in style.modifiers() {
+            if mdf.is_off() {
+                out = out.remove_modifier(flag(mdf));
+            } else {
+                out = out.add_modifier(flag(mdf));
+            }
+        }
+
+        out
+    }
+}
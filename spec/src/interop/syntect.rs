@@ -0,0 +1,34 @@
+//! [`syntect`] conversions, used by the `highlight` module of `cli-styler`
+//!
+//! Highlighting themes only carry RGB colors and bold, italic and underline.
+
+use ::syntect::highlighting::{self as sy, FontStyle};
+
+use crate::style::{Color, Style};
+
+/// The background is dropped: themes paint every token with the snippet's background,
+/// which belongs to the terminal. Fully transparent foregrounds are dropped as well.
+impl From<sy::Style> for Style {
+    fn from(style: sy::Style) -> Self {
+        let sy::Color { r, g, b, a } = style.foreground;
+        let mut out = Style::new();
+
+        if a > 0 {
+            out = out.fg(Color::RGB(r, g, b));
+        }
+
+        if style.font_style.contains(FontStyle::BOLD) {
+            out = out.bold();
+        }
+
+        if style.font_style.contains(FontStyle::ITALIC) {
+            out = out.italic();
+        }
+
+        if style.font_style.contains(FontStyle::UNDERLINE) {
+            out = out.underline();
+        }
+
+        out
+    }
+}
@@ -0,0 +1,47 @@
+//! [`termcolor`] conversions, for legacy Windows consoles without VT processing
+//!
+//! Styles are converted to a [`ColorSpec`] for the console API, which doesn't
+//! understand escape sequences.
+
+use ::termcolor::ColorSpec;
+
+use crate::style::{Color, Modifier, Style};
+
+/// Convert a color, [`Color::TerminalDefault`] has no equivalent and maps to `None`
+fn to_termcolor(clr: Color) -> Option<::termcolor::Color> {
+    Some(match clr {
+        Color::TerminalDefault => return None,
+        Color::Indexed(i) => ::termcolor::Color::Ansi256(i),
+        Color::RGB(r, g, b) | Color::RGBA(r, g, b, _) => ::termcolor::Color::Rgb(r, g, b),
+        Color::Black => ::termcolor::Color::Black,
+        Color::Red => ::termcolor::Color::Red,
+        Color::Green => ::termcolor::Color::Green,
+        Color::Yellow => ::termcolor::Color::Yellow,
+        Color::Blue => ::termcolor::Color::Blue,
+        Color::Magenta => ::termcolor::Color::Magenta,
+        Color::Cyan => ::termcolor::Color::Cyan,
+        Color::White => ::termcolor::Color::White,
+    })
+}
+
+/// `termcolor` has a single "intense" flag, set when either color is brightened, and
+/// draws every [`UnderlineStyle`](crate::style::UnderlineStyle) as a single underline.
+///
+/// Blink, invert, hide, double underline, overline, frames and super/subscript have no
+/// equivalent and are dropped.
+impl From<Style> for ColorSpec {
+    fn from(style: Style) -> Self {
+        let mut spec = ColorSpec::new();
+
+        spec.set_fg(style.fg_color().and_then(to_termcolor))
+            .set_bg(style.bg_color().and_then(to_termcolor))
+            .set_intense(style.is_bright_fg() || style.is_bright_bg())
+            .set_bold(style.has_modifier(Modifier::Bold))
+            .set_dimmed(style.has_modifier(Modifier::Dim))
+            .set_italic(style.has_modifier(Modifier::Italic))
+            .set_underline(style.has_modifier(Modifier::Underline) || style.ul.is_some())
+            .set_strikethrough(style.has_modifier(Modifier::Strike));
+
+        spec
+    }
+}
@@ -0,0 +1,146 @@
+//! Specs, styles and the markup tokenizer of `cli-styler`
+//!
+//! Shared by `cli-styler`, which re-exports the public modules, and `cli-styler-macros`,
+//! so both accept exactly the same specs. Use them through `cli-styler`.
+
+#![warn(missing_docs, clippy::missing_docs_in_private_items)]
+
+mod color;
+mod names;
+
+#[cfg(feature = "markup")]
+mod shortcodes;
+
+#[cfg(any(
+    feature = "anstyle",
+    feature = "crossterm",
+    feature = "ratatui",
+    feature = "syntect",
+    feature = "termcolor"
+))]
+mod interop;
+
+pub mod caps;
+pub mod error;
+pub mod palette;
+pub mod parser;
+pub mod style;
+pub mod theme;
+
+#[doc(hidden)]
+#[path = "private.rs"]
+pub mod __private;
+
+/// Unit Tests
+#[cfg(test)]
+mod tests {
+    mod caps_test {
+        use crate::style::ColorLevel;
+
+        #[test]
+        fn caps_detection() {
+            use crate::caps::level_from_env;
+
+            fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+                move |key| {
+                    vars.iter()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, v)| v.to_string())
+                }
+            }
+
+            let truecolor = [("TERM", "xterm"), ("COLORTERM", "truecolor")];
+            assert_eq!(level_from_env(env(&truecolor), true), ColorLevel::TrueColor);
+            assert_eq!(level_from_env(env(&truecolor), false), ColorLevel::None);
+
+            let indexed = [("TERM", "xterm-256color")];
+            assert_eq!(level_from_env(env(&indexed), true), ColorLevel::Ansi256);
+
+            let basic = [("TERM", "screen")];
+            assert_eq!(level_from_env(env(&basic), true), ColorLevel::Ansi16);
+
+            let dumb = [("TERM", "dumb"), ("COLORTERM", "truecolor")];
+            assert_eq!(level_from_env(env(&dumb), true), ColorLevel::None);
+        }
+
+        #[test]
+        fn env_flags() {
+            use crate::caps::flags_from_env;
+
+            let flags = |vars: &[(&str, &str)]| {
+                let vars = vars.to_vec();
+                flags_from_env(move |key| {
+                    vars.iter()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, v)| v.to_string())
+                })
+            };
+
+            assert!(flags(&[("NO_COLOR", "1")]).no_color);
+            assert!(!flags(&[("NO_COLOR", "")]).no_color);
+            assert!(flags(&[("CLICOLOR", "0")]).no_color);
+            assert!(flags(&[("CLICOLOR_FORCE", "1")]).force);
+            assert!(flags(&[("FORCE_COLOR", "3")]).force);
+            assert!(!flags(&[("FORCE_COLOR", "0")]).force);
+        }
+
+        #[test]
+        fn no_color() {
+            use crate::caps::{ColorChoice, colorize, flags_from_env};
+
+            let no_color = flags_from_env(|key| (key == "NO_COLOR").then(|| "1".to_string()));
+            let unset = flags_from_env(|_| None);
+
+            assert!(!colorize(ColorChoice::Auto, no_color));
+            assert!(colorize(ColorChoice::Auto, unset));
+            assert!(colorize(ColorChoice::Always, no_color));
+            assert!(!colorize(ColorChoice::Never, unset));
+        }
+
+        #[test]
+        #[cfg(not(windows))]
+        fn unicode_detection() {
+            use crate::caps::unicode_from_env;
+
+            let unicode = |vars: &[(&str, &str)]| {
+                let vars = vars.to_vec();
+                unicode_from_env(move |key| {
+                    vars.iter()
+                        .find(|(k, _)| *k == key)
+                        .map(|(_, v)| v.to_string())
+                })
+            };
+
+            assert!(unicode(&[("LANG", "en_US.UTF-8")]));
+            assert!(unicode(&[("LC_ALL", ""), ("LANG", "de_DE.utf8")]));
+            assert!(!unicode(&[("LC_ALL", "C"), ("LANG", "en_US.UTF-8")]));
+            assert!(!unicode(&[("TERM", "linux"), ("LANG", "en_US.UTF-8")]));
+            assert!(!unicode(&[]));
+        }
+    }
+
+    mod color_test {
+        #[test]
+        fn stable_hash() {
+            // Pinned values, these must not change between releases
+            assert_eq!(crate::color::stable_hash(b"alice"), 0xc5d1_556d_6677_4a5c);
+        }
+    }
+
+    #[cfg(feature = "markup")]
+    mod shortcodes_test {
+        use crate::shortcodes::{SHORTCODES, replace};
+
+        #[test]
+        fn replace_shortcodes() {
+            assert_eq!(
+                replace(" a::check::y: :nope: 12:30 :", false),
+                " a:✔:y: :nope: 12:30 :"
+            );
+            assert_eq!(replace(":warning: :check:", true), "! v");
+
+            let names = SHORTCODES.iter().map(|&(name, _, _)| name);
+            assert!(names.clone().zip(names.skip(1)).all(|(a, b)| a < b));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Ordered sets of named colors (`accent`, `muted`, ...)
+
+use crate::{
+    color::{distance_sq, stable_hash},
+    style::Color,
+};
+
+#[cfg(feature = "serde")]
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{Error, MapAccess, Visitor},
+};
+
+/// An ordered set of named colors
+///
+/// Colors keep the order they were first inserted in, re-inserting a name
+/// replaces its color in place. With the `serde` feature a palette (de)serializes
+/// as an ordered map of names to color specs (`"r"`, `"orange"`, `"#ff8800"`, ...).
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{palette::Palette, prelude::*};
+///
+/// let palette = Palette::new()
+///     .with("accent", Color::RGB(255, 136, 0))
+///     .with("muted", Color::Indexed(244));
+///
+/// assert_eq!(palette.get("accent"), Some(Color::RGB(255, 136, 0)));
+/// assert_eq!(palette.nearest(Color::RGB(250, 140, 10)), Some(("accent", Color::RGB(255, 136, 0))));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Palette {
+    /// Colors with their names, in insertion order
+    colors: Vec<(String, Color)>,
+}
+
+impl Palette {
+    /// Creates a new, empty [`Palette`]
+    pub const fn new() -> Self {
+        Self { colors: Vec::new() }
+    }
+
+    /// Add a color, returning the previous color registered under `name`
+    pub fn insert(&mut self, name: impl Into<String>, color: Color) -> Option<Color> {
+        let name = name.into();
+
+        match self.colors.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, slot)) => Some(std::mem::replace(slot, color)),
+            None => {
+                self.colors.push((name, color));
+                None
+            }
+        }
+    }
+
+    /// Builder variant of [`Palette::insert`]
+    pub fn with(mut self, name: impl Into<String>, color: Color) -> Self {
+        self.insert(name, color);
+        self
+    }
+
+    /// Remove a color from the palette, keeping the order of the others
+    pub fn remove(&mut self, name: &str) -> Option<Color> {
+        let i = self.colors.iter().position(|(n, _)| n == name)?;
+        Some(self.colors.remove(i).1)
+    }
+
+    /// Get the color registered under `name`
+    pub fn get(&self, name: &str) -> Option<Color> {
+        self.colors
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|&(_, color)| color)
+    }
+
+    /// Get the color at position `index`, in insertion order
+    pub fn get_index(&self, index: usize) -> Option<(&str, Color)> {
+        self.colors
+            .get(index)
+            .map(|(name, color)| (name.as_str(), *color))
+    }
+
+    /// Check whether a color is registered under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Find the color closest to `color` by RGB distance, ties go to the earlier entry
+    pub fn nearest(&self, color: Color) -> Option<(&str, Color)> {
+        let target = color.to_rgb();
+
+        self.iter()
+            .min_by_key(|(_, candidate)| distance_sq(candidate.to_rgb(), target))
+    }
+
+    /// Pick a stable entry for `key`, like [`Color::from_hash`] restricted to this palette
+    ///
+    /// The pick only depends on the key and the palette's length, so it survives restarts,
+    /// but changes when colors are added or removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{palette::Palette, prelude::*};
+    ///
+    /// let palette = Palette::new()
+    ///     .with("red", Color::Red)
+    ///     .with("green", Color::Green)
+    ///     .with("blue", Color::Blue);
+    ///
+    /// assert_eq!(palette.pick_for("worker-1"), palette.pick_for("worker-1"));
+    /// assert_eq!(Palette::new().pick_for("worker-1"), None);
+    /// ```
+    pub fn pick_for(&self, key: impl AsRef<[u8]>) -> Option<(&str, Color)> {
+        if self.colors.is_empty() {
+            return None;
+        }
+
+        let index = stable_hash(key.as_ref()) % self.colors.len() as u64;
+        self.get_index(index as usize)
+    }
+
+    /// Iterate over the colors in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Color)> {
+        self.colors
+            .iter()
+            .map(|(name, color)| (name.as_str(), *color))
+    }
+
+    /// Number of colors
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Check whether the palette has no colors
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Color)> for Palette {
+    fn from_iter<T: IntoIterator<Item = (K, Color)>>(iter: T) -> Self {
+        let mut palette = Self::new();
+        palette.extend(iter);
+        palette
+    }
+}
+
+impl<K: Into<String>> Extend<(K, Color)> for Palette {
+    fn extend<T: IntoIterator<Item = (K, Color)>>(&mut self, iter: T) {
+        for (name, color) in iter {
+            self.insert(name, color);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Palette {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(name, color)| (name, color.to_spec())))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Palette {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Collects the map entries in document order
+        struct PaletteVisitor;
+
+        impl<'de> Visitor<'de> for PaletteVisitor {
+            type Value = Palette;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map of names to color specs")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Palette, A::Error> {
+                let mut palette = Palette::new();
+
+                while let Some((name, spec)) = map.next_entry::<String, String>()? {
+                    match spec.parse::<Color>() {
+                        Ok(color) => palette.insert(name, color),
+                        Err(err) => Err(A::Error::custom(format!("{name}: {err}")))?,
+                    };
+                }
+
+                Ok(palette)
+            }
+        }
+
+        deserializer.deserialize_map(PaletteVisitor)
+    }
+}
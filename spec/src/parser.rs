@@ -0,0 +1,1369 @@
+//! Parsing of specs and markup
+//!
+//! With the `markup` feature, [`tokens`] exposes the markup tokenizer.
+
+use std::ops::Range;
+
+use crate::{
+    error::ParsingError,
+    style::{Color, Modifier, Modifiers, ParseOptions, RawSgr, SpecWarning, Style, UnderlineStyle},
+};
+
+#[cfg(feature = "markup")]
+use std::fmt;
+
+#[cfg(feature = "markup")]
+use crate::{
+    caps::Condition,
+    error::{Limit, floor_char_boundary},
+    theme::Theme,
+};
+
+/// The built-in dialects of the spec grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParsingMode {
+    /// Markup mode, used everywhere but the `style` binary
+    Markup,
+
+    /// Command Line mode, also accepts the deprecated hex suffix form `ff8800#`
+    #[cfg(feature = "cli")]
+    CommandLine,
+}
+
+impl Dialect for ParsingMode {
+    fn hex_digits<'a>(&self, value: &'a str) -> Option<&'a str> {
+        match self {
+            ParsingMode::Markup => prefixed_hex(value),
+
+            #[cfg(feature = "cli")]
+            ParsingMode::CommandLine => prefixed_hex(value).or_else(|| value.strip_suffix('#')),
+        }
+    }
+}
+
+/// A parameter of the spec grammar, see [`Dialect::param`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Param {
+    /// Foreground color (`f`)
+    Fg,
+    /// Bright foreground color (`fb`)
+    BrightFg,
+    /// Background color (`b`)
+    Bg,
+    /// Bright background color (`bb`)
+    BrightBg,
+    /// Modifiers, underline shape or raw SGR parameters (`m`)
+    Modifiers,
+}
+
+impl Param {
+    /// Check whether this parameter sets the background
+    fn is_bg(self) -> bool {
+        matches!(self, Param::Bg | Param::BrightBg)
+    }
+
+    /// Check whether this parameter sets a bright color
+    fn is_bright(self) -> bool {
+        matches!(self, Param::BrightFg | Param::BrightBg)
+    }
+}
+
+/// Hooks customizing the spec grammar while reusing the tokenizer and [`Style`] construction
+///
+/// Every hook defaults to the markup syntax, so a dialect only overrides what differs.
+/// The built-in dialects are the [`ParsingMode`]s.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{
+///     parser::{Dialect, Param},
+///     prelude::*,
+///     style::ParseOptions,
+/// };
+///
+/// /// Long parameter names, `$` hex colors and a `brand` color
+/// struct Verbose;
+///
+/// impl Dialect for Verbose {
+///     fn param(&self, name: &str) -> Option<Param> {
+///         match name {
+///             "fg" => Some(Param::Fg),
+///             "bg" => Some(Param::Bg),
+///             "style" => Some(Param::Modifiers),
+///             _ => None,
+///         }
+///     }
+///
+///     fn hex_digits<'a>(&self, value: &'a str) -> Option<&'a str> {
+///         value.strip_prefix('$')
+///     }
+///
+///     fn color_alias(&self, name: &str) -> Option<Color> {
+///         (name == "brand").then_some(Color::RGB(0x12, 0x34, 0x56))
+///     }
+/// }
+///
+/// let opts = ParseOptions::new();
+///
+/// assert_eq!(
+///     Style::new_from_dialect_spec("fg $f80 bg brand style b", &Verbose, &opts).unwrap(),
+///     Style::new().fg_rgb(255, 136, 0).bg_rgb(0x12, 0x34, 0x56).bold()
+/// );
+/// assert!(Style::new_from_dialect_spec("f r", &Verbose, &opts).is_err());
+/// ```
+pub trait Dialect {
+    /// Get the parameter set by `name` (`f`, `m`, ...), `None` for unknown names
+    fn param(&self, name: &str) -> Option<Param> {
+        match name {
+            "f" => Some(Param::Fg),
+            "fb" => Some(Param::BrightFg),
+            "b" => Some(Param::Bg),
+            "bb" => Some(Param::BrightBg),
+            "m" => Some(Param::Modifiers),
+            _ => None,
+        }
+    }
+
+    /// Get the digits of a hex color, `None` if `value` isn't written as hex
+    ///
+    /// `value` is lowercased already. Defaults to `#ff8800` and `0xff8800`.
+    fn hex_digits<'a>(&self, value: &'a str) -> Option<&'a str> {
+        prefixed_hex(value)
+    }
+
+    /// Resolve a color name as written, before the built-in names
+    ///
+    /// Aliases of the [`ParseOptions`] take precedence.
+    fn color_alias(&self, _name: &str) -> Option<Color> {
+        None
+    }
+}
+
+/// Constant for easier access
+#[allow(non_upper_case_globals)]
+pub(crate) const Mk: ParsingMode = ParsingMode::Markup;
+
+/// Constant for easier access
+#[cfg(feature = "cli")]
+#[allow(non_upper_case_globals)]
+pub(crate) const Cli: ParsingMode = ParsingMode::CommandLine;
+
+/// A piece of markup, see [`tokens`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg(feature = "markup")]
+#[non_exhaustive]
+pub enum Token {
+    /// A closing tag `</>`
+    End,
+    /// An empty opening tag `<>`
+    Empty,
+
+    /// An opening tag with the style it applies (`<f r m b>`)
+    Fmt(Style),
+    /// An opening `<if ...>` tag, its content is only rendered when the condition holds
+    If(Condition),
+    /// An `<include name>` tag, replaced by the fragment of that name, it isn't closed
+    Include(String),
+    /// A `<code lang>` section with its language and the code, which isn't parsed
+    #[cfg(feature = "code")]
+    Code(String, String),
+    /// Text between tags, with escapes (`\<`, `\>`, `\\`) resolved
+    Text(String),
+    /// The content of a `<raw>` section, taken literally
+    Raw(String),
+}
+
+/// Inner state used by the parser state machine
+#[derive(Debug, Default)]
+#[cfg(feature = "markup")]
+pub(crate) enum State {
+    /// Text encompassed
+    #[default]
+    Text,
+
+    /// Encountered '<'
+    Lt,
+    /// Encountered the escape character, see [`ParseOptions::escape_char`]
+    Escape,
+    /// Encountered '</', with the name of the closing tag so far
+    MaybeClose(String),
+
+    /// Text inside '<>'
+    Tag(String),
+
+    /// Inside `<raw>`, with the text so far
+    Raw(String),
+
+    /// Inside `<code lang>`, with the language and the code so far
+    #[cfg(feature = "code")]
+    Code(String, String),
+
+    /// Inside `<!--`, with the number of `-` just seen
+    Comment(usize),
+}
+
+/// Default options for [`tokens`]
+#[cfg(feature = "markup")]
+static DEFAULT_OPTIONS: ParseOptions = ParseOptions::new();
+
+/// Split markup into [`Token`]s along with the byte range each of them covers
+///
+/// This is the tokenizer `Markup` is built from, so tools like editors and linters see
+/// exactly the same grammar. Tags are ranges from `<` to `>`, text covers its escapes.
+/// Everything between `<raw>` and `</raw>` is a [`Token::Raw`], its range covers both
+/// tags. Comments (`<!-- note -->`) are skipped, the range of the text around them
+/// covers them. After the first error nothing more is yielded, unbalanced tags are
+/// reported where they are detected. The limits of the [`ParseOptions`] are enforced
+/// as the input is split.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{parser::{Token, tokens}, prelude::*};
+///
+/// let tokens = tokens("hi <f r>you</>").collect::<Result<Vec<_>, _>>().unwrap();
+///
+/// assert_eq!(
+///     tokens,
+///     [
+///         (Token::Text("hi ".to_string()), 0..3),
+///         (Token::Fmt(Style::new().fg(Color::Red)), 3..8),
+///         (Token::Text("you".to_string()), 8..11),
+///         (Token::End, 11..14),
+///     ]
+/// );
+/// ```
+#[cfg(feature = "markup")]
+pub fn tokens(input: &str) -> Tokens<'_> {
+    Tokens::new(input, &Mk, None, &DEFAULT_OPTIONS)
+}
+
+/// Split markup whose tag specs are parsed in `dialect` with `opts`, see [`tokens`]
+#[cfg(feature = "markup")]
+pub fn tokens_with<'a>(
+    input: &'a str,
+    dialect: &'a dyn Dialect,
+    opts: &'a ParseOptions,
+) -> Tokens<'a> {
+    Tokens::new(input, dialect, None, opts)
+}
+
+/// Iterator over the spanned [`Token`]s of markup, created by [`tokens`]
+#[cfg(feature = "markup")]
+pub struct Tokens<'a> {
+    /// Markup being split
+    source: &'a str,
+    /// Remaining characters
+    chars: std::str::CharIndices<'a>,
+    /// Dialect tag specs are written in
+    dialect: &'a dyn Dialect,
+    /// Theme whose entries tags may name
+    theme: Option<&'a Theme>,
+    /// Options tag specs are parsed with
+    opts: &'a ParseOptions,
+    /// State of the state machine
+    state: State,
+    /// Text collected since `text_start`
+    text: String,
+    /// Start of the text being collected
+    text_start: usize,
+    /// Start of the tag being parsed
+    tag_start: usize,
+    /// Spans of the tags still open
+    open: Vec<Range<usize>>,
+    /// Number of opening tags so far
+    tags: usize,
+    /// Contents and spans of the tags opened in earlier chunks, see [`Tokens::resume`]
+    outer: Vec<(String, Range<usize>)>,
+    /// Whether `source` is a chunk of a longer input, tags may stay open at its end
+    partial: bool,
+    /// Whether errors are recovered from instead of ending the tokens, see [`tokenize_lenient`]
+    lenient: bool,
+    /// Errors recovered from in lenient mode
+    warnings: Vec<ParsingError>,
+    /// Character to process again after recovering from an invalid tag
+    pushback: Option<(usize, char)>,
+    /// End of the last character processed
+    pos: usize,
+    /// Whether the end of input or an error was reached
+    done: bool,
+}
+
+#[cfg(feature = "markup")]
+impl<'a> Tokens<'a> {
+    /// Creates a tokenizer resolving tags through `theme` first, see [`tokenize`]
+    pub(crate) fn new(
+        source: &'a str,
+        dialect: &'a dyn Dialect,
+        theme: Option<&'a Theme>,
+        opts: &'a ParseOptions,
+    ) -> Self {
+        Self {
+            source,
+            chars: source.char_indices(),
+            dialect,
+            theme,
+            opts,
+            state: State::Text,
+            text: String::new(),
+            text_start: 0,
+            tag_start: 0,
+            open: Vec::new(),
+            tags: 0,
+            outer: Vec::new(),
+            partial: false,
+            lenient: false,
+            warnings: Vec::new(),
+            pushback: None,
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Continue tokenizing a chunk of markup, `chunk` has to start in text
+    ///
+    /// `outer` are the tags left open by the earlier chunks and `tags` the number of
+    /// opening tags in them. Tags still open at the end of the chunk aren't an error.
+    pub(crate) fn resume(
+        chunk: &'a str,
+        dialect: &'a dyn Dialect,
+        theme: Option<&'a Theme>,
+        opts: &'a ParseOptions,
+        outer: Vec<(String, Range<usize>)>,
+        tags: usize,
+    ) -> Self {
+        Self {
+            outer,
+            tags,
+            partial: true,
+            ..Self::new(chunk, dialect, theme, opts)
+        }
+    }
+
+    /// Get the tags left open and the number of opening tags, for the next [`Tokens::resume`]
+    ///
+    /// Spans of tags opened in this chunk are shifted by `offset`.
+    pub(crate) fn suspend(self, offset: usize) -> (Vec<(String, Range<usize>)>, usize) {
+        let mut open = self.outer;
+        open.extend(self.open.iter().map(|span| {
+            (
+                self.source[span.start + 1..span.end - 1].to_string(),
+                span.start + offset..span.end + offset,
+            )
+        }));
+
+        (open, self.tags)
+    }
+
+    /// Advance the state machine until a token is complete
+    fn step(&mut self) -> Result<Option<(Token, Range<usize>)>, ParsingError> {
+        loop {
+            let (i, ch) = self
+                .pushback
+                .take()
+                .or_else(|| self.chars.next())
+                .map_or((self.source.len(), None), |(i, c)| (i, Some(c)));
+            let tag_start = self.tag_start;
+            self.pos = ch.map_or(i, |c| i + c.len_utf8());
+
+            self.state = match std::mem::take(&mut self.state) {
+                State::Lt => match ch {
+                    None => Err(ParsingError::Eof(">".to_string(), tag_start..i))?,
+                    Some('/') => State::MaybeClose(String::new()),
+                    Some('>') => {
+                        self.open_tag(tag_start..i + 1)?;
+                        return Ok(Some((Token::Empty, tag_start..i + 1)));
+                    }
+                    Some(c) => State::Tag(c.to_string()),
+                },
+
+                State::Tag(mut tag_content) => match ch {
+                    None => Err(ParsingError::Eof(
+                        format!("Tag name: {tag_content}"),
+                        tag_start..i,
+                    ))?,
+                    Some('>') if tag_content.trim() == RAW => State::Raw(String::new()),
+                    Some('>') => {
+                        #[cfg(feature = "code")]
+                        if let Some(lang) = argument_of(&tag_content, CODE) {
+                            self.state = State::Code(lang.to_string(), String::new());
+                            continue;
+                        }
+
+                        if let Some(name) = argument_of(&tag_content, INCLUDE) {
+                            let token = Token::Include(name.to_string());
+                            return Ok(Some((token, tag_start..i + 1)));
+                        }
+
+                        self.open_tag(tag_start..i + 1)?;
+
+                        if let Some(name) = argument_of(&tag_content, IF) {
+                            let condition = Condition::from_name(name).ok_or_else(|| {
+                                ParsingError::UnknownCondition(name.to_string(), tag_start..i + 1)
+                            })?;
+
+                            return Ok(Some((Token::If(condition), tag_start..i + 1)));
+                        }
+
+                        let name = tag_content.trim();
+                        let style = match self.theme.and_then(|theme| theme.get(name)) {
+                            Some(&style) => style,
+                            // A single word can't be a spec, every parameter takes a value
+                            None if is_tag_name(name) && self.dialect.param(name).is_none() => {
+                                return Err(ParsingError::UnknownTag(
+                                    name.to_string(),
+                                    self.theme
+                                        .map_or_else(Vec::new, |theme| suggest(name, theme)),
+                                    tag_start..i + 1,
+                                ));
+                            }
+                            None => parse_style(&tag_content, self.dialect, self.opts)
+                                .map_err(|e| e.offset(tag_start + 1))?,
+                        };
+
+                        return Ok(Some((Token::Fmt(style), tag_start..i + 1)));
+                    }
+                    Some(c) if is_tag_char(c) => {
+                        tag_content.push(c);
+                        State::Tag(tag_content)
+                    }
+                    Some(c) => Err(ParsingError::InvalidTagChar(c, i..i + c.len_utf8()))?,
+                },
+
+                State::Raw(mut raw) => match ch {
+                    None => Err(ParsingError::Eof(format!("</{RAW}>"), tag_start..i))?,
+                    Some(c) => {
+                        raw.push(c);
+
+                        match raw.strip_suffix(RAW_END) {
+                            Some("") => State::Text,
+                            Some(raw) => {
+                                return Ok(Some((Token::Raw(raw.to_string()), tag_start..i + 1)));
+                            }
+                            None => State::Raw(raw),
+                        }
+                    }
+                },
+
+                #[cfg(feature = "code")]
+                State::Code(lang, mut code) => match ch {
+                    None => Err(ParsingError::Eof(CODE_END.to_string(), tag_start..i))?,
+                    Some(c) => {
+                        code.push(c);
+
+                        match code.strip_suffix(CODE_END) {
+                            Some(code) => {
+                                let token = Token::Code(lang, code.to_string());
+                                return Ok(Some((token, tag_start..i + 1)));
+                            }
+                            None => State::Code(lang, code),
+                        }
+                    }
+                },
+
+                State::Comment(dashes) => match ch {
+                    None => Err(ParsingError::Eof(COMMENT_END.to_string(), tag_start..i))?,
+                    Some('>') if dashes >= 2 => State::Text,
+                    Some('-') => State::Comment(dashes + 1),
+                    Some(_) => State::Comment(0),
+                },
+
+                State::Escape => {
+                    let escape = self.opts.escape;
+
+                    match ch {
+                        None => self.text.push(escape),
+                        Some(c) if c == '<' || c == '>' || c == escape => self.text.push(c),
+                        Some(c) => {
+                            self.text.push(escape);
+                            self.text.push(c);
+                        }
+                    }
+
+                    State::default()
+                }
+
+                State::MaybeClose(mut name) => match ch {
+                    None => Err(ParsingError::Eof(format!("</{name}"), tag_start..i))?,
+                    Some('>') => {
+                        self.close_tag(&name, tag_start..i + 1)?;
+                        return Ok(Some((Token::End, tag_start..i + 1)));
+                    }
+                    Some(c) if is_tag_char(c) => {
+                        name.push(c);
+                        State::MaybeClose(name)
+                    }
+                    Some(c) => Err(ParsingError::InvalidTagChar(c, i..i + c.len_utf8()))?,
+                },
+
+                State::Text => match ch {
+                    None if self.lenient && !self.open.is_empty() => {
+                        if let Some(token) = self.take_text(i) {
+                            return Ok(Some(token));
+                        }
+
+                        let span = self.open.pop().expect("open tags are left");
+                        self.warnings.push(ParsingError::UnclosedTags(span));
+                        return Ok(Some((Token::End, i..i)));
+                    }
+                    None => {
+                        self.done = true;
+
+                        if let Some(span) = self.open.last().filter(|_| !self.partial) {
+                            Err(ParsingError::UnclosedTags(span.clone()))?
+                        }
+
+                        return Ok(self.take_text(i));
+                    }
+                    Some(c) if c == self.opts.escape => {
+                        if self.text.is_empty() {
+                            self.text_start = i;
+                        }
+
+                        State::Escape
+                    }
+                    Some('<') if self.source[i..].starts_with(COMMENT) => {
+                        self.tag_start = i;
+                        self.chars.nth(COMMENT.len() - 2);
+
+                        if self.text.is_empty() {
+                            self.text_start = i;
+                        }
+
+                        State::Comment(0)
+                    }
+                    Some('<') => {
+                        self.tag_start = i;
+                        self.state = State::Lt;
+
+                        if let Some(token) = self.take_text(i) {
+                            return Ok(Some(token));
+                        }
+
+                        continue;
+                    }
+                    Some(c) => {
+                        if self.text.is_empty() {
+                            self.text_start = i;
+                        }
+
+                        self.text.push(c);
+                        State::Text
+                    }
+                },
+            };
+        }
+    }
+
+    /// Track the opening tag at `span`, enforcing the tag limits of the options
+    fn open_tag(&mut self, span: Range<usize>) -> Result<(), ParsingError> {
+        self.tags += 1;
+
+        if let Some(max) = self.opts.max_tags.filter(|&max| self.tags > max) {
+            return Err(ParsingError::LimitExceeded(Limit::Tags, max, span));
+        }
+
+        let depth = self.outer.len() + self.open.len();
+        if let Some(max) = self.opts.max_depth.filter(|&max| depth >= max) {
+            return Err(ParsingError::LimitExceeded(Limit::Depth, max, span));
+        }
+
+        self.open.push(span);
+        Ok(())
+    }
+
+    /// Close the innermost open tag, a named closing tag (`</error>`) has to repeat it
+    fn close_tag(&mut self, name: &str, span: Range<usize>) -> Result<(), ParsingError> {
+        let opened = match self.open.last() {
+            Some(open) => &self.source[open.start + 1..open.end - 1],
+            None => self
+                .outer
+                .last()
+                .map(|(opened, _)| opened.as_str())
+                .ok_or(ParsingError::UnexpectedClosingTag(span.clone()))?,
+        }
+        .trim();
+
+        // `</if>` closes any `<if ...>`
+        let matches = opened.split_whitespace().eq(name.split_whitespace())
+            || (name.trim() == IF && argument_of(opened, IF).is_some());
+
+        if !name.trim().is_empty() && !matches {
+            return Err(ParsingError::MismatchedClosingTag(
+                name.trim().to_string(),
+                opened.to_string(),
+                span,
+            ));
+        }
+
+        if self.open.pop().is_none() {
+            self.outer.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Keep the tag that failed with `err` as literal text, for lenient mode
+    fn recover(&mut self, err: ParsingError) {
+        // The invalid character may start the next tag
+        let end = match err {
+            ParsingError::InvalidTagChar(c, ref span) => {
+                self.pushback = Some((span.start, c));
+                span.start
+            }
+            _ => self.pos,
+        };
+
+        if self
+            .open
+            .last()
+            .is_some_and(|open| open.start == self.tag_start)
+        {
+            self.open.pop();
+        }
+
+        if self.text.is_empty() {
+            self.text_start = self.tag_start;
+        }
+
+        self.text.push_str(&self.source[self.tag_start..end]);
+        self.state = State::Text;
+        self.warnings.push(err);
+    }
+
+    /// Emit the text collected up to `end`, if any
+    fn take_text(&mut self, end: usize) -> Option<(Token, Range<usize>)> {
+        if self.text.is_empty() {
+            return None;
+        }
+
+        let text = std::mem::take(&mut self.text);
+        Some((Token::Text(text), self.text_start..end))
+    }
+}
+
+#[cfg(feature = "markup")]
+impl fmt::Debug for Tokens<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tokens")
+            .field("source", &self.source)
+            .field("state", &self.state)
+            .field("open", &self.open)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "markup")]
+impl Iterator for Tokens<'_> {
+    type Item = Result<(Token, Range<usize>), ParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let len = self.source.len();
+        if let Some(max) = self.opts.max_len.filter(|&max| len > max) {
+            let err = ParsingError::LimitExceeded(Limit::Length, max, max..len);
+
+            if !self.lenient {
+                self.done = true;
+                return Some(Err(err));
+            }
+
+            // Nothing was processed yet, the rest of the input is dropped
+            self.source = &self.source[..floor_char_boundary(self.source, max)];
+            self.chars = self.source.char_indices();
+            self.warnings.push(err);
+        }
+
+        loop {
+            match self.step() {
+                Ok(Some(token)) => return Some(Ok(token)),
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) if self.lenient => self.recover(err),
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// Name of the tag whose content is taken literally
+#[cfg(feature = "markup")]
+pub(crate) const RAW: &str = "raw";
+
+/// Closing tag of a raw section
+#[cfg(feature = "markup")]
+pub(crate) const RAW_END: &str = "</raw>";
+
+/// Name of the tag rendering its content only when a [`Condition`] holds
+#[cfg(feature = "markup")]
+pub(crate) const IF: &str = "if";
+
+/// Name of the tag replaced by a markup fragment
+#[cfg(feature = "markup")]
+pub(crate) const INCLUDE: &str = "include";
+
+/// Name of the tag whose content is highlighted as code
+#[cfg(feature = "code")]
+pub(crate) const CODE: &str = "code";
+
+/// Closing tag of a code section
+#[cfg(feature = "code")]
+pub(crate) const CODE_END: &str = "</code>";
+
+/// Get the argument of a `<name argument>` tag's content, `None` for other tags
+#[cfg(feature = "markup")]
+pub(crate) fn argument_of<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    tag.trim_start()
+        .strip_prefix(name)
+        .filter(|rest| rest.starts_with(|c: char| c.is_ascii_whitespace()))
+        .map(str::trim)
+}
+
+/// Start of a comment
+#[cfg(feature = "markup")]
+pub(crate) const COMMENT: &str = "<!--";
+
+/// End of a comment
+#[cfg(feature = "markup")]
+pub(crate) const COMMENT_END: &str = "-->";
+
+/// Check whether `c` may appear inside a tag
+#[cfg(feature = "markup")]
+pub(crate) fn is_tag_char(c: char) -> bool {
+    matches!(
+        c,
+        ',' | ':' | '=' | '#' | '-' | '_' | '(' | ')' | '%' | '!' | '.'
+    ) || c.is_ascii_whitespace()
+        || c.is_ascii_alphanumeric()
+}
+
+/// Check whether tag content is a single name (`error`) rather than a spec
+#[cfg(feature = "markup")]
+fn is_tag_name(tag: &str) -> bool {
+    !tag.is_empty() && !tag.contains(|c: char| c.is_ascii_whitespace() || c == '=')
+}
+
+/// Get the entries of `theme` close to the unknown tag `name`, closest first
+#[cfg(feature = "markup")]
+fn suggest(name: &str, theme: &Theme) -> Vec<String> {
+    let max = (name.chars().count() / 3).max(1);
+
+    let mut close = theme
+        .iter()
+        .map(|(entry, _)| (edit_distance(name, entry), entry))
+        .filter(|&(distance, _)| distance <= max)
+        .collect::<Vec<_>>();
+    close.sort();
+
+    close
+        .into_iter()
+        .map(|(_, entry)| entry.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between `a` and `b`, counted in chars
+#[cfg(feature = "markup")]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Splits markup text into a flat list of [`Token`]s with the byte range each covers
+///
+/// Tags naming an entry of `theme` resolve to that style, others are parsed as specs.
+/// Returned tokens always have balanced opening and closing tags.
+#[cfg(feature = "markup")]
+pub(crate) fn tokenize(
+    s: impl AsRef<str>,
+    dialect: &dyn Dialect,
+    theme: Option<&Theme>,
+    opts: &ParseOptions,
+) -> Result<Vec<(Token, Range<usize>)>, ParsingError> {
+    Tokens::new(s.as_ref(), dialect, theme, opts).collect()
+}
+
+/// Like [`tokenize`], but invalid tags are kept as text and unclosed tags closed at the end
+///
+/// Returns the tokens along with the errors that were recovered from. Markup over
+/// the length limit of the options is cut off at the limit.
+#[cfg(feature = "markup")]
+pub(crate) fn tokenize_lenient(
+    s: impl AsRef<str>,
+    dialect: &dyn Dialect,
+    theme: Option<&Theme>,
+    opts: &ParseOptions,
+) -> (Vec<(Token, Range<usize>)>, Vec<ParsingError>) {
+    let mut tokens = Tokens::new(s.as_ref(), dialect, theme, opts);
+    tokens.lenient = true;
+
+    let mut collected: Vec<(Token, Range<usize>)> = Vec::new();
+
+    // Text in front of a tag is emitted before the tag turns out to be text as well
+    for (token, span) in tokens.by_ref().flatten() {
+        match (collected.last_mut(), token) {
+            (Some((Token::Text(last), last_span)), Token::Text(text)) => {
+                last.push_str(&text);
+                last_span.end = span.end;
+            }
+            (_, token) => collected.push((token, span)),
+        }
+    }
+
+    (collected, tokens.warnings)
+}
+
+/// Split a spec into its arguments along with their byte ranges
+///
+/// Arguments are separated by whitespace outside of parentheses (`rgb(0, 0, 0)`),
+/// a `key=value` argument is split into the parameter and its value.
+fn split_args(s: &str) -> Result<Vec<(&str, Range<usize>)>, ParsingError> {
+    let mut args = Vec::new();
+
+    for (start, arg) in words(s) {
+        let Some((param, val)) = arg.split_once('=') else {
+            args.push((arg, start..start + arg.len()));
+            continue;
+        };
+
+        let param_span = start..start + param.len();
+
+        // `f b=r`, the pending parameter has no value
+        if let Some((pending, span)) = args.last().filter(|_| args.len() % 2 == 1) {
+            Err(ParsingError::MissingParamVal(
+                pending.to_string(),
+                args.len(),
+                span.clone(),
+            ))?
+        }
+
+        if val.is_empty() {
+            return Err(ParsingError::MissingParamVal(
+                param.to_string(),
+                args.len() + 1,
+                param_span,
+            ));
+        }
+
+        args.push((param, param_span));
+        args.push((val, start + param.len() + 1..start + arg.len()));
+    }
+
+    Ok(args)
+}
+
+/// Split `s` on whitespace outside of parentheses, along with the offset of every word
+fn words(s: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let (mut start, mut depth) = (None, 0usize);
+
+    for (i, ch) in s.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    words.push((begin, &s[begin..i]));
+                }
+                continue;
+            }
+            _ => (),
+        }
+
+        start.get_or_insert(i);
+    }
+
+    if let Some(begin) = start {
+        words.push((begin, &s[begin..]));
+    }
+
+    words
+}
+
+/// Parses the style spec
+///
+/// Multiple `m` groups are combined: modifiers are inserted in order, the last
+/// underline shape wins and raw parameters are appended. A repeated foreground or
+/// background is an error unless [`ParseOptions::lenient_repeats`] is set, then
+/// the last one wins.
+pub(crate) fn parse_style(
+    s: impl AsRef<str>,
+    dialect: &dyn Dialect,
+    opts: &ParseOptions,
+) -> Result<Style, ParsingError> {
+    let s = s.as_ref();
+    let arguments = split_args(s)?;
+
+    let length = arguments.len();
+
+    if let Some(max) = opts.max_args.filter(|&max| length > max) {
+        let span = arguments[max].1.start..s.trim_end().len();
+        Err(ParsingError::TooManyArgs(s.to_string(), length, max, span))?
+    }
+
+    if let [.., (param, span)] = &arguments[..]
+        && length % 2 == 1
+    {
+        Err(ParsingError::MissingParamVal(
+            param.to_string(),
+            length,
+            span.clone(),
+        ))?
+    }
+
+    let mut style = Style::new();
+    // Whether the foreground and the background were set already
+    let mut seen = [false; 2];
+
+    for arg in arguments.chunks_exact(2) {
+        if let [(param, param_span), (val, span)] = arg {
+            let kind = dialect.param(param).ok_or_else(|| {
+                ParsingError::InvalidParamName(param.to_string(), param_span.clone())
+            })?;
+
+            if kind != Param::Modifiers {
+                let slot = usize::from(kind.is_bg());
+                if seen[slot] && !opts.lenient_repeats {
+                    Err(ParsingError::DuplicateParam(
+                        param.to_string(),
+                        param_span.clone(),
+                    ))?
+                }
+                seen[slot] = true;
+            }
+
+            let color = || parse_color(val, dialect, opts).map_err(|e| e.offset(span.start));
+            let bright = kind.is_bright() || is_bright_letter(val, opts);
+
+            style = match kind {
+                Param::Fg | Param::BrightFg => {
+                    let style = style.clear_fg().fg(color()?);
+                    if bright { style.fg_brighten() } else { style }
+                }
+                Param::Bg | Param::BrightBg => {
+                    let style = style.clear_bg().bg(color()?);
+                    if bright { style.bg_brighten() } else { style }
+                }
+                Param::Modifiers => {
+                    if let Some(raw) = val.strip_prefix("raw:") {
+                        style.raw = style
+                            .raw
+                            .append(raw)
+                            .map_err(|e| e.offset(span.start + 4))?;
+                        style
+                    } else if let Some(name) = val.strip_prefix("u:") {
+                        let ul =
+                            UnderlineStyle::from_name(&name.to_lowercase()).ok_or_else(|| {
+                                ParsingError::InvalidUnderline(
+                                    name.to_string(),
+                                    span.start + 2..span.end,
+                                )
+                            })?;
+                        style.underline_style(ul)
+                    } else {
+                        parse_modfiers(val)
+                            .into_iter()
+                            .map(|mdf| mdf.map(|(mdf, _)| mdf))
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|e| e.offset(span.start))?
+                            .into_iter()
+                            .fold(style, Style::insert_modifier)
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(style)
+}
+
+/// Check whether `val` is an uppercase color letter (`R`), which stands for the bright variant
+///
+/// Aliases registered under the letter take precedence.
+fn is_bright_letter(val: &str, opts: &ParseOptions) -> bool {
+    let mut chars = val.chars();
+
+    matches!(
+        (chars.next(), chars.next()),
+        (Some(c), None) if c.is_ascii_uppercase() && Color::from_char(c.to_ascii_lowercase()).is_some()
+    ) && !opts.aliases.contains(val)
+}
+
+/// Collects everything questionable about a spec, see [`Style::check_spec`]
+pub(crate) fn check_style(s: &str, dialect: &dyn Dialect, opts: &ParseOptions) -> Vec<SpecWarning> {
+    let invalid = |err: ParsingError| SpecWarning::Invalid(err.to_string(), err.span());
+
+    let arguments = match split_args(s) {
+        Ok(arguments) => arguments,
+        Err(err) => return vec![invalid(err)],
+    };
+
+    let mut warnings = Vec::new();
+
+    if let Some(max) = opts.max_args.filter(|&max| arguments.len() > max) {
+        let span = arguments[max].1.start..s.trim_end().len();
+        warnings.push(invalid(ParsingError::TooManyArgs(
+            s.to_string(),
+            arguments.len(),
+            max,
+            span,
+        )));
+    }
+
+    if let [.., (param, span)] = &arguments[..]
+        && arguments.len() % 2 == 1
+    {
+        warnings.push(invalid(ParsingError::MissingParamVal(
+            param.to_string(),
+            arguments.len(),
+            span.clone(),
+        )));
+    }
+
+    // Modifiers set so far, along with where each of them was set
+    let mut mdfs = Modifiers::default();
+    let mut mdf_spans: Vec<(Modifier, char, Range<usize>)> = Vec::new();
+    let mut seen = [false; 2];
+
+    for arg in arguments.chunks_exact(2) {
+        let [(param, param_span), (val, span)] = arg else {
+            continue;
+        };
+
+        match dialect.param(param) {
+            Some(kind @ (Param::Fg | Param::BrightFg | Param::Bg | Param::BrightBg)) => {
+                let slot = usize::from(kind.is_bg());
+                if std::mem::replace(&mut seen[slot], true) {
+                    warnings.push(SpecWarning::DuplicateColor(
+                        param.to_string(),
+                        param_span.clone(),
+                    ));
+                }
+
+                match parse_color(val, dialect, opts) {
+                    Ok(color) if kind.is_bright() && !color.is_color() => warnings.push(
+                        SpecWarning::IneffectiveBright(val.to_string(), span.clone()),
+                    ),
+                    Ok(_) => (),
+                    Err(err) => warnings.push(invalid(err.offset(span.start))),
+                }
+            }
+
+            Some(Param::Modifiers) if val.starts_with("raw:") => {
+                if let Err(err) = RawSgr::parse(&val[4..]) {
+                    warnings.push(invalid(err.offset(span.start + 4)));
+                }
+            }
+
+            Some(Param::Modifiers) if val.starts_with("u:") => {
+                if UnderlineStyle::from_name(&val[2..].to_lowercase()).is_none() {
+                    warnings.push(invalid(ParsingError::InvalidUnderline(
+                        val[2..].to_string(),
+                        span.start + 2..span.end,
+                    )));
+                }
+            }
+
+            Some(Param::Modifiers) => {
+                for mdf in parse_modfiers(val) {
+                    let (mdf, ch_span) = match mdf {
+                        Ok((mdf, mdf_span)) => {
+                            (mdf, span.start + mdf_span.start..span.start + mdf_span.end)
+                        }
+                        Err(err) => {
+                            warnings.push(invalid(err.offset(span.start)));
+                            continue;
+                        }
+                    };
+                    let ch = mdf.to_char();
+
+                    if mdfs.contains(mdf) {
+                        warnings.push(SpecWarning::RedundantModifier(ch, ch_span));
+                        continue;
+                    }
+
+                    mdfs.insert(mdf);
+                    mdf_spans.retain(|(earlier, ch, span)| {
+                        let kept = mdfs.contains(*earlier);
+                        if !kept {
+                            warnings.push(SpecWarning::OverriddenModifier(*ch, span.clone()));
+                        }
+                        kept
+                    });
+                    mdf_spans.push((mdf, ch, ch_span));
+                }
+            }
+
+            None => warnings.push(SpecWarning::UnknownParam(
+                param.to_string(),
+                param_span.clone(),
+            )),
+        }
+    }
+
+    if warnings.is_empty() {
+        let lenient = ParseOptions {
+            lenient_repeats: true,
+            ..opts.clone()
+        };
+
+        if let Ok(style) = parse_style(s, dialect, &lenient) {
+            let span = 0..s.trim_end().len();
+            warnings.extend(
+                style
+                    .validate()
+                    .into_iter()
+                    .map(|warning| SpecWarning::Conflict(warning, span.clone())),
+            );
+        }
+    }
+
+    warnings.sort_by_key(|warning| warning.span().start);
+    warnings
+}
+
+/// Parse the color spec for the style(s)
+pub(crate) fn parse_color(
+    s: &str,
+    dialect: &dyn Dialect,
+    opts: &ParseOptions,
+) -> Result<Color, ParsingError> {
+    if let Some(color) = opts.aliases.get(s).or_else(|| dialect.color_alias(s)) {
+        return Ok(color);
+    }
+
+    let span = 0..s.len();
+    let s = s.to_lowercase();
+
+    // 1-letter aliases
+    if s.len() == 1 && !s.chars().next().unwrap().is_ascii_digit() {
+        return match s.as_str() {
+            "r" => Ok(Color::Red),
+            "g" => Ok(Color::Green),
+            "b" => Ok(Color::Blue),
+            "c" => Ok(Color::Cyan),
+            "m" => Ok(Color::Magenta),
+            "y" => Ok(Color::Yellow),
+            "k" => Ok(Color::Black),
+            "w" => Ok(Color::White),
+            invalid_color => Err(ParsingError::InvalidClrSpec(
+                invalid_color.to_string(),
+                span.clone(),
+            ))?,
+        };
+    }
+
+    // Full color names: "red", "orange", "slategray"
+    if let Some(color) = Color::from_name(&s) {
+        return Ok(color);
+    }
+
+    // Numeric input -> Indexed
+    if let Ok(i) = s.parse() {
+        return Ok(Color::Indexed(i));
+    }
+
+    // CSS functional input: "rgb(0, 128, 255)", "hsl(210, 100%, 50%)"
+    if let Some((func, args)) = s.strip_suffix(')').and_then(|s| s.split_once('(')) {
+        return parse_color_fn(func.trim(), args, func.len() + 1, span);
+    }
+
+    // Hex input -> RGB
+    if let Some(hex) = dialect.hex_digits(&s) {
+        let expanded = match hex.len() {
+            2 => hex.repeat(3),
+            3 | 4 => hex
+                .chars()
+                .flat_map(|c| std::iter::repeat_n(c, 2))
+                .collect(),
+            6 | 8 => hex.to_string(),
+            l => Err(ParsingError::InvalidHexClr(
+                hex.to_string(),
+                l,
+                span.clone(),
+            ))?,
+        };
+
+        let channel = |i: usize, name: char| {
+            expanded
+                .get(2 * i..2 * i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| ParsingError::InvalidHexComp(name, expanded.clone(), span.clone()))
+        };
+
+        let (r, g, b) = (channel(0, 'r')?, channel(1, 'g')?, channel(2, 'b')?);
+
+        return Ok(match expanded.len() {
+            8 => Color::RGBA(r, g, b, channel(3, 'a')?),
+            _ => Color::RGB(r, g, b),
+        });
+    }
+
+    // RGB-style input: "255,,128"
+    if s.matches(',').count() == 2 {
+        let mut rgb = [0; 3];
+        let mut start = 0;
+
+        for (slot, part) in rgb.iter_mut().zip(s.split(',')) {
+            let trimmed = part.trim();
+
+            *slot = match trimmed.parse::<u8>() {
+                Ok(v) => v,
+                Err(_) if trimmed.is_empty() || opts.lenient_rgb => 0,
+                Err(_) => Err(ParsingError::InvalidRgbComponent(
+                    trimmed.to_string(),
+                    start..start + part.len(),
+                ))?,
+            };
+            start += part.len() + 1;
+        }
+
+        return Ok(Color::RGB(rgb[0], rgb[1], rgb[2]));
+    }
+
+    Err(ParsingError::UnknownClrFmt(s.to_string(), span))
+}
+
+/// Parse the arguments of a CSS color function, `rgb(0, 128, 255)` or `hsl(210, 100%, 50%)`
+///
+/// RGB channels are `0..=255` or percentages, the hue is in degrees, saturation and
+/// lightness are percentages (the `%` is optional). An optional fourth argument is
+/// the alpha, `0..=1` or a percentage, producing [`Color::RGBA`]. `start` is the
+/// offset of `args` in the parsed value.
+fn parse_color_fn(
+    func: &str,
+    args: &str,
+    start: usize,
+    span: Range<usize>,
+) -> Result<Color, ParsingError> {
+    let hsl = match func {
+        "rgb" | "rgba" => false,
+        "hsl" | "hsla" => true,
+        _ => return Err(ParsingError::UnknownClrFmt(format!("{func}({args})"), span)),
+    };
+
+    // Every argument's value, whether it is a percentage, its text and its span
+    let mut parts = Vec::new();
+    let mut offset = start;
+
+    for part in args.split(',') {
+        let text = part.trim();
+        let begin = offset + part.len() - part.trim_start().len();
+        let text_span = begin..begin + text.len();
+
+        let (digits, percent) = match text.strip_suffix('%') {
+            Some(digits) => (digits, true),
+            None if hsl && parts.is_empty() => (text.strip_suffix("deg").unwrap_or(text), false),
+            None => (text, false),
+        };
+
+        let value = digits
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| ParsingError::InvalidClrFnComp(text.to_string(), text_span.clone()))?;
+
+        parts.push((value, percent, text, text_span));
+        offset += part.len() + 1;
+    }
+
+    if !(3..=4).contains(&parts.len()) {
+        return Err(ParsingError::InvalidClrFnArgs(
+            func.to_string(),
+            parts.len(),
+            span,
+        ));
+    }
+
+    // Value of argument `i` scaled to `0..=max`, percentages are relative to `max`
+    let arg = |i: usize, max: f32| {
+        let (value, percent, text, span) = &parts[i];
+        let value = if *percent {
+            value / 100.0 * max
+        } else {
+            *value
+        };
+
+        (0.0..=max)
+            .contains(&value)
+            .then_some(value)
+            .ok_or_else(|| ParsingError::InvalidClrFnComp(text.to_string(), span.clone()))
+    };
+
+    let (r, g, b) = if hsl {
+        let (hue, percent, text, span) = &parts[0];
+        if *percent {
+            return Err(ParsingError::InvalidClrFnComp(
+                text.to_string(),
+                span.clone(),
+            ));
+        }
+
+        Color::from_hsl(*hue, arg(1, 100.0)? / 100.0, arg(2, 100.0)? / 100.0).to_rgb()
+    } else {
+        let channel = |i| arg(i, 255.0).map(|value| value.round() as u8);
+        (channel(0)?, channel(1)?, channel(2)?)
+    };
+
+    Ok(match parts.len() {
+        4 => Color::RGBA(r, g, b, (arg(3, 1.0)? * 255.0).round() as u8),
+        _ => Color::RGB(r, g, b),
+    })
+}
+
+/// Get the digits of a hex color written `#ff8800` or `0xff8800`
+fn prefixed_hex(s: &str) -> Option<&str> {
+    s.strip_prefix('#').or_else(|| s.strip_prefix("0x"))
+}
+
+/// Parse the modifiers for the style(s), along with the byte range each of them covers
+///
+/// A `!` or `-` in front of a modifier selects the "off" modifier disabling it
+/// (`!b` turns bold off). Invalid modifiers don't stop the parsing.
+fn parse_modfiers(input: &str) -> Vec<Result<(Modifier, Range<usize>), ParsingError>> {
+    let mut modifers = Vec::new();
+    let mut chars = input.char_indices();
+
+    while let Some((i, ch)) = chars.next() {
+        let negated = matches!(ch, '!' | '-');
+        let (ch, end) = match negated.then(|| chars.next()).flatten() {
+            Some((j, next)) => (next, j + next.len_utf8()),
+            None => (ch, i + ch.len_utf8()),
+        };
+        let span = i..end;
+
+        let mdf = Modifier::from_char(ch).and_then(|mdf| match negated {
+            true => mdf.negate(),
+            false => Some(mdf),
+        });
+
+        modifers.push(
+            mdf.map(|mdf| (mdf, span.clone()))
+                .ok_or(ParsingError::InvalidModifier(ch, span)),
+        );
+    }
+
+    modifers
+}
@@ -0,0 +1,178 @@
+//! Internals `cli-styler` and `cli-styler-macros` build on
+//!
+//! Not part of the public API, nothing in here is covered by semver.
+
+#[cfg(feature = "markup")]
+use std::{borrow::Cow, ops::Range};
+
+use crate::{
+    caps,
+    error::ParsingError,
+    parser::{self, Dialect, ParsingMode},
+    style::{ParseOptions, Style},
+};
+
+#[cfg(feature = "markup")]
+use crate::{
+    parser::{Token, Tokens},
+    style::ColorLevel,
+    theme::Theme,
+};
+
+#[cfg(feature = "markup")]
+pub use crate::shortcodes::{partial_start, replace};
+
+/// Shorthand for [`ParsingMode::Markup`]
+#[allow(non_upper_case_globals)]
+pub const Mk: ParsingMode = parser::Mk;
+
+/// Shorthand for [`ParsingMode::CommandLine`]
+#[cfg(feature = "cli")]
+#[allow(non_upper_case_globals)]
+pub const Cli: ParsingMode = parser::Cli;
+
+/// Name of the tag whose content is taken literally
+#[cfg(feature = "markup")]
+pub const RAW: &str = parser::RAW;
+
+/// Closing tag of a raw section
+#[cfg(feature = "markup")]
+pub const RAW_END: &str = parser::RAW_END;
+
+/// Name of the tag rendering its content only when a condition holds
+#[cfg(feature = "markup")]
+pub const IF: &str = parser::IF;
+
+/// Name of the tag whose content is highlighted as code
+#[cfg(feature = "code")]
+pub const CODE: &str = parser::CODE;
+
+/// Closing tag of a code section
+#[cfg(feature = "code")]
+pub const CODE_END: &str = parser::CODE_END;
+
+/// Start of a comment
+#[cfg(feature = "markup")]
+pub const COMMENT: &str = parser::COMMENT;
+
+/// End of a comment
+#[cfg(feature = "markup")]
+pub const COMMENT_END: &str = parser::COMMENT_END;
+
+/// Get the argument of a `<name argument>` tag's content, `None` for other tags
+#[cfg(feature = "markup")]
+pub fn argument_of<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    parser::argument_of(tag, name)
+}
+
+/// Check whether `c` may appear inside a tag
+#[cfg(feature = "markup")]
+pub fn is_tag_char(c: char) -> bool {
+    parser::is_tag_char(c)
+}
+
+/// Split markup into tokens with balanced tags, tags naming an entry of `theme` resolve to it
+#[cfg(feature = "markup")]
+pub fn tokenize(
+    s: impl AsRef<str>,
+    dialect: &dyn Dialect,
+    theme: Option<&Theme>,
+    opts: &ParseOptions,
+) -> Result<Vec<(Token, Range<usize>)>, ParsingError> {
+    parser::tokenize(s, dialect, theme, opts)
+}
+
+/// Like [`tokenize`], but recovering from errors, which are returned along with the tokens
+#[cfg(feature = "markup")]
+pub fn tokenize_lenient(
+    s: impl AsRef<str>,
+    dialect: &dyn Dialect,
+    theme: Option<&Theme>,
+    opts: &ParseOptions,
+) -> (Vec<(Token, Range<usize>)>, Vec<ParsingError>) {
+    parser::tokenize_lenient(s, dialect, theme, opts)
+}
+
+/// Continue tokenizing a chunk of markup, given the tags left open by [`suspend_tokens`]
+#[cfg(feature = "markup")]
+pub fn resume_tokens<'a>(
+    chunk: &'a str,
+    dialect: &'a dyn Dialect,
+    theme: Option<&'a Theme>,
+    opts: &'a ParseOptions,
+    outer: Vec<(String, Range<usize>)>,
+    tags: usize,
+) -> Tokens<'a> {
+    Tokens::resume(chunk, dialect, theme, opts, outer, tags)
+}
+
+/// Get the tags left open and the number of opening tags, spans shifted by `offset`
+#[cfg(feature = "markup")]
+pub fn suspend_tokens(tokens: Tokens<'_>, offset: usize) -> (Vec<(String, Range<usize>)>, usize) {
+    tokens.suspend(offset)
+}
+
+/// Parse a single spec
+pub fn parse_style(
+    s: impl AsRef<str>,
+    dialect: &dyn Dialect,
+    opts: &ParseOptions,
+) -> Result<Style, ParsingError> {
+    parser::parse_style(s, dialect, opts)
+}
+
+/// Shift the span of an error by `by` bytes
+pub fn offset_error(err: ParsingError, by: usize) -> ParsingError {
+    err.offset(by)
+}
+
+/// Get the maximum markup length of the options
+pub fn max_len(opts: &ParseOptions) -> Option<usize> {
+    opts.max_len
+}
+
+/// Get the escape character of the options
+pub fn escape_char(opts: &ParseOptions) -> char {
+    opts.escape
+}
+
+/// Get the SGR parameters of a style, joined by `;`
+pub fn sgr_params(style: &Style) -> String {
+    style.collect()
+}
+
+/// Apply `;` separated SGR parameters to a style, like a terminal would
+pub fn apply_sgr(style: &mut Style, params: &str) {
+    style.apply_sgr(params);
+}
+
+/// Flip the switch of `cli_styler::set_enabled`
+pub fn set_enabled(enabled: bool) {
+    caps::set_enabled(enabled);
+}
+
+/// Check whether styling functions should emit escape sequences at all
+pub fn should_colorize() -> bool {
+    caps::should_colorize()
+}
+
+/// Get the color level markup is rendered for
+#[cfg(feature = "markup")]
+pub fn render_level() -> ColorLevel {
+    caps::render_level()
+}
+
+/// Get whether markup renders shortcodes as their ASCII fallback, `None` while they're kept
+#[cfg(feature = "markup")]
+pub fn shortcodes_ascii() -> Option<bool> {
+    caps::shortcodes_ascii()
+}
+
+/// Resolve the shortcodes of `text` as chosen with `caps::set_shortcodes`
+#[cfg(feature = "markup")]
+pub fn shortcoded(text: &str) -> Cow<'_, str> {
+    match caps::shortcodes_ascii() {
+        Some(ascii) if text.contains(':') => Cow::Owned(replace(text, ascii)),
+        _ => Cow::Borrowed(text),
+    }
+}
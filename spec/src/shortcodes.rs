@@ -0,0 +1,96 @@
+//! Shortcodes (`:warning:`) markup text may use, see [`crate::style::Shortcodes`]
+
+/// Known shortcodes with their symbol and ASCII fallback, sorted by name
+pub(crate) const SHORTCODES: &[(&str, &str, &str)] = &[
+    ("arrow_down", "↓", "v"),
+    ("arrow_left", "←", "<-"),
+    ("arrow_right", "→", "->"),
+    ("arrow_up", "↑", "^"),
+    ("bug", "🐛", "[bug]"),
+    ("bullet", "•", "*"),
+    ("check", "✔", "v"),
+    ("cross", "✖", "x"),
+    ("ellipsis", "…", "..."),
+    ("fire", "🔥", "(!)"),
+    ("heart", "♥", "<3"),
+    ("hourglass", "⌛", "(...)"),
+    ("info", "ℹ", "i"),
+    ("lightning", "⚡", "!"),
+    ("lock", "🔒", "[locked]"),
+    ("question", "❓", "?"),
+    ("rocket", "🚀", ">>"),
+    ("sparkles", "✨", "*"),
+    ("star", "★", "*"),
+    ("tada", "🎉", "\\o/"),
+    ("warning", "⚠", "!"),
+    ("white_check_mark", "✅", "[v]"),
+    ("x", "❌", "[x]"),
+];
+
+/// Get the symbol and the ASCII fallback of the shortcode `name`, without colons
+pub(crate) fn lookup(name: &str) -> Option<(&'static str, &'static str)> {
+    SHORTCODES
+        .binary_search_by_key(&name, |&(name, _, _)| name)
+        .ok()
+        .map(|i| (SHORTCODES[i].1, SHORTCODES[i].2))
+}
+
+/// Replace the known shortcodes of `text`, with their ASCII fallback if `ascii`
+///
+/// Unknown shortcodes are kept as written.
+pub fn replace(text: &str, ascii: bool) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find(':') {
+        output.push_str(&rest[..i]);
+        let tail = &rest[i + 1..];
+        let len = name_len(tail);
+
+        let known = tail[len..]
+            .starts_with(':')
+            .then(|| lookup(&tail[..len]))
+            .flatten();
+
+        match known {
+            Some((symbol, fallback)) => {
+                output.push_str(if ascii { fallback } else { symbol });
+                rest = &tail[len + 1..];
+            }
+            // The closing colon may open the next shortcode
+            None => {
+                output.push(':');
+                rest = tail;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Get the start of the shortcode `text` may end in the middle of
+pub fn partial_start(text: &str) -> Option<usize> {
+    let mut start = 0;
+
+    while let Some(i) = text[start..].find(':').map(|i| start + i) {
+        let len = name_len(&text[i + 1..]);
+
+        if i + 1 + len == text.len() {
+            return Some(i);
+        }
+
+        start = match lookup(&text[i + 1..i + 1 + len]) {
+            Some(_) if text[i + 1 + len..].starts_with(':') => i + len + 2,
+            _ => i + 1,
+        };
+    }
+
+    None
+}
+
+/// Get the length of the shortcode name `s` starts with
+fn name_len(s: &str) -> usize {
+    s.find(|c: char| !matches!(c, 'a'..='z' | '0'..='9' | '_' | '+' | '-'))
+        .unwrap_or(s.len())
+}
@@ -0,0 +1,2754 @@
+//! A library for styling terminal text with ANSI escape sequences.
+//!
+//! Provides functionality to create styled text with foreground/background colors,
+//! text modifiers (bold, italic, etc.), and batch styling operations.
+
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    ops::{BitOr, Range},
+    str::FromStr,
+    sync::OnceLock,
+};
+
+use crate::{
+    caps,
+    color::ANSI_PALETTE,
+    error::{ParsingError, StylerError},
+    names,
+    palette::Palette,
+    parser::{Dialect, Mk, check_style, parse_color, parse_style},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Constants for ANSI escape sequences
+const ESC: char = '\x1b';
+
+/// ANSI reset sequence that clears all formatting
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// Generates a Control Sequence Introducer (CSI) with the given formats
+///
+/// # Arguments
+/// * `formats` - The format codes to include in the CSI
+pub(crate) fn csi(formats: &str) -> String {
+    format!("{ESC}[{formats}m")
+}
+
+/// Wraps text with ANSI escape sequences and resets at the end
+///
+/// # Arguments
+/// * `text` - The text to wrap
+/// * `formats` - The format codes to apply
+fn wrap(text: &str, formats: &str) -> String {
+    if text.is_empty() || formats.is_empty() || !caps::should_colorize() {
+        return text.to_string();
+    }
+
+    format!("{}{text}{RESET}", csi(formats))
+}
+
+/// Provides `.style()` method for [`Style`] and [`CompiledStyle`]
+pub trait Stylable {
+    /// Apply this style to the provided text
+    fn style(&self, text: impl AsRef<str>) -> String;
+
+    /// Apply this style only when `cond` holds, otherwise return the plain text
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let failed = false;
+    /// let status = Style::new().fg(Color::Red).style_if(failed, "status");
+    ///
+    /// assert_eq!(status, "status");
+    /// ```
+    fn style_if(&self, cond: bool, text: impl AsRef<str>) -> String {
+        if cond {
+            self.style(text)
+        } else {
+            text.as_ref().to_string()
+        }
+    }
+
+    /// Apply this style to every line of the text separately
+    ///
+    /// The style is reset before and re-applied after every `\n`, so backgrounds and
+    /// inverted colors don't spill to the line end in pagers and terminal multiplexers.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    /// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+    ///
+    /// let note = Style::new().bg(Color::Blue).style_multiline("one\n\ntwo");
+    ///
+    /// assert_eq!(note, "\x1b[44mone\x1b[0m\n\n\x1b[44mtwo\x1b[0m");
+    /// ```
+    fn style_multiline(&self, text: impl AsRef<str>) -> String {
+        text.as_ref()
+            .split('\n')
+            .map(|line| self.style(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Stylable for Style {
+    fn style(&self, text: impl AsRef<str>) -> String {
+        wrap(text.as_ref(), &self.collect())
+    }
+}
+
+impl Stylable for CompiledStyle {
+    fn style(&self, text: impl AsRef<str>) -> String {
+        let text = text.as_ref();
+
+        if text.is_empty() || self.params.is_empty() || !caps::should_colorize() {
+            return text.to_string();
+        }
+
+        let sgr = self.sgr();
+        let mut out = String::with_capacity(sgr.len() + text.len() + RESET.len());
+        out.push_str(sgr);
+        out.push_str(text);
+        out.push_str(RESET);
+        out
+    }
+}
+
+/// Trait for easily applying compiled styles to strings
+pub trait Stylize {
+    /// Apply a style to this string
+    fn apply(&self, style: &impl Stylable) -> String;
+
+    /// Apply a style to this string only when `cond` holds
+    fn apply_if(&self, cond: bool, style: &impl Stylable) -> String;
+}
+
+impl Stylize for str {
+    fn apply(&self, style: &impl Stylable) -> String {
+        style.style(self)
+    }
+
+    fn apply_if(&self, cond: bool, style: &impl Stylable) -> String {
+        style.style_if(cond, self)
+    }
+}
+
+/// Generates the chainable shorthand methods shared by [`StylizeExt`] and [`Styled`]
+macro_rules! shorthands {
+    ($($(#[$meta:meta])* $name:ident => $builder:ident $(($arg:expr))?;)*) => {
+        /// Chainable shorthand methods (`"error".red().bold().on_yellow()`)
+        ///
+        /// Every method returns a [`Styled`] display adapter borrowing the text.
+        pub trait StylizeExt {
+            /// Wrap this text in an unstyled [`Styled`] adapter
+            fn styled(&self) -> Styled<'_>;
+
+            $(
+                $(#[$meta])*
+                fn $name(&self) -> Styled<'_> {
+                    self.styled().$name()
+                }
+            )*
+        }
+
+        impl<'a> Styled<'a> {
+            $(
+                $(#[$meta])*
+                pub fn $name(self) -> Self {
+                    self.map(|st| st.$builder($($arg)?))
+                }
+            )*
+        }
+    };
+}
+
+shorthands! {
+    /// Set the foreground to black
+    black => fg(Black);
+    /// Set the foreground to red
+    red => fg(Red);
+    /// Set the foreground to green
+    green => fg(Green);
+    /// Set the foreground to yellow
+    yellow => fg(Yellow);
+    /// Set the foreground to blue
+    blue => fg(Blue);
+    /// Set the foreground to magenta
+    magenta => fg(Magenta);
+    /// Set the foreground to cyan
+    cyan => fg(Cyan);
+    /// Set the foreground to white
+    white => fg(White);
+
+    /// Set the background to black
+    on_black => bg(Black);
+    /// Set the background to red
+    on_red => bg(Red);
+    /// Set the background to green
+    on_green => bg(Green);
+    /// Set the background to yellow
+    on_yellow => bg(Yellow);
+    /// Set the background to blue
+    on_blue => bg(Blue);
+    /// Set the background to magenta
+    on_magenta => bg(Magenta);
+    /// Set the background to cyan
+    on_cyan => bg(Cyan);
+    /// Set the background to white
+    on_white => bg(White);
+
+    /// Brighten the foreground color
+    bright => fg_brighten;
+    /// Brighten the background color
+    on_bright => bg_brighten;
+
+    /// Apply bold styling
+    bold => bold;
+    /// Apply dim styling
+    dim => dim;
+    /// Apply italic styling
+    italic => italic;
+    /// Apply underline styling
+    underline => underline;
+    /// Apply blink styling
+    blink => blink;
+    /// Swap foreground and background colors
+    invert => invert;
+    /// Hide text (revealed when selected)
+    hide => hide;
+    /// Apply strikethrough styling
+    strike => strike;
+    /// Apply double underline styling
+    double_ul => double_ul;
+    /// Apply overline styling
+    overline => overline;
+    /// Apply rapid blink styling
+    rapid_blink => rapid_blink;
+    /// Draw a frame around the text
+    framed => framed;
+    /// Draw a circle around the text
+    encircled => encircled;
+    /// Apply superscript styling
+    superscript => superscript;
+    /// Apply subscript styling
+    subscript => subscript;
+}
+
+impl StylizeExt for str {
+    fn styled(&self) -> Styled<'_> {
+        Styled {
+            style: Cow::Owned(Style::new()),
+            text: self,
+        }
+    }
+}
+
+/// Color Types with their respective ANSI code offsets
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub(crate) enum ClrType {
+    #[default]
+    Fg = 0,
+    Bg = 10,       // +10 to convert to background
+    FgBright = 60, // +60 to make the color intense
+    BgBright = 70, // +70 = 10 + 60 (convert to intense background)
+}
+
+impl ClrType {
+    /// Get the Control Sequence Introducer code depending on the type
+    ///
+    /// Returns:
+    /// - `38` for Foreground modification
+    /// - `48` for Background modification
+    fn get_csi(self) -> u8 {
+        match self {
+            Self::Fg | Self::FgBright => 38,
+            Self::Bg | Self::BgBright => 48,
+        }
+    }
+
+    /// Get the bright or normal variant of this color type
+    pub(crate) fn with_bright(self, bright: bool) -> Self {
+        match (self, bright) {
+            (Self::Fg | Self::FgBright, false) => Self::Fg,
+            (Self::Fg | Self::FgBright, true) => Self::FgBright,
+            (Self::Bg | Self::BgBright, false) => Self::Bg,
+            (Self::Bg | Self::BgBright, true) => Self::BgBright,
+        }
+    }
+
+    /// Check whether this is a bright color type
+    pub(crate) fn is_bright(self) -> bool {
+        matches!(self, Self::FgBright | Self::BgBright)
+    }
+}
+
+/// Which side of the text a color is applied to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ClrTarget {
+    /// The text itself
+    Fg,
+    /// The cells behind the text
+    Bg,
+}
+
+/// Color support levels of a terminal, ordered from least to most capable
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum ColorLevel {
+    /// No color support
+    None,
+    /// The 8 basic colors and their bright variants
+    Ansi16,
+    /// The 256-color indexed palette
+    Ansi256,
+    /// 24-bit RGB colors
+    #[default]
+    TrueColor,
+}
+
+/// Colors mapped to their respective ANSI codes
+#[repr(u8)]
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Color {
+    Black = 30,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    #[default]
+    White,
+
+    /// Indexed ANSI color (256-color mode)
+    Indexed(u8),
+
+    /// True RGB color
+    #[allow(clippy::upper_case_acronyms)]
+    RGB(u8, u8, u8),
+
+    /// RGB color with an alpha channel, see [`Color::composite_over`]
+    ///
+    /// Terminals can't blend, so the alpha is ignored when rendered.
+    #[allow(clippy::upper_case_acronyms)]
+    RGBA(u8, u8, u8, u8),
+
+    /// The terminal's default color (`39` / `49`)
+    TerminalDefault,
+}
+
+use Color::*;
+
+impl Color {
+    /// Check whether this is a basic ANSI color
+    pub fn is_color(self) -> bool {
+        matches!(
+            self,
+            Black | Red | Green | Yellow | Blue | Magenta | Cyan | White
+        )
+    }
+
+    /// Check whether this is an RGB color, with or without alpha
+    pub fn is_rgb(self) -> bool {
+        matches!(self, RGB(_, _, _) | RGBA(_, _, _, _))
+    }
+
+    /// Check whether this is the terminal's default color
+    pub fn is_default(self) -> bool {
+        matches!(self, TerminalDefault)
+    }
+
+    /// Check whether this is an indexed ANSI color
+    pub fn is_indexed(self) -> bool {
+        matches!(self, Indexed(_))
+    }
+
+    /// Convert a char to [`Color`]
+    pub fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'r' => Some(Red),
+            'g' => Some(Green),
+            'b' => Some(Blue),
+            'c' => Some(Cyan),
+            'm' => Some(Magenta),
+            'y' => Some(Yellow),
+            'k' => Some(Black),
+            'w' => Some(White),
+            _ => None,
+        }
+    }
+
+    /// Convert a basic [`Color`] to its spec char
+    pub fn to_char(self) -> Option<char> {
+        match self {
+            Red => Some('r'),
+            Green => Some('g'),
+            Blue => Some('b'),
+            Cyan => Some('c'),
+            Magenta => Some('m'),
+            Yellow => Some('y'),
+            Black => Some('k'),
+            White => Some('w'),
+            _ => None,
+        }
+    }
+
+    /// Format the color as a spec value (`r`, `99`, `#aabbcc`)
+    pub(crate) fn to_spec(self) -> String {
+        match self {
+            Indexed(i) => i.to_string(),
+            RGB(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+            RGBA(r, g, b, a) => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+            TerminalDefault => "default".to_string(),
+            color => color.to_char().map(String::from).unwrap_or_default(),
+        }
+    }
+
+    /// Look up a color by its full name
+    ///
+    /// The eight basic names (`red`, `blue`, ...) map to the terminal's ANSI colors,
+    /// every other CSS/X11 name (`orange`, `slategray`, ...) maps to its RGB value.
+    /// Matching is case-insensitive and ignores `-`, `_` and spaces.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name
+            .chars()
+            .filter(|c| !matches!(c, '-' | '_' | ' '))
+            .collect::<String>()
+            .to_lowercase();
+
+        match name.as_str() {
+            "black" => Some(Black),
+            "red" => Some(Red),
+            "green" => Some(Green),
+            "yellow" => Some(Yellow),
+            "blue" => Some(Blue),
+            "magenta" => Some(Magenta),
+            "cyan" => Some(Cyan),
+            "white" => Some(White),
+            "default" => Some(TerminalDefault),
+            name => names::lookup(name).map(|(r, g, b)| RGB(r, g, b)),
+        }
+    }
+
+    /// Get the corresponding ANSI code for basic colors
+    pub(crate) fn to_num(self) -> u8 {
+        match self {
+            Black => 30,
+            Red => 31,
+            Green => 32,
+            Yellow => 33,
+            Blue => 34,
+            Magenta => 35,
+            Cyan => 36,
+            White => 37,
+            _ => 0,
+        }
+    }
+
+    /// Write the SGR parameters of the color for the given color type
+    pub(crate) fn write_codes(self, ct: ClrType, w: &mut impl fmt::Write) -> fmt::Result {
+        match self {
+            Indexed(i) => write!(w, "{};5;{}", ct.get_csi(), i),
+            RGB(r, g, b) | RGBA(r, g, b, _) => {
+                write!(w, "{};2;{};{};{}", ct.get_csi(), r, g, b)
+            }
+            TerminalDefault => write!(w, "{}", ct.get_csi() + 1),
+            color => write!(w, "{}", color.to_num() + ct as u8),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = StylerError;
+
+    /// Parses a single color value of the spec grammar (`r`, `orange`, `99`, `#ABC`, `255,,0`, `rgb(0, 128, 255)`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_color(s, &Mk, &ParseOptions::new()).map_err(StylerError::ParsingError)
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = StylerError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// ANSI text modifiers
+///
+/// Most codes are widely compatible, rapid blink, framed, encircled, superscript
+/// and subscript are only rendered by some terminals.
+/// The `No*` variants turn a single attribute off without a full [`Modifier::Reset`];
+/// `NoBold` clears both bold and dim, `NoUnderline` both single and double underline,
+/// `NoBlink` both blink speeds, `NoFramed` framed and encircled, and `NoScript`
+/// superscript and subscript.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum Modifier {
+    Reset = 0,        // r        1
+    Bold,             // b        2
+    Dim,              // d        4
+    Italic,           // i        8
+    Underline,        // u       16
+    Blink,            // k       32
+    RapidBlink,       // q   524288
+    Invert = 7,       // v       64
+    Hide,             // h      128
+    Strike,           // s      256
+    DoubleUL = 21,    // l      512
+    Framed = 51,      // f  1048576
+    Encircled,        // e  2097152
+    Overline,         // o     1024
+    NoFramed,         // !f 16777216
+    NoBold = 22,      // !b    2048
+    NoItalic,         // !i    4096
+    NoUnderline,      // !u    8192
+    NoBlink,          // !k   16384
+    NoInvert = 27,    // !v   32768
+    NoHide,           // !h   65536
+    NoStrike,         // !s  131072
+    NoOverline = 55,  // !o  262144
+    Superscript = 73, // p  4194304
+    Subscript,        // n  8388608
+    NoScript,         // !p 33554432
+}
+
+use Modifier::*;
+
+impl Modifier {
+    /// Every modifier, ordered by its bit in [`Modifiers`]
+    pub(crate) const ALL: [Modifier; 26] = [
+        Reset,
+        Bold,
+        Dim,
+        Italic,
+        Underline,
+        Blink,
+        Invert,
+        Hide,
+        Strike,
+        DoubleUL,
+        Overline,
+        NoBold,
+        NoItalic,
+        NoUnderline,
+        NoBlink,
+        NoInvert,
+        NoHide,
+        NoStrike,
+        NoOverline,
+        RapidBlink,
+        Framed,
+        Encircled,
+        Superscript,
+        Subscript,
+        NoFramed,
+        NoScript,
+    ];
+
+    /// Get the bit representing this modifier inside [`Modifiers`]
+    const fn bit(self) -> u32 {
+        match self {
+            Reset => 1,
+            Bold => 1 << 1,
+            Dim => 1 << 2,
+            Italic => 1 << 3,
+            Underline => 1 << 4,
+            Blink => 1 << 5,
+            Invert => 1 << 6,
+            Hide => 1 << 7,
+            Strike => 1 << 8,
+            DoubleUL => 1 << 9,
+            Overline => 1 << 10,
+            NoBold => 1 << 11,
+            NoItalic => 1 << 12,
+            NoUnderline => 1 << 13,
+            NoBlink => 1 << 14,
+            NoInvert => 1 << 15,
+            NoHide => 1 << 16,
+            NoStrike => 1 << 17,
+            NoOverline => 1 << 18,
+            RapidBlink => 1 << 19,
+            Framed => 1 << 20,
+            Encircled => 1 << 21,
+            Superscript => 1 << 22,
+            Subscript => 1 << 23,
+            NoFramed => 1 << 24,
+            NoScript => 1 << 25,
+        }
+    }
+
+    /// Get the bits of the modifiers whose effect is overridden when this one is applied after them
+    const fn overrides(self) -> u32 {
+        match self {
+            Reset => !Reset.bit(),
+            Bold | Dim => NoBold.bit(),
+            Italic => NoItalic.bit(),
+            Underline | DoubleUL => NoUnderline.bit(),
+            Blink | RapidBlink => NoBlink.bit(),
+            Invert => NoInvert.bit(),
+            Hide => NoHide.bit(),
+            Strike => NoStrike.bit(),
+            Overline => NoOverline.bit(),
+            Framed | Encircled => NoFramed.bit(),
+            Superscript | Subscript => NoScript.bit(),
+            NoBold => Bold.bit() | Dim.bit(),
+            NoItalic => Italic.bit(),
+            NoUnderline => Underline.bit() | DoubleUL.bit(),
+            NoBlink => Blink.bit() | RapidBlink.bit(),
+            NoInvert => Invert.bit(),
+            NoHide => Hide.bit(),
+            NoStrike => Strike.bit(),
+            NoOverline => Overline.bit(),
+            NoFramed => Framed.bit() | Encircled.bit(),
+            NoScript => Superscript.bit() | Subscript.bit(),
+        }
+    }
+
+    /// Check whether this is one of the targeted "off" modifiers
+    pub fn is_off(self) -> bool {
+        self.negate().is_none() && self != Reset
+    }
+
+    /// Get the "off" modifier disabling this one, `None` for [`Modifier::Reset`] and "off" modifiers
+    pub fn negate(self) -> Option<Self> {
+        match self {
+            Bold | Dim => Some(NoBold),
+            Italic => Some(NoItalic),
+            Underline | DoubleUL => Some(NoUnderline),
+            Blink | RapidBlink => Some(NoBlink),
+            Invert => Some(NoInvert),
+            Hide => Some(NoHide),
+            Strike => Some(NoStrike),
+            Overline => Some(NoOverline),
+            Framed | Encircled => Some(NoFramed),
+            Superscript | Subscript => Some(NoScript),
+            _ => None,
+        }
+    }
+
+    /// Convert a char to [`Modifier`]
+    pub fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'b' => Some(Bold),
+            'd' => Some(Dim),
+            'i' => Some(Italic),
+            'u' => Some(Underline),
+            'k' => Some(Blink),
+            'v' => Some(Invert),
+            'h' => Some(Hide),
+            's' => Some(Strike),
+            'l' => Some(DoubleUL),
+            'o' => Some(Overline),
+            'q' => Some(RapidBlink),
+            'f' => Some(Framed),
+            'e' => Some(Encircled),
+            'p' => Some(Superscript),
+            'n' => Some(Subscript),
+            'r' => Some(Reset),
+            _ => None,
+        }
+    }
+
+    /// Get the SGR code which turns this modifier off, if it has any lasting effect
+    ///
+    /// Some codes are shared: `22` clears both bold and dim, `24` both underlines, and so on.
+    pub(crate) fn off_code(self) -> Option<u8> {
+        self.negate().map(|off| off as u8)
+    }
+
+    /// Convert a [`Modifier`] to its spec char
+    ///
+    /// "Off" modifiers share the char of the attribute they disable.
+    pub fn to_char(self) -> char {
+        match self {
+            Bold | NoBold => 'b',
+            Dim => 'd',
+            Italic | NoItalic => 'i',
+            Underline | NoUnderline => 'u',
+            Blink | NoBlink => 'k',
+            Invert | NoInvert => 'v',
+            Hide | NoHide => 'h',
+            Strike | NoStrike => 's',
+            DoubleUL => 'l',
+            Overline | NoOverline => 'o',
+            RapidBlink => 'q',
+            Framed | NoFramed => 'f',
+            Encircled => 'e',
+            Superscript | NoScript => 'p',
+            Subscript => 'n',
+            Reset => 'r',
+        }
+    }
+
+    /// Format the modifier as it appears in a spec (`b`, `!b`)
+    pub(crate) fn to_spec(self) -> String {
+        if self.is_off() {
+            format!("!{}", self.to_char())
+        } else {
+            self.to_char().to_string()
+        }
+    }
+}
+
+/// Compact set of [`Modifier`]s, one bit per modifier
+///
+/// Iteration yields modifiers in their canonical (bit) order, which is also the
+/// order in which their SGR codes are emitted.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Modifiers(u32);
+
+impl Modifiers {
+    /// Add a modifier to the set, dropping the modifiers it overrides
+    ///
+    /// [`Modifier::Reset`] drops every other modifier, while an "off" modifier
+    /// and the modifiers it disables drop each other.
+    pub(crate) const fn insert(&mut self, mdf: Modifier) {
+        self.0 = self.0 & !mdf.overrides() | mdf.bit();
+    }
+
+    /// Remove a modifier from the set
+    pub(crate) fn remove(&mut self, mdf: Modifier) {
+        self.0 &= !mdf.bit();
+    }
+
+    /// Check whether the set contains the modifier
+    pub(crate) fn contains(self, mdf: Modifier) -> bool {
+        self.0 & mdf.bit() != 0
+    }
+
+    /// Check whether the set is empty
+    pub(crate) fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the modifiers in canonical order
+    pub(crate) fn iter(self) -> impl Iterator<Item = Modifier> {
+        Modifier::ALL
+            .into_iter()
+            .filter(move |&mdf| self.contains(mdf))
+    }
+}
+
+impl Extend<Modifier> for Modifiers {
+    fn extend<T: IntoIterator<Item = Modifier>>(&mut self, iter: T) {
+        for mdf in iter {
+            self.insert(mdf);
+        }
+    }
+}
+
+/// Serialized as the list of its modifiers, keeping the bit layout internal
+#[cfg(feature = "serde")]
+impl Serialize for Modifiers {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Modifiers {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut mdfs = Self::default();
+        mdfs.extend(Vec::<Modifier>::deserialize(deserializer)?);
+        Ok(mdfs)
+    }
+}
+
+/// Underline shapes, emitted as the `4:1` to `4:5` sub-parameters
+///
+/// Terminals without support usually fall back to a single underline.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub enum UnderlineStyle {
+    /// A single straight line
+    #[default]
+    Single = 1,
+    /// Two straight lines
+    Double,
+    /// A wavy line, common for diagnostics
+    Curly,
+    /// A dotted line
+    Dotted,
+    /// A dashed line
+    Dashed,
+}
+
+impl UnderlineStyle {
+    /// Every underline style, ordered by sub-parameter
+    pub(crate) const ALL: [UnderlineStyle; 5] = [
+        Self::Single,
+        Self::Double,
+        Self::Curly,
+        Self::Dotted,
+        Self::Dashed,
+    ];
+
+    /// Get the name used in specs (`m u:curly`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Double => "double",
+            Self::Curly => "curly",
+            Self::Dotted => "dotted",
+            Self::Dashed => "dashed",
+        }
+    }
+
+    /// Look up an underline style by its spec name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|ul| ul.name() == name)
+    }
+
+    /// Look up an underline style by its sub-parameter, `0` and unknown values give `None`
+    pub(crate) fn from_sub_param(param: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|&ul| ul as u8 == param)
+    }
+}
+
+/// SGR parameters attached to a [`Style`] verbatim, see [`Style::raw_sgr`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub(crate) struct RawSgr {
+    /// Number of used entries in `params`
+    len: u8,
+    /// Parameters, in the order they are emitted
+    params: [u8; RawSgr::CAPACITY],
+}
+
+impl RawSgr {
+    /// Maximum number of raw parameters a style can carry
+    pub(crate) const CAPACITY: usize = 8;
+
+    /// No raw parameters
+    const EMPTY: Self = Self {
+        len: 0,
+        params: [0; Self::CAPACITY],
+    };
+
+    /// Parse `;` or `,` separated numeric parameters (`"58;5;196"`)
+    pub(crate) fn parse(s: &str) -> Result<Self, ParsingError> {
+        Self::EMPTY.append(s)
+    }
+
+    /// Parse parameters like [`RawSgr::parse`] and add them after the existing ones
+    pub(crate) fn append(mut self, s: &str) -> Result<Self, ParsingError> {
+        let invalid = || ParsingError::InvalidRawSgr(s.to_string(), 0..s.len());
+
+        for param in s.split([';', ',']) {
+            let slot = self.params.get_mut(self.len as usize).ok_or_else(invalid)?;
+            *slot = param.trim().parse().map_err(|_| invalid())?;
+            self.len += 1;
+        }
+
+        Ok(self)
+    }
+
+    /// Get the parameters
+    pub(crate) fn params(&self) -> &[u8] {
+        &self.params[..self.len as usize]
+    }
+
+    /// Check whether there are no parameters
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A combination of attributes which is valid but probably doesn't look as intended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StyleWarning {
+    /// Bold and dim are both set, terminals render this inconsistently
+    BoldDim,
+    /// Underline and double underline are both set, only one is shown
+    UnderlineDoubleUnderline,
+    /// Hidden text is combined with other attributes which can't be seen
+    HiddenWithOthers,
+    /// Reset is combined with other attributes, which are applied after it
+    ResetWithOthers,
+    /// Foreground and background are the same color, the text is unreadable
+    SameColors,
+}
+
+impl fmt::Display for StyleWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BoldDim => "bold and dim are both set",
+            Self::UnderlineDoubleUnderline => "underline and double underline are both set",
+            Self::HiddenWithOthers => "hidden text has other attributes",
+            Self::ResetWithOthers => "reset is combined with other attributes",
+            Self::SameColors => "foreground and background are the same color",
+        })
+    }
+}
+
+/// Something questionable about a spec, reported by [`Style::check_spec`]
+///
+/// The last field of every variant is the byte range of the affected part of the spec.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SpecWarning {
+    /// Part of the spec can't be parsed, with the parser's error message
+    Invalid(String, Range<usize>),
+    /// A parameter which doesn't exist (`x` in `x r`)
+    UnknownParam(String, Range<usize>),
+    /// The foreground or background is set more than once
+    DuplicateColor(String, Range<usize>),
+    /// A modifier which is already set
+    RedundantModifier(char, Range<usize>),
+    /// A modifier which is undone by a later one (`b` in `m b m r`)
+    OverriddenModifier(char, Range<usize>),
+    /// `fb` or `bb` with an indexed or RGB color, only basic colors have bright variants
+    IneffectiveBright(String, Range<usize>),
+    /// The spec parses to a contradictory style, see [`Style::validate`]
+    Conflict(StyleWarning, Range<usize>),
+}
+
+impl SpecWarning {
+    /// Get the byte range of the affected part of the spec
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::Invalid(_, span)
+            | Self::UnknownParam(_, span)
+            | Self::DuplicateColor(_, span)
+            | Self::RedundantModifier(_, span)
+            | Self::OverriddenModifier(_, span)
+            | Self::IneffectiveBright(_, span)
+            | Self::Conflict(_, span) => span.clone(),
+        }
+    }
+}
+
+impl fmt::Display for SpecWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(msg, _) => f.write_str(msg),
+            Self::UnknownParam(param, _) => write!(f, "unknown parameter '{param}'"),
+            Self::DuplicateColor(param, _) => write!(f, "'{param}' sets a color set before"),
+            Self::RedundantModifier(ch, _) => write!(f, "modifier '{ch}' is already set"),
+            Self::OverriddenModifier(ch, _) => write!(f, "modifier '{ch}' is undone later"),
+            Self::IneffectiveBright(color, _) => {
+                write!(f, "'{color}' has no bright variant, only basic colors do")
+            }
+            Self::Conflict(warning, _) => warning.fmt(f),
+        }
+    }
+}
+
+/// Options changing how specs are parsed, see [`Style::new_from_cli_spec_with`]
+///
+/// The defaults are what [`Style::new_from_cli_spec`] and markup use.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// Whether unparsable RGB components silently become `0`
+    pub(crate) lenient_rgb: bool,
+    /// Maximum number of arguments, unlimited if `None`
+    pub(crate) max_args: Option<usize>,
+    /// Whether a repeated foreground or background replaces the earlier one
+    pub(crate) lenient_repeats: bool,
+    /// User defined color names, resolved before the built-in ones
+    pub(crate) aliases: Palette,
+    /// Maximum length of markup in bytes, unlimited if `None`
+    pub(crate) max_len: Option<usize>,
+    /// Maximum number of opening tags in markup, unlimited if `None`
+    pub(crate) max_tags: Option<usize>,
+    /// Maximum nesting depth of markup tags, unlimited if `None`
+    pub(crate) max_depth: Option<usize>,
+    /// Character escaping `<`, `>` and itself in markup text
+    pub(crate) escape: char,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseOptions {
+    /// Creates the default, strict [`ParseOptions`]
+    pub const fn new() -> Self {
+        Self {
+            lenient_rgb: false,
+            max_args: None,
+            lenient_repeats: false,
+            aliases: Palette::new(),
+            max_len: None,
+            max_tags: None,
+            max_depth: None,
+            escape: '\\',
+        }
+    }
+
+    /// Register a color name usable wherever specs take a color (`f brand`, `<b brand>`)
+    ///
+    /// Aliases are matched exactly and take precedence over the built-in names,
+    /// registering a name again replaces its color.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::ParseOptions};
+    ///
+    /// let opts = ParseOptions::new().with_alias("brand", Color::RGB(0x12, 0x34, 0x56));
+    ///
+    /// assert_eq!(
+    ///     Style::new_from_cli_spec_with("f brand m b", &opts).unwrap(),
+    ///     Style::new().fg_rgb(0x12, 0x34, 0x56).bold()
+    /// );
+    /// ```
+    pub fn with_alias(mut self, name: impl Into<String>, color: Color) -> Self {
+        self.aliases.insert(name, color);
+        self
+    }
+
+    /// Register every color of `palette` as an alias, see [`ParseOptions::with_alias`]
+    pub fn with_aliases(mut self, palette: &Palette) -> Self {
+        self.aliases.extend(
+            palette
+                .iter()
+                .map(|(name, color)| (name.to_string(), color)),
+        );
+        self
+    }
+
+    /// Creates [`ParseOptions`] with every lenient behavior turned on
+    ///
+    /// Useful for specs written by hand on the command line, where mistakes should
+    /// degrade gracefully instead of failing.
+    pub const fn lenient() -> Self {
+        Self::new().lenient_rgb(true).lenient_repeats(true)
+    }
+
+    /// Let a repeated foreground or background (`f r f g`) replace the earlier one
+    ///
+    /// Repeating them is an error by default. Repeated `m` groups are always
+    /// combined, later modifiers overriding earlier ones as in [`Style::merge`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::ParseOptions};
+    ///
+    /// let lenient = ParseOptions::new().lenient_repeats(true);
+    ///
+    /// assert!(Style::new_from_cli_spec("f r f g").is_err());
+    /// assert_eq!(
+    ///     Style::new_from_cli_spec_with("fb r f g", &lenient).unwrap(),
+    ///     Style::new().fg(Color::Green)
+    /// );
+    /// ```
+    pub const fn lenient_repeats(mut self, lenient: bool) -> Self {
+        self.lenient_repeats = lenient;
+        self
+    }
+
+    /// Reject specs with more than `max` arguments (`f r` and `f=r` are both two)
+    ///
+    /// Specs are unlimited by default, mind that every repeated `m` group counts.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::ParseOptions};
+    ///
+    /// let short = ParseOptions::new().max_args(4);
+    ///
+    /// assert!(Style::new_from_cli_spec_with("f r b k", &short).is_ok());
+    /// assert!(Style::new_from_cli_spec_with("f r b k m b", &short).is_err());
+    /// assert!(Style::new_from_cli_spec("f r fb g").is_err());
+    /// ```
+    pub const fn max_args(mut self, max: usize) -> Self {
+        self.max_args = Some(max);
+        self
+    }
+
+    /// Reject markup longer than `max` bytes, before any of it is parsed
+    ///
+    /// Together with [`ParseOptions::max_tags`] and [`ParseOptions::max_depth`] this
+    /// bounds the work done for untrusted markup, exceeding any of them is a
+    /// [`ParsingError::LimitExceeded`](crate::error::ParsingError::LimitExceeded).
+    pub const fn max_len(mut self, max: usize) -> Self {
+        self.max_len = Some(max);
+        self
+    }
+
+    /// Reject markup with more than `max` opening tags, see [`ParseOptions::max_len`]
+    pub const fn max_tags(mut self, max: usize) -> Self {
+        self.max_tags = Some(max);
+        self
+    }
+
+    /// Reject markup nesting tags more than `max` levels deep, see [`ParseOptions::max_len`]
+    pub const fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    /// Escape with `escape` instead of a backslash in markup text
+    ///
+    /// The escape character makes a following `<`, `>` or itself literal, in front of
+    /// anything else it's kept as written. For text full of backslashes like Windows
+    /// paths or regexes, which would otherwise need every backslash doubled. `escape`
+    /// shouldn't be `<`, `>` or a character used in tags.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{markup::Markup, style::ParseOptions};
+    ///
+    /// let opts = ParseOptions::new().escape_char('^');
+    /// let markup = Markup::new_with_options(r"<m b>C:\dir\</> ^<a^> ^^", &opts).unwrap();
+    ///
+    /// assert_eq!(markup.render_plain(), r"C:\dir\ <a> ^");
+    /// ```
+    pub const fn escape_char(mut self, escape: char) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Turn unparsable components of `r,g,b` colors into `0` instead of failing
+    ///
+    /// Empty components (`255,,0`) are `0` either way.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::ParseOptions};
+    ///
+    /// let lenient = ParseOptions::new().lenient_rgb(true);
+    ///
+    /// assert!(Style::new_from_cli_spec("f 25x,,5").is_err());
+    /// assert_eq!(
+    ///     Style::new_from_cli_spec_with("f 25x,,5", &lenient).unwrap(),
+    ///     Style::new().fg_rgb(0, 0, 5)
+    /// );
+    /// ```
+    pub const fn lenient_rgb(mut self, lenient: bool) -> Self {
+        self.lenient_rgb = lenient;
+        self
+    }
+}
+
+/// How markup text shortcodes (`:warning:`) are rendered, see [`caps::set_shortcodes`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Shortcodes {
+    /// Keep shortcodes as written
+    #[default]
+    Off,
+    /// Replace shortcodes with Unicode symbols (`⚠`, `→`)
+    Unicode,
+    /// Replace shortcodes with ASCII fallbacks (`!`, `->`)
+    Ascii,
+    /// Use symbols if the terminal can render them, see [`caps::supports_unicode`]
+    Auto,
+}
+
+/// The core styling builder for creating styled text.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::prelude::*;
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let style_1 = Style::new()
+///     .fg_rgb(0, 255, 255)
+///     .bg_index(198)
+///     .italic()
+///     .overline()
+///     .underline();
+///
+/// let style_2 = Style::new_from_cli_spec("fb r b #ABC m ilobu").unwrap();
+///
+/// assert_eq!(style_1.style("Hello"), "\u{1b}[38;2;0;255;255;48;5;198;3;4;53mHello\u{1b}[0m");
+/// assert_eq!(style_2.style("Hello"), "\u{1b}[91;48;2;170;187;204;1;3;4;21;53mHello\u{1b}[0m");
+/// ```
+///
+/// Modifiers are kept as a set, so their SGR codes come out in a canonical order
+/// rather than in the order they were added: [`Modifier::Reset`] always comes
+/// first, and adding it drops the modifiers added before it.
+///
+/// ```rust
+/// use cli_styler::prelude::*;
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// assert_eq!(Style::new().italic().bold().style("x"), "\u{1b}[1;3mx\u{1b}[0m");
+/// assert_eq!(Style::new().bold().reset().style("x"), "\u{1b}[0mx\u{1b}[0m");
+/// assert_eq!(Style::new().reset().bold().style("x"), "\u{1b}[0;1mx\u{1b}[0m");
+/// ```
+///
+/// With the `serde` feature the modifiers serialize as a list of names in that
+/// same order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+pub struct Style {
+    /// Foreground color & type
+    pub(crate) fg: Option<(Color, ClrType)>,
+    /// Background color & type
+    pub(crate) bg: Option<(Color, ClrType)>,
+    /// Modifiers for the text
+    pub(crate) mdfs: Modifiers,
+    /// Underline shape, replaces the underline modifiers when set
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) ul: Option<UnderlineStyle>,
+    /// Parameters emitted verbatim after everything else
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "RawSgr::is_empty")
+    )]
+    pub(crate) raw: RawSgr,
+}
+
+impl Style {
+    /// Creates a new, empty [`Style`]
+    pub const fn new() -> Self {
+        Self {
+            fg: None,
+            bg: None,
+            mdfs: Modifiers(0),
+            ul: None,
+            raw: RawSgr::EMPTY,
+        }
+    }
+
+    /// Constructs a new [`Style`] from the provided spec
+    pub fn new_from_cli_spec(spec: impl AsRef<str>) -> Result<Self, StylerError> {
+        Self::new_from_cli_spec_with(spec, &ParseOptions::new())
+    }
+
+    /// Constructs a new [`Style`] from the provided spec, parsed with non-default options
+    pub fn new_from_cli_spec_with(
+        spec: impl AsRef<str>,
+        opts: &ParseOptions,
+    ) -> Result<Self, StylerError> {
+        parse_style(spec, &Mk, opts).map_err(StylerError::ParsingError)
+    }
+
+    /// Constructs a new [`Style`] from a spec written in a custom [`Dialect`]
+    pub fn new_from_dialect_spec(
+        spec: impl AsRef<str>,
+        dialect: &dyn Dialect,
+        opts: &ParseOptions,
+    ) -> Result<Self, StylerError> {
+        parse_style(spec, dialect, opts).map_err(StylerError::ParsingError)
+    }
+
+    /// Check a spec for errors and questionable parts without stopping at the first one
+    ///
+    /// Unlike parsing, every problem of the spec is reported, sorted by position. A spec
+    /// without warnings parses to a style without [conflicts](Style::validate).
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::style::{SpecWarning, Style};
+    ///
+    /// assert!(Style::check_spec("f r m bu").is_empty());
+    /// assert_eq!(
+    ///     Style::check_spec("x r fb 99 m bb"),
+    ///     [
+    ///         SpecWarning::UnknownParam("x".to_string(), 0..1),
+    ///         SpecWarning::IneffectiveBright("99".to_string(), 7..9),
+    ///         SpecWarning::RedundantModifier('b', 13..14),
+    ///     ]
+    /// );
+    /// ```
+    pub fn check_spec(spec: impl AsRef<str>) -> Vec<SpecWarning> {
+        Self::check_spec_with(spec, &ParseOptions::new())
+    }
+
+    /// Check a spec parsed with non-default options, see [`Style::check_spec`]
+    pub fn check_spec_with(spec: impl AsRef<str>, opts: &ParseOptions) -> Vec<SpecWarning> {
+        check_style(spec.as_ref(), &Mk, opts)
+    }
+
+    /// Constructs a new [`Style`] from the provided spec, rejecting contradictory attributes
+    ///
+    /// See [`Style::validate`] for the combinations which are rejected.
+    pub fn new_from_cli_spec_strict(spec: impl AsRef<str>) -> Result<Self, StylerError> {
+        Self::new_from_cli_spec(spec)?.strict()
+    }
+
+    /// Set the foreground color (supports indexed and RGB colors)
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = match self.fg {
+            Some((_, ct)) => Some((color, ct)),
+            None => Some((color, ClrType::Fg)),
+        };
+        self
+    }
+
+    /// Set the background color (supports indexed and RGB colors)
+    pub const fn bg(mut self, color: Color) -> Self {
+        self.bg = match self.bg {
+            Some((_, ct)) => Some((color, ct)),
+            None => Some((color, ClrType::Bg)),
+        };
+        self
+    }
+
+    /// Set the foreground color as RGB
+    pub const fn fg_rgb(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.fg = match self.fg {
+            Some((_, ct)) => Some((Color::RGB(r, g, b), ct)),
+            None => Some((Color::RGB(r, g, b), ClrType::Fg)),
+        };
+        self
+    }
+
+    /// Set the background color as RGB
+    pub const fn bg_rgb(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.bg = match self.bg {
+            Some((_, ct)) => Some((Color::RGB(r, g, b), ct)),
+            None => Some((Color::RGB(r, g, b), ClrType::Bg)),
+        };
+        self
+    }
+
+    /// Explicitly revert the foreground to the terminal's default color
+    pub const fn fg_default(self) -> Self {
+        self.fg(Color::TerminalDefault)
+    }
+
+    /// Explicitly revert the background to the terminal's default color
+    pub const fn bg_default(self) -> Self {
+        self.bg(Color::TerminalDefault)
+    }
+
+    /// Set the foreground color as an indexed color
+    pub const fn fg_index(mut self, i: u8) -> Self {
+        self.fg = Some((Color::Indexed(i), ClrType::Fg));
+        self
+    }
+
+    /// Set the background color as an indexed color
+    pub const fn bg_index(mut self, i: u8) -> Self {
+        self.bg = Some((Color::Indexed(i), ClrType::Bg));
+        self
+    }
+
+    /// Brighten the foreground color (for ANSI colors only)
+    pub const fn fg_brighten(mut self) -> Self {
+        self.fg = match self.fg {
+            Some((clr, _)) => Some((clr, ClrType::FgBright)),
+            None => Some((Color::White, ClrType::FgBright)),
+        };
+        self
+    }
+
+    /// Brighten the background color (for ANSI colors only)
+    pub const fn bg_brighten(mut self) -> Self {
+        self.bg = match self.bg {
+            Some((clr, _)) => Some((clr, ClrType::BgBright)),
+            None => Some((Color::White, ClrType::BgBright)),
+        };
+        self
+    }
+
+    /// Apply bold styling
+    pub const fn bold(self) -> Self {
+        self.insert_modifier(Modifier::Bold)
+    }
+
+    /// Apply dim styling
+    pub const fn dim(self) -> Self {
+        self.insert_modifier(Modifier::Dim)
+    }
+
+    /// Apply italic styling
+    pub const fn italic(self) -> Self {
+        self.insert_modifier(Modifier::Italic)
+    }
+
+    /// Apply underline styling
+    pub const fn underline(self) -> Self {
+        self.insert_modifier(Modifier::Underline)
+    }
+
+    /// Apply blink styling
+    pub const fn blink(self) -> Self {
+        self.insert_modifier(Modifier::Blink)
+    }
+
+    /// Apply rapid blink styling
+    pub const fn rapid_blink(self) -> Self {
+        self.insert_modifier(Modifier::RapidBlink)
+    }
+
+    /// Swap foreground and background colors
+    pub const fn invert(self) -> Self {
+        self.insert_modifier(Modifier::Invert)
+    }
+
+    /// Hide text (revealed when selected)
+    pub const fn hide(self) -> Self {
+        self.insert_modifier(Modifier::Hide)
+    }
+
+    /// Apply strikethrough styling
+    pub const fn strike(self) -> Self {
+        self.insert_modifier(Modifier::Strike)
+    }
+
+    /// Apply double underline styling
+    pub const fn double_ul(self) -> Self {
+        self.insert_modifier(Modifier::DoubleUL)
+    }
+
+    /// Apply an underline of the given shape, written as `m u:curly` in specs
+    ///
+    /// It replaces [`Style::underline`] and [`Style::double_ul`], which in turn replace it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::UnderlineStyle};
+    /// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+    ///
+    /// let error = Style::new().fg(Color::Red).underline_style(UnderlineStyle::Curly);
+    ///
+    /// assert_eq!(error.style("typo"), "\u{1b}[31;4:3mtypo\u{1b}[0m");
+    /// assert_eq!(error, Style::new_from_cli_spec("f r m u:curly").unwrap());
+    /// ```
+    pub const fn underline_style(mut self, ul: UnderlineStyle) -> Self {
+        self.mdfs.0 &=
+            !(Modifier::Underline.bit() | Modifier::DoubleUL.bit() | Modifier::NoUnderline.bit());
+        self.ul = Some(ul);
+        self
+    }
+
+    /// Apply overline styling
+    pub const fn overline(self) -> Self {
+        self.insert_modifier(Modifier::Overline)
+    }
+
+    /// Draw a frame around the text
+    pub const fn framed(self) -> Self {
+        self.insert_modifier(Modifier::Framed)
+    }
+
+    /// Draw a circle around the text
+    pub const fn encircled(self) -> Self {
+        self.insert_modifier(Modifier::Encircled)
+    }
+
+    /// Apply superscript styling
+    pub const fn superscript(self) -> Self {
+        self.insert_modifier(Modifier::Superscript)
+    }
+
+    /// Apply subscript styling
+    pub const fn subscript(self) -> Self {
+        self.insert_modifier(Modifier::Subscript)
+    }
+
+    /// Turn bold and dim off (normal intensity)
+    pub const fn no_bold(self) -> Self {
+        self.insert_modifier(Modifier::NoBold)
+    }
+
+    /// Turn italic off
+    pub const fn no_italic(self) -> Self {
+        self.insert_modifier(Modifier::NoItalic)
+    }
+
+    /// Turn single and double underline off
+    pub const fn no_underline(self) -> Self {
+        self.insert_modifier(Modifier::NoUnderline)
+    }
+
+    /// Turn blink off
+    pub const fn no_blink(self) -> Self {
+        self.insert_modifier(Modifier::NoBlink)
+    }
+
+    /// Turn invert off
+    pub const fn no_invert(self) -> Self {
+        self.insert_modifier(Modifier::NoInvert)
+    }
+
+    /// Turn hide off
+    pub const fn no_hide(self) -> Self {
+        self.insert_modifier(Modifier::NoHide)
+    }
+
+    /// Turn strikethrough off
+    pub const fn no_strike(self) -> Self {
+        self.insert_modifier(Modifier::NoStrike)
+    }
+
+    /// Turn overline off
+    pub const fn no_overline(self) -> Self {
+        self.insert_modifier(Modifier::NoOverline)
+    }
+
+    /// Turn framed and encircled off
+    pub const fn no_framed(self) -> Self {
+        self.insert_modifier(Modifier::NoFramed)
+    }
+
+    /// Turn superscript and subscript off
+    pub const fn no_script(self) -> Self {
+        self.insert_modifier(Modifier::NoScript)
+    }
+
+    /// Remove the foreground color, leaving the terminal's current one untouched
+    ///
+    /// Unlike [`Style::fg_default`], which explicitly switches to the default color.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::Modifier};
+    ///
+    /// const BASE: Style = Style::new().fg(Color::Red).bg(Color::Black).bold().italic();
+    ///
+    /// assert_eq!(
+    ///     BASE.clear_fg().without(Modifier::Italic),
+    ///     Style::new().bg(Color::Black).bold()
+    /// );
+    /// assert_eq!(BASE.clear_bg().clear_modifiers(), Style::new().fg(Color::Red));
+    /// ```
+    pub const fn clear_fg(mut self) -> Self {
+        self.fg = None;
+        self
+    }
+
+    /// Remove the background color, leaving the terminal's current one untouched
+    pub const fn clear_bg(mut self) -> Self {
+        self.bg = None;
+        self
+    }
+
+    /// Remove every modifier, including the underline shape
+    pub const fn clear_modifiers(mut self) -> Self {
+        self.mdfs = Modifiers(0);
+        self.ul = None;
+        self
+    }
+
+    /// Remove a single modifier
+    ///
+    /// Removing [`Modifier::Underline`] or [`Modifier::DoubleUL`] also removes the underline shape.
+    pub const fn without(mut self, mdf: Modifier) -> Self {
+        self.mdfs.0 &= !mdf.bit();
+
+        if matches!(mdf, Modifier::Underline | Modifier::DoubleUL) {
+            self.ul = None;
+        }
+        self
+    }
+
+    /// Compile this style into a `CompiledStyle` for efficient reuse
+    pub fn compile(&self) -> CompiledStyle {
+        let params = self.collect();
+        let sgr = OnceLock::new();
+
+        if !params.is_empty() {
+            let _ = sgr.set(csi(&params));
+        }
+
+        CompiledStyle {
+            params: Cow::Owned(params),
+            sgr,
+        }
+    }
+
+    /// Compile this style against a target [`ColorLevel`], see [`Style::adapt`]
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{caps, prelude::*};
+    ///
+    /// let warning = Style::new().fg_rgb(255, 170, 0).bold().compile_for(caps::detect());
+    /// # let _ = warning;
+    /// ```
+    pub fn compile_for(&self, level: ColorLevel) -> CompiledStyle {
+        self.adapt(level).compile()
+    }
+
+    /// Reset all styling
+    pub const fn reset(self) -> Self {
+        self.insert_modifier(Modifier::Reset)
+    }
+
+    /// Checks whether a `Style` has any effect on text or not
+    pub fn is_empty(&self) -> bool {
+        if self.fg.is_some() {
+            return false;
+        }
+
+        if self.bg.is_some() {
+            return false;
+        }
+
+        if !self.mdfs.is_empty() {
+            return false;
+        }
+
+        self.ul.is_none() && self.raw.is_empty()
+    }
+
+    /// Serialize this style back into a canonical spec string
+    ///
+    /// The output re-parses into an equal [`Style`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let style = Style::new().fg_rgb(0xAA, 0xBB, 0xCC).bg(Color::Red).bg_brighten().underline().bold();
+    ///
+    /// assert_eq!(style.to_spec(), "f #aabbcc bb r m bu");
+    /// assert_eq!(Style::new_from_cli_spec(style.to_spec()).unwrap(), style);
+    /// ```
+    pub fn to_spec(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some((clr, ct)) = self.fg {
+            let param = if ct.is_bright() { "fb" } else { "f" };
+            parts.push(format!("{param} {}", clr.to_spec()));
+        }
+
+        if let Some((clr, ct)) = self.bg {
+            let param = if ct.is_bright() { "bb" } else { "b" };
+            parts.push(format!("{param} {}", clr.to_spec()));
+        }
+
+        if !self.mdfs.is_empty() {
+            parts.push(format!(
+                "m {}",
+                self.mdfs.iter().map(Modifier::to_spec).collect::<String>()
+            ));
+        }
+
+        if let Some(ul) = self.ul {
+            parts.push(format!("m u:{}", ul.name()));
+        }
+
+        if !self.raw.is_empty() {
+            let params = self.raw.params().iter().map(u8::to_string);
+            parts.push(format!("m raw:{}", params.collect::<Vec<_>>().join(",")));
+        }
+
+        parts.join(" ")
+    }
+
+    /// Rewrite a spec into its canonical form, the [`to_spec`](Style::to_spec) of its [normalized](Style::normalize) style
+    ///
+    /// Parameters come in the order foreground, background, modifiers, colors are
+    /// lowercase single letters, indices or six digit hex codes, and modifiers are
+    /// deduplicated in canonical order. Specs producing the same output format equally.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// assert_eq!(Style::format_spec("m ub b=#ABC f Red m b").unwrap(), "f r b #aabbcc m bu");
+    /// assert_eq!(Style::format_spec("m ib  fb=G").unwrap(), Style::format_spec("fb g m bi").unwrap());
+    /// assert!(Style::format_spec("f nope").is_err());
+    /// ```
+    pub fn format_spec(spec: impl AsRef<str>) -> Result<String, StylerError> {
+        Ok(Self::new_from_cli_spec(spec)?.normalize().to_spec())
+    }
+
+    /// Attach SGR parameters the crate doesn't model, emitted verbatim after everything else
+    ///
+    /// `params` are `;` or `,` separated numbers (`"60"`, `"58;5;196"`), at most
+    /// eight of them. They replace previously attached raw parameters. In specs the
+    /// same is written as `m raw:58,5,196`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    /// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+    ///
+    /// let style = Style::new().bold().raw_sgr("58;5;196").unwrap();
+    ///
+    /// assert_eq!(style.style("hi"), "\u{1b}[1;58;5;196mhi\u{1b}[0m");
+    /// assert_eq!(style, Style::new_from_cli_spec("m b m raw:58,5,196").unwrap());
+    /// ```
+    pub fn raw_sgr(mut self, params: &str) -> Result<Self, StylerError> {
+        self.raw = RawSgr::parse(params).map_err(StylerError::ParsingError)?;
+        Ok(self)
+    }
+
+    /// Get the raw SGR parameters attached with [`Style::raw_sgr`]
+    pub fn raw_params(&self) -> &[u8] {
+        self.raw.params()
+    }
+
+    /// Get the foreground color, if one is set
+    ///
+    /// Named `fg_color` since [`Style::fg`] is the builder method.
+    pub fn fg_color(&self) -> Option<Color> {
+        self.fg.map(|(clr, _)| clr)
+    }
+
+    /// Get the background color, if one is set
+    ///
+    /// Named `bg_color` since [`Style::bg`] is the builder method.
+    pub fn bg_color(&self) -> Option<Color> {
+        self.bg.map(|(clr, _)| clr)
+    }
+
+    /// Iterate over the modifiers applied by this style, in canonical order
+    pub fn modifiers(&self) -> impl Iterator<Item = Modifier> + '_ {
+        self.mdfs.iter()
+    }
+
+    /// Check whether the given modifier is applied by this style
+    pub fn has_modifier(&self, mdf: Modifier) -> bool {
+        self.mdfs.contains(mdf)
+    }
+
+    /// Check whether the foreground color is brightened
+    pub fn is_bright_fg(&self) -> bool {
+        matches!(self.fg, Some((_, ClrType::FgBright)))
+    }
+
+    /// Check whether the background color is brightened
+    pub fn is_bright_bg(&self) -> bool {
+        matches!(self.bg, Some((_, ClrType::BgBright)))
+    }
+
+    /// Describe this style as inline CSS declarations (`color:#cd0000;font-weight:bold`)
+    ///
+    /// Colors resolve through the default xterm palette like [`Color::to_rgb`], bright
+    /// variants included, and [invert](Modifier::Invert) swaps them. Attributes without
+    /// a CSS equivalent (blinking, "off" modifiers, raw SGR parameters) are left out.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::style::Style;
+    ///
+    /// let style = Style::new_from_cli_spec("f R m is m u:curly").unwrap();
+    ///
+    /// assert_eq!(
+    ///     style.to_css(),
+    ///     "color:#ff0000;font-style:italic;text-decoration:underline line-through;text-decoration-style:wavy"
+    /// );
+    /// ```
+    pub fn to_css(&self) -> String {
+        let css_color = |(clr, ct): (Color, ClrType)| {
+            let (r, g, b) = match clr {
+                TerminalDefault => return None,
+                clr if clr.is_color() && ct.is_bright() => {
+                    ANSI_PALETTE[clr.ansi_index() as usize + 8]
+                }
+                clr => clr.to_rgb(),
+            };
+
+            Some(match clr.alpha() {
+                255 => format!("#{r:02x}{g:02x}{b:02x}"),
+                a => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+            })
+        };
+
+        let (mut fg, mut bg) = (self.fg.and_then(css_color), self.bg.and_then(css_color));
+        if self.has_modifier(Invert) {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+
+        let mut decls = Vec::new();
+        decls.extend(fg.map(|fg| format!("color:{fg}")));
+        decls.extend(bg.map(|bg| format!("background-color:{bg}")));
+
+        for (mdf, decl) in [
+            (Bold, "font-weight:bold"),
+            (Dim, "opacity:0.5"),
+            (Italic, "font-style:italic"),
+            (Hide, "visibility:hidden"),
+            (Superscript, "vertical-align:super"),
+            (Subscript, "vertical-align:sub"),
+            (Framed, "border:1px solid"),
+            (Encircled, "border:1px solid;border-radius:50%"),
+        ] {
+            if self.has_modifier(mdf) {
+                decls.push(decl.to_string());
+            }
+        }
+
+        let underlined =
+            self.has_modifier(Underline) || self.has_modifier(DoubleUL) || self.ul.is_some();
+        let lines = [
+            (underlined, "underline"),
+            (self.has_modifier(Strike), "line-through"),
+            (self.has_modifier(Overline), "overline"),
+        ]
+        .into_iter()
+        .filter_map(|(set, line)| set.then_some(line))
+        .collect::<Vec<_>>();
+
+        if !lines.is_empty() {
+            decls.push(format!("text-decoration:{}", lines.join(" ")));
+        }
+
+        let shape = match self.ul {
+            Some(UnderlineStyle::Double) => Some("double"),
+            Some(UnderlineStyle::Curly) => Some("wavy"),
+            Some(UnderlineStyle::Dotted) => Some("dotted"),
+            Some(UnderlineStyle::Dashed) => Some("dashed"),
+            Some(UnderlineStyle::Single) => None,
+            None => self.has_modifier(DoubleUL).then_some("double"),
+        };
+
+        decls.extend(shape.map(|shape| format!("text-decoration-style:{shape}")));
+        decls.join(";")
+    }
+
+    /// Rewrite the colors of this style to the nearest representation supported by `level`
+    ///
+    /// [`ColorLevel::None`] drops both colors while keeping the modifiers.
+    pub fn adapt(&self, level: ColorLevel) -> Style {
+        let adapt = |slot: Option<(Color, ClrType)>| {
+            slot.and_then(|(clr, ct)| match level {
+                ColorLevel::None => None,
+                ColorLevel::Ansi16 if !clr.is_color() && clr != TerminalDefault => {
+                    let (clr, bright) = clr.quantize_to_16();
+                    Some((clr, ct.with_bright(bright)))
+                }
+                ColorLevel::Ansi256 if clr.is_rgb() => Some((clr.quantize_to_256(), ct)),
+                _ => Some((clr, ct)),
+            })
+        };
+
+        Style {
+            fg: adapt(self.fg),
+            bg: adapt(self.bg),
+            ..*self
+        }
+    }
+
+    /// Adjust the foreground so its WCAG contrast against the background is at least `min_ratio`
+    ///
+    /// The foreground is shifted towards black or white (whichever reads better on the
+    /// background) in small steps. Styles missing either color are returned unchanged.
+    pub fn ensure_contrast(&self, min_ratio: f32) -> Style {
+        let (Some((fg, ct)), Some((bg, _))) = (self.fg, self.bg) else {
+            return *self;
+        };
+
+        if fg.contrast_ratio(bg) >= min_ratio {
+            return *self;
+        }
+
+        let target = bg.contrast_fg();
+        let adjusted = (1..=20)
+            .map(|step| fg.mix(target, step as f32 / 20.0))
+            .find(|clr| clr.contrast_ratio(bg) >= min_ratio)
+            .unwrap_or(target);
+
+        Style {
+            fg: Some((adjusted, ct)),
+            ..*self
+        }
+    }
+
+    /// Interpolate between this style and `other`, e.g. for pulsing or fading text
+    ///
+    /// Colors set in both styles are mixed in RGB (bright basic colors use their
+    /// bright palette entry). Everything else, including a color set in only one of
+    /// the styles, snaps from `self` to `other` at `t = 0.5`. `t` is clamped to
+    /// `0.0..=1.0`, the ends return the styles unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let dim = Style::new().fg_rgb(0, 0, 0);
+    /// let lit = Style::new().fg_rgb(200, 100, 0).bold();
+    ///
+    /// assert_eq!(dim.lerp(&lit, 0.25), Style::new().fg_rgb(50, 25, 0));
+    /// assert_eq!(dim.lerp(&lit, 0.75), Style::new().fg_rgb(150, 75, 0).bold());
+    /// ```
+    pub fn lerp(&self, other: &Style, t: f32) -> Style {
+        if t <= 0.0 {
+            return *self;
+        }
+
+        if t >= 1.0 {
+            return *other;
+        }
+
+        let rgb = |(clr, ct): (Color, ClrType)| match clr {
+            clr if clr.is_color() && ct.is_bright() => Indexed(clr.ansi_index() + 8),
+            clr => clr,
+        };
+        let mix = |from, to| match (from, to) {
+            (Some(a), Some(b)) => Some((rgb(a).mix(rgb(b), t), a.1.with_bright(false))),
+            _ if t < 0.5 => from,
+            _ => to,
+        };
+
+        Style {
+            fg: mix(self.fg, other.fg),
+            bg: mix(self.bg, other.bg),
+            ..if t < 0.5 { *self } else { *other }
+        }
+    }
+
+    /// Get the escape sequence switching from this style to `next` with as few codes as possible
+    ///
+    /// Removed attributes use their targeted "off" codes (`22`, `23`, `24`, `39`, `49`, ...)
+    /// instead of a full reset. Returns an empty string when both styles are equal.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let current = Style::new().fg(Color::Red).bold().italic();
+    /// let next = Style::new().fg(Color::Red).italic().underline();
+    ///
+    /// assert_eq!(current.transition_to(&next), "\u{1b}[22;4m");
+    /// assert_eq!(next.transition_to(&Style::new()), "\u{1b}[39;23;24m");
+    /// ```
+    pub fn transition_to(&self, next: &Style) -> String {
+        if self == next {
+            return String::new();
+        }
+
+        // `Reset` is emitted first and clears everything anyway
+        if next.mdfs.contains(Modifier::Reset) {
+            return csi(&next.collect());
+        }
+
+        // Raw parameters have no known "off" codes, so they can only be cleared by a reset
+        if self.raw != next.raw && !self.raw.is_empty() {
+            let params = next.collect();
+
+            return if params.is_empty() {
+                csi("0")
+            } else {
+                csi(&format!("0;{params}"))
+            };
+        }
+
+        let mut codes = Vec::new();
+        let mut write_color =
+            |from: Option<(Color, ClrType)>, to: Option<(Color, ClrType)>, off| {
+                if from != to {
+                    let mut code = String::new();
+                    match to {
+                        // Writing into a `String` never fails
+                        Some((clr, ct)) => _ = clr.write_codes(ct, &mut code),
+                        None => code.push_str(off),
+                    }
+                    codes.push(code);
+                }
+            };
+
+        write_color(self.fg, next.fg, "39");
+        write_color(self.bg, next.bg, "49");
+
+        let mut offs = Vec::new();
+        let mut current = self.mdfs;
+
+        for mdf in self.mdfs.iter().filter(|&mdf| !next.mdfs.contains(mdf)) {
+            let Some(off) = mdf.off_code() else {
+                continue;
+            };
+
+            if !offs.contains(&off) {
+                offs.push(off);
+            }
+
+            // Shared off codes clear their siblings as well
+            for sibling in Modifier::ALL {
+                if sibling.off_code() == Some(off) {
+                    current.remove(sibling);
+                }
+            }
+        }
+
+        let underlined = next.ul.is_some()
+            || next.mdfs.contains(Modifier::Underline)
+            || next.mdfs.contains(Modifier::DoubleUL);
+        let ul_off = Modifier::NoUnderline as u8;
+        if self.ul.is_some() && !underlined && !offs.contains(&ul_off) {
+            offs.push(ul_off);
+        }
+
+        codes.extend(offs.iter().map(u8::to_string));
+        codes.extend(
+            next.mdfs
+                .iter()
+                .filter(|&mdf| !current.contains(mdf))
+                .map(|mdf| (mdf as u8).to_string()),
+        );
+
+        if let Some(ul) = next.ul
+            && self.ul != next.ul
+        {
+            codes.push(format!("4:{}", ul as u8));
+        }
+
+        if self.raw != next.raw {
+            codes.extend(next.raw.params().iter().map(u8::to_string));
+        }
+
+        if codes.is_empty() {
+            String::new()
+        } else {
+            csi(&codes.join(";"))
+        }
+    }
+
+    /// Layer `other` on top of this style, returning the combined [`Style`]
+    ///
+    /// Colors set in `other` take precedence, while modifiers of both styles are unioned
+    /// with those of `other` overriding conflicting ones (`no_bold` over `bold`, `underline`
+    /// over an [`UnderlineStyle`]).
+    pub fn merge(&self, other: &Style) -> Style {
+        let mut merged = Style {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            raw: if other.raw.is_empty() {
+                self.raw
+            } else {
+                other.raw
+            },
+            ..*self
+        };
+
+        // Added one by one, so the underline modifiers and shapes replace each other
+        for mdf in other.mdfs.iter() {
+            merged = merged.insert_modifier(mdf);
+        }
+
+        match other.ul {
+            Some(ul) => merged.underline_style(ul),
+            None => merged,
+        }
+    }
+
+    /// Internal helper to add a modifier
+    pub(crate) const fn insert_modifier(mut self, mdf: Modifier) -> Self {
+        if matches!(
+            mdf,
+            Modifier::Reset | Modifier::Underline | Modifier::DoubleUL | Modifier::NoUnderline
+        ) {
+            self.ul = None;
+        }
+
+        self.mdfs.insert(mdf);
+        self
+    }
+
+    /// Apply the parameters of an SGR sequence (`1;38;5;99`) on top of this style
+    pub(crate) fn apply_sgr(&mut self, params: &str) {
+        let mut params = params.split(';');
+
+        while let Some(param) = params.next() {
+            if param.contains(':') {
+                self.apply_sub_params(param);
+                continue;
+            }
+
+            // An empty parameter means `0`, unparsable ones are skipped
+            let code = match param {
+                "" => 0,
+                param => match param.parse::<u8>() {
+                    Ok(code) => code,
+                    Err(_) => continue,
+                },
+            };
+
+            match code {
+                0 => *self = Style::new(),
+                1 => self.mdfs.insert(Modifier::Bold),
+                2 => self.mdfs.insert(Modifier::Dim),
+                3 => self.mdfs.insert(Modifier::Italic),
+                4 => *self = self.underline(),
+                5 => self.mdfs.insert(Modifier::Blink),
+                6 => self.mdfs.insert(Modifier::RapidBlink),
+                7 => self.mdfs.insert(Modifier::Invert),
+                8 => self.mdfs.insert(Modifier::Hide),
+                9 => self.mdfs.insert(Modifier::Strike),
+                21 => *self = self.double_ul(),
+                51 => self.mdfs.insert(Modifier::Framed),
+                52 => self.mdfs.insert(Modifier::Encircled),
+                53 => self.mdfs.insert(Modifier::Overline),
+                73 => self.mdfs.insert(Modifier::Superscript),
+                74 => self.mdfs.insert(Modifier::Subscript),
+                22..=29 | 54 | 55 | 75 => {
+                    if code == Modifier::NoUnderline as u8 {
+                        self.ul = None;
+                    }
+
+                    for mdf in Modifier::ALL {
+                        if mdf.off_code() == Some(code) {
+                            self.mdfs.remove(mdf);
+                        }
+                    }
+                }
+                30..=37 => self.fg = Some((Color::from_ansi_index(code - 30), ClrType::Fg)),
+                90..=97 => self.fg = Some((Color::from_ansi_index(code - 90), ClrType::FgBright)),
+                40..=47 => self.bg = Some((Color::from_ansi_index(code - 40), ClrType::Bg)),
+                100..=107 => {
+                    self.bg = Some((Color::from_ansi_index(code - 100), ClrType::BgBright))
+                }
+                39 => self.fg = None,
+                49 => self.bg = None,
+                38 | 48 => {
+                    let mut next = || params.next().and_then(|code| code.parse::<u8>().ok());
+
+                    let color = match next() {
+                        Some(5) => next().map(Color::Indexed),
+                        Some(2) => match (next(), next(), next()) {
+                            (Some(r), Some(g), Some(b)) => Some(Color::RGB(r, g, b)),
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+
+                    if let Some(color) = color {
+                        if code == 38 {
+                            self.fg = Some((color, ClrType::Fg));
+                        } else {
+                            self.bg = Some((color, ClrType::Bg));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Apply a colon separated SGR parameter (`38:5:99`, `48:2::255:0:0`)
+    fn apply_sub_params(&mut self, param: &str) {
+        let parts = param
+            .split(':')
+            .map(|part| part.parse::<u8>().ok())
+            .collect::<Vec<_>>();
+
+        // `4:0` removes the underline like `24`
+        if let [Some(4), Some(sub)] = parts.as_slice() {
+            match UnderlineStyle::from_sub_param(*sub) {
+                Some(ul) => *self = self.underline_style(ul),
+                None => {
+                    self.ul = None;
+                    self.mdfs.remove(Modifier::Underline);
+                    self.mdfs.remove(Modifier::DoubleUL);
+                }
+            }
+            return;
+        }
+
+        let color = match parts.as_slice() {
+            [_, Some(5), Some(i)] => Color::Indexed(*i),
+            // The color space id is optional
+            [_, Some(2), _, Some(r), Some(g), Some(b)]
+            | [_, Some(2), Some(r), Some(g), Some(b)] => Color::RGB(*r, *g, *b),
+            _ => return,
+        };
+
+        match parts[0] {
+            Some(38) => self.fg = Some((color, ClrType::Fg)),
+            Some(48) => self.bg = Some((color, ClrType::Bg)),
+            _ => {}
+        }
+    }
+
+    /// Get the canonical form of this style, the one its SGR parameters are generated from
+    ///
+    /// Duplicate and overridden modifiers are already dropped when they are added,
+    /// on top of that a [`Modifier::Reset`] makes "off" modifiers and default colors
+    /// redundant. Two styles producing the same output normalize to equal styles.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let style = Style::new().reset().no_bold().fg_default().underline();
+    ///
+    /// assert_eq!(style.normalize(), Style::new().reset().underline());
+    /// assert_eq!(Style::new().bold().bold(), Style::new().bold());
+    /// ```
+    pub fn normalize(&self) -> Style {
+        let mut style = *self;
+
+        if style.mdfs.contains(Modifier::Reset) {
+            for mdf in Modifier::ALL.into_iter().filter(|mdf| mdf.is_off()) {
+                style.mdfs.remove(mdf);
+            }
+
+            for clr in [&mut style.fg, &mut style.bg] {
+                if matches!(clr, Some((Color::TerminalDefault, _))) {
+                    *clr = None;
+                }
+            }
+        }
+
+        style
+    }
+
+    /// Check the style for contradictory attribute combinations
+    ///
+    /// Such styles are still emitted as requested, but usually don't look the way
+    /// they were meant to. Warnings are returned in the order of [`StyleWarning`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::{prelude::*, style::StyleWarning};
+    ///
+    /// assert!(Style::new().bold().fg(Color::Red).validate().is_empty());
+    /// assert_eq!(Style::new().bold().dim().validate(), [StyleWarning::BoldDim]);
+    /// ```
+    pub fn validate(&self) -> Vec<StyleWarning> {
+        let style = self.normalize();
+        let has = |mdf| style.mdfs.contains(mdf);
+        let others = |mdf| {
+            style.fg.is_some()
+                || style.bg.is_some()
+                || style.ul.is_some()
+                || style.mdfs.iter().any(|other| other != mdf)
+        };
+
+        let mut warnings = Vec::new();
+
+        if has(Modifier::Bold) && has(Modifier::Dim) {
+            warnings.push(StyleWarning::BoldDim);
+        }
+
+        if has(Modifier::Underline) && has(Modifier::DoubleUL) {
+            warnings.push(StyleWarning::UnderlineDoubleUnderline);
+        }
+
+        if has(Modifier::Hide) && others(Modifier::Hide) {
+            warnings.push(StyleWarning::HiddenWithOthers);
+        }
+
+        if has(Modifier::Reset) && others(Modifier::Reset) {
+            warnings.push(StyleWarning::ResetWithOthers);
+        }
+
+        if let (Some((fg, fg_ct)), Some((bg, bg_ct))) = (style.fg, style.bg)
+            && fg == bg
+            && fg != Color::TerminalDefault
+            && fg_ct.is_bright() == bg_ct.is_bright()
+        {
+            warnings.push(StyleWarning::SameColors);
+        }
+
+        warnings
+    }
+
+    /// Reject the style if [`Style::validate`] reports any warning
+    ///
+    /// Meant to end a builder chain when contradictory styles should be an error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// assert!(Style::new().bold().strict().is_ok());
+    /// assert!(Style::new().hide().underline().strict().is_err());
+    /// ```
+    pub fn strict(self) -> Result<Self, StylerError> {
+        match self.validate().first() {
+            Some(&warning) => Err(StylerError::ConflictingStyle(warning)),
+            None => Ok(self),
+        }
+    }
+
+    /// Write the `;`-separated SGR parameters of this style without allocating
+    ///
+    /// A [`Modifier::Reset`] comes first so it never cancels the colors of the same style.
+    pub(crate) fn write_codes(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        let style = self.normalize();
+        let mut sep = "";
+
+        if style.mdfs.contains(Modifier::Reset) {
+            write!(w, "{}", Modifier::Reset as u8)?;
+            sep = ";";
+        }
+
+        if let Some((fgclr, ct)) = style.fg {
+            w.write_str(sep)?;
+            fgclr.write_codes(ct, w)?;
+            sep = ";";
+        }
+
+        if let Some((bgclr, ct)) = style.bg {
+            w.write_str(sep)?;
+            bgclr.write_codes(ct, w)?;
+            sep = ";";
+        }
+
+        for mdf in style.mdfs.iter().filter(|&mdf| mdf != Modifier::Reset) {
+            write!(w, "{sep}{}", mdf as u8)?;
+            sep = ";";
+        }
+
+        if let Some(ul) = style.ul {
+            write!(w, "{sep}4:{}", ul as u8)?;
+            sep = ";";
+        }
+
+        for param in style.raw.params() {
+            write!(w, "{sep}{param}")?;
+            sep = ";";
+        }
+
+        Ok(())
+    }
+
+    /// Collect all modifiers into an ANSI escape sequence string
+    pub(crate) fn collect(&self) -> String {
+        let mut codes = String::new();
+        // Writing into a `String` never fails
+        let _ = self.write_codes(&mut codes);
+        codes
+    }
+
+    /// Write `text` with this style applied directly into an [`io::Write`]
+    pub fn write_styled<W: io::Write>(&self, w: &mut W, text: &str) -> io::Result<()> {
+        write!(w, "{}", self.paint(text))
+    }
+
+    /// Write `text` with this style applied directly into a [`fmt::Write`]
+    pub fn write_styled_fmt<W: fmt::Write>(&self, w: &mut W, text: &str) -> fmt::Result {
+        write!(w, "{}", self.paint(text))
+    }
+
+    /// Wrap `text` in a [`Styled`] adapter which formats without allocating
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::prelude::*;
+    ///
+    /// let style = Style::new().fg(Color::Red).bold();
+    ///
+    /// assert_eq!(format!("{}", style.paint("Hi")), style.style("Hi"));
+    /// ```
+    pub fn paint<'a>(&'a self, text: &'a str) -> Styled<'a> {
+        Styled {
+            style: Cow::Borrowed(self),
+            text,
+        }
+    }
+}
+
+impl BitOr for Style {
+    type Output = Style;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.merge(&rhs)
+    }
+}
+
+impl BitOr<&Style> for &Style {
+    type Output = Style;
+
+    fn bitor(self, rhs: &Style) -> Self::Output {
+        self.merge(rhs)
+    }
+}
+
+impl FromStr for Style {
+    type Err = StylerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new_from_cli_spec(s)
+    }
+}
+
+impl TryFrom<&str> for Style {
+    type Error = StylerError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Collects modifiers into a style, later modifiers override earlier ones like [`Style::merge`]
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, style::Modifier};
+///
+/// let emphasis: Style = "biu".chars().filter_map(Modifier::from_char).collect();
+///
+/// assert_eq!(emphasis, Style::new().bold().italic().underline());
+/// ```
+impl FromIterator<Modifier> for Style {
+    fn from_iter<T: IntoIterator<Item = Modifier>>(iter: T) -> Self {
+        let mut style = Style::new();
+        style.extend(iter);
+        style
+    }
+}
+
+impl Extend<Modifier> for Style {
+    fn extend<T: IntoIterator<Item = Modifier>>(&mut self, iter: T) {
+        for mdf in iter {
+            *self = self.insert_modifier(mdf);
+        }
+    }
+}
+
+/// Collects colors into a style, the last color of each [`ClrTarget`] wins
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, style::ClrTarget};
+///
+/// let style: Style = [(Color::Red, ClrTarget::Fg), (Color::Black, ClrTarget::Bg)]
+///     .into_iter()
+///     .collect();
+///
+/// assert_eq!(style, Style::new().fg(Color::Red).bg(Color::Black));
+/// ```
+impl FromIterator<(Color, ClrTarget)> for Style {
+    fn from_iter<T: IntoIterator<Item = (Color, ClrTarget)>>(iter: T) -> Self {
+        let mut style = Style::new();
+        style.extend(iter);
+        style
+    }
+}
+
+impl Extend<(Color, ClrTarget)> for Style {
+    fn extend<T: IntoIterator<Item = (Color, ClrTarget)>>(&mut self, iter: T) {
+        for (color, target) in iter {
+            *self = match target {
+                ClrTarget::Fg => self.fg(color),
+                ClrTarget::Bg => self.bg(color),
+            };
+        }
+    }
+}
+
+/// Display adapter pairing a [`Style`] with borrowed text
+///
+/// The escape sequence, text and reset are written straight into the formatter,
+/// so `println!("{}", style.paint(msg))` builds no intermediate `String`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Styled<'a> {
+    /// Style applied to the text
+    pub(crate) style: Cow<'a, Style>,
+    /// Text to be styled
+    pub(crate) text: &'a str,
+}
+
+impl<'a> Styled<'a> {
+    /// Set the foreground color
+    pub fn fg(self, color: Color) -> Self {
+        self.map(|st| st.fg(color))
+    }
+
+    /// Set the background color
+    pub fn on(self, color: Color) -> Self {
+        self.map(|st| st.bg(color))
+    }
+
+    /// Replace the style with the provided one
+    pub fn with_style(self, style: Style) -> Self {
+        Self {
+            style: Cow::Owned(style),
+            text: self.text,
+        }
+    }
+
+    /// Get the style applied to the text
+    pub fn get_style(&self) -> &Style {
+        &self.style
+    }
+
+    /// Get the text being styled
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// Internal helper to transform the style with a builder method
+    fn map(self, f: impl FnOnce(Style) -> Style) -> Self {
+        Self {
+            style: Cow::Owned(f(self.style.into_owned())),
+            text: self.text,
+        }
+    }
+}
+
+impl fmt::Display for Styled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.text.is_empty() || self.style.is_empty() || !caps::should_colorize() {
+            return f.write_str(self.text);
+        }
+
+        write!(f, "{ESC}[")?;
+        self.style.write_codes(f)?;
+        write!(f, "m{}{RESET}", self.text)
+    }
+}
+
+/// A pre-compiled style for efficient repeated use.
+///
+/// Use this for global styles that won't change after initialization.
+///
+/// # Example
+/// ```rust
+/// use std::sync::LazyLock;
+/// use cli_styler::style::{Color, CompiledStyle, Style, Stylable};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// static WARNING: LazyLock<CompiledStyle> = LazyLock::new(|| {
+///     Style::new()
+///         .fg(Color::Red)
+///         .fg_brighten()
+///         .bg(Color::Yellow)
+///         .bg_brighten()
+///         .compile()
+/// });
+///
+/// static ERROR: LazyLock<CompiledStyle> = LazyLock::new(|| {
+///     CompiledStyle::new_from_cli_spec("f #fff b 255,,").unwrap()
+/// });
+///
+/// assert_eq!(WARNING.style("Warning!!"), "\u{1b}[91;103mWarning!!\u{1b}[0m");
+/// assert_eq!(ERROR.style("Error!!"), "\u{1b}[38;2;255;255;255;48;2;255;0;0mError!!\u{1b}[0m");
+/// ```
+///
+/// Styles built without parsing don't need a `LazyLock` at all:
+/// ```rust
+/// use cli_styler::style::{Color, CompiledStyle, Style, Stylable};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// const NOTE: Style = Style::new().fg(Color::Cyan).fg_brighten().bold();
+/// static OK: CompiledStyle = CompiledStyle::from_static("32;1");
+///
+/// assert_eq!(NOTE.style("note"), "\u{1b}[96;1mnote\u{1b}[0m");
+/// assert_eq!(OK, Style::new().fg(Color::Green).bold().compile());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompiledStyle {
+    /// SGR parameters (`"31;1"`), empty for the empty style
+    params: Cow<'static, str>,
+    /// Complete escape sequence opening the style, built from `params` on first use
+    sgr: OnceLock<String>,
+}
+
+// Whether `sgr` is built yet doesn't matter, only the parameters are compared
+impl PartialEq for CompiledStyle {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params
+    }
+}
+
+impl Eq for CompiledStyle {}
+
+impl PartialOrd for CompiledStyle {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CompiledStyle {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.params.cmp(&other.params)
+    }
+}
+
+impl Hash for CompiledStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.params.hash(state);
+    }
+}
+
+/// Serialized as its SGR parameters
+#[cfg(feature = "serde")]
+impl Serialize for CompiledStyle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.params)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CompiledStyle {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|params| Self::from_params(Cow::Owned(params)))
+    }
+}
+
+impl CompiledStyle {
+    /// Constructs a new [`CompiledStyle`] from a [`Style`]
+    pub fn new(style: Style) -> Self {
+        style.compile()
+    }
+
+    /// Constructs a new [`CompiledStyle`] from the provided spec
+    pub fn new_from_cli_spec(spec: impl AsRef<str>) -> Result<Self, StylerError> {
+        Style::new_from_cli_spec(spec).map(|st| st.compile())
+    }
+
+    /// Wraps already compiled SGR parameters (`"31;1"`), usable in `const` context
+    ///
+    /// The parameters aren't validated, prefer the `style!` macro or a `const` [`Style`] for checked styles.
+    pub const fn from_static(params: &'static str) -> Self {
+        Self::from_params(Cow::Borrowed(params))
+    }
+
+    /// Wraps SGR parameters, the escape sequence is built on first use
+    const fn from_params(params: Cow<'static, str>) -> Self {
+        Self {
+            params,
+            sgr: OnceLock::new(),
+        }
+    }
+
+    /// Get the complete escape sequence opening this style, ignoring `cli_styler::set_enabled`
+    fn sgr(&self) -> &str {
+        if self.params.is_empty() {
+            ""
+        } else {
+            self.sgr.get_or_init(|| csi(&self.params))
+        }
+    }
+
+    /// Get the escape sequence opening this style
+    ///
+    /// Empty for the empty style and while styling is disabled, see `cli_styler::set_enabled`.
+    /// Together with [`CompiledStyle::suffix`] this allows styling output written in many chunks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::style::{Color, Style};
+    /// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+    ///
+    /// let style = Style::new().fg(Color::Green).compile();
+    ///
+    /// let mut bar = style.prefix().to_string();
+    /// for _ in 0..3 {
+    ///     bar.push('#');
+    /// }
+    /// bar.push_str(style.suffix());
+    ///
+    /// assert_eq!(bar, "\u{1b}[32m###\u{1b}[0m");
+    /// ```
+    pub fn prefix(&self) -> &str {
+        if caps::should_colorize() {
+            self.sgr()
+        } else {
+            ""
+        }
+    }
+
+    /// Get the reset sequence closing this style, empty whenever [`CompiledStyle::prefix`] is
+    pub fn suffix(&self) -> &str {
+        if self.params.is_empty() || !caps::should_colorize() {
+            ""
+        } else {
+            RESET
+        }
+    }
+
+    /// Write `text` with this style applied directly into an [`io::Write`]
+    pub fn write_styled<W: io::Write>(&self, w: &mut W, text: &str) -> io::Result<()> {
+        if text.is_empty() || self.params.is_empty() || !caps::should_colorize() {
+            return w.write_all(text.as_bytes());
+        }
+
+        w.write_all(self.sgr().as_bytes())?;
+        w.write_all(text.as_bytes())?;
+        w.write_all(RESET.as_bytes())
+    }
+
+    /// Write `text` with this style applied directly into a [`fmt::Write`]
+    pub fn write_styled_fmt<W: fmt::Write>(&self, w: &mut W, text: &str) -> fmt::Result {
+        if text.is_empty() || self.params.is_empty() || !caps::should_colorize() {
+            return w.write_str(text);
+        }
+
+        w.write_str(self.sgr())?;
+        w.write_str(text)?;
+        w.write_str(RESET)
+    }
+}
+
+impl FromStr for CompiledStyle {
+    type Err = StylerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new_from_cli_spec(s)
+    }
+}
+
+impl TryFrom<&str> for CompiledStyle {
+    type Error = StylerError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Bundles the `text` and the `spec` (style) together
+#[derive(Default)]
+pub struct Part {
+    /// text to which the provided spec would be applied to
+    pub text: String,
+    /// spec which would be applied to the provided text
+    pub spec: String,
+}
+
+#[cfg(feature = "cli")]
+impl Part {
+    /// Parses CLI spec
+    fn style(&self) -> Result<String, StylerError> {
+        Style::new_from_cli_spec(&self.spec).map(|st| st.style(&self.text))
+    }
+}
+
+/// A builder for creating complex styled text with multiple segments
+#[derive(Default)]
+#[allow(clippy::missing_docs_in_private_items)]
+#[cfg(feature = "cli")]
+pub struct BatchStyler {
+    parts: Vec<Part>,
+}
+
+#[cfg(feature = "cli")]
+impl BatchStyler {
+    /// Create a new BatchStyler
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert `text` and `specs` to the current instance
+    pub fn push(mut self, text: impl ToString, spec: impl ToString) -> Self {
+        self.parts.push(Part {
+            text: text.to_string(),
+            spec: spec.to_string(),
+        });
+
+        self
+    }
+
+    /// Collects all parts of the Styler and returns a string
+    pub fn build(self) -> Result<String, StylerError> {
+        self.collect().map(|v| v.concat())
+    }
+
+    /// Collects all parts of the Styler and returns a string with the provided separator
+    pub fn build_with_separator(self, sep: impl AsRef<str>) -> Result<String, StylerError> {
+        self.collect().map(|v| v.join(sep.as_ref()))
+    }
+
+    /// Collect and merge the input into the final output
+    fn collect(self) -> Result<Vec<String>, StylerError> {
+        self.parts
+            .into_iter()
+            .enumerate()
+            .map(|(i, part)| {
+                part.style()
+                    .map_err(|err| StylerError::BatchError(i, Box::new(err)))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    }
+}
@@ -0,0 +1,276 @@
+//! Themes mapping semantic names (`error`, `warning`, `path`, ...) to styles
+
+use std::collections::BTreeMap;
+
+use crate::{
+    error::StylerError,
+    style::{CompiledStyle, Stylable, Style},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+/// A set of named styles
+///
+/// [`Theme::default`] returns the built-in theme, [`Theme::new`] an empty one.
+/// With the `serde` feature a theme (de)serializes as a map of names to specs.
+///
+/// # Example
+/// ```rust
+/// use cli_styler::{prelude::*, theme::Theme};
+/// # cli_styler::caps::set_color_choice(cli_styler::caps::ColorChoice::Always);
+///
+/// let theme = Theme::new()
+///     .with("error", Style::new().fg(Color::Red).bold())
+///     .with_spec("path", "f c m u")
+///     .unwrap();
+///
+/// assert_eq!(theme.style("error", "failed"), "\u{1b}[31;1mfailed\u{1b}[0m");
+/// assert_eq!(theme.style("unknown", "plain"), "plain");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Styles by name
+    styles: BTreeMap<String, Style>,
+}
+
+impl Theme {
+    /// Creates a new, empty [`Theme`]
+    pub fn new() -> Self {
+        Self {
+            styles: BTreeMap::new(),
+        }
+    }
+
+    /// The built-in theme, covering common message kinds
+    pub fn builtin() -> Self {
+        Self::from_specs([
+            ("error", "fb r m b"),
+            ("warning", "fb y m b"),
+            ("info", "fb c"),
+            ("success", "fb g"),
+            ("hint", "f m m i"),
+            ("path", "f c m u"),
+            ("code", "fb w b k"),
+            ("muted", "m d"),
+        ])
+        .expect("built-in theme specs are valid")
+    }
+
+    /// The [built-in theme](Self::builtin) plus entries for log output
+    ///
+    /// Adds the `debug` and `trace` levels, the log `target`, `field` names and the `timestamp`.
+    pub fn logging() -> Self {
+        let mut theme = Self::builtin();
+
+        for (name, spec) in [
+            ("debug", "f b"),
+            ("trace", "m d"),
+            ("target", "m d"),
+            ("field", "m i"),
+            ("timestamp", "m d"),
+        ] {
+            theme
+                .insert_spec(name, spec)
+                .expect("built-in logging specs are valid");
+        }
+
+        theme
+    }
+
+    /// Build a theme from `(name, spec)` pairs
+    ///
+    /// Errors carry the name of the offending entry, see [`StylerError::ThemeEntry`].
+    pub fn from_specs<K, V>(specs: impl IntoIterator<Item = (K, V)>) -> Result<Self, StylerError>
+    where
+        K: Into<String>,
+        V: AsRef<str>,
+    {
+        specs
+            .into_iter()
+            .map(|(name, spec)| {
+                let name = name.into();
+                match Style::new_from_cli_spec(spec) {
+                    Ok(style) => Ok((name, style)),
+                    Err(err) => Err(StylerError::ThemeEntry(name, Box::new(err))),
+                }
+            })
+            .collect()
+    }
+
+    /// Load a theme from a TOML table of specs (`error = "fb r m b"`)
+    ///
+    /// # Example
+    /// ```rust
+    /// use cli_styler::theme::Theme;
+    ///
+    /// let theme = Theme::from_toml_str(r#"
+    ///     error = "fb r m b"
+    ///     path = "f c m u"
+    /// "#).unwrap();
+    ///
+    /// assert!(theme.contains("error"));
+    /// assert!(Theme::from_toml_str(r#"error = "x r""#).is_err());
+    /// ```
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(s: &str) -> Result<Self, StylerError> {
+        let specs = toml::from_str::<BTreeMap<String, String>>(s)
+            .map_err(|err| StylerError::ThemeFormat(err.to_string()))?;
+
+        Self::from_specs(specs)
+    }
+
+    /// Serialize the theme into a TOML table of specs
+    #[cfg(feature = "toml")]
+    pub fn to_toml_string(&self) -> Result<String, StylerError> {
+        toml::to_string(self).map_err(|err| StylerError::ThemeFormat(err.to_string()))
+    }
+
+    /// Load a theme from a JSON object of specs (`{ "error": "fb r m b" }`)
+    #[cfg(feature = "json")]
+    pub fn from_json_str(s: &str) -> Result<Self, StylerError> {
+        let specs = serde_json::from_str::<BTreeMap<String, String>>(s)
+            .map_err(|err| StylerError::ThemeFormat(err.to_string()))?;
+
+        Self::from_specs(specs)
+    }
+
+    /// Serialize the theme into a JSON object of specs
+    #[cfg(feature = "json")]
+    pub fn to_json_string(&self) -> Result<String, StylerError> {
+        serde_json::to_string_pretty(self).map_err(|err| StylerError::ThemeFormat(err.to_string()))
+    }
+
+    /// Add a style, returning the previous style registered under `name`
+    pub fn insert(&mut self, name: impl Into<String>, style: Style) -> Option<Style> {
+        self.styles.insert(name.into(), style)
+    }
+
+    /// Add a style parsed from `spec`, returning the previous style registered under `name`
+    pub fn insert_spec(
+        &mut self,
+        name: impl Into<String>,
+        spec: impl AsRef<str>,
+    ) -> Result<Option<Style>, StylerError> {
+        Ok(self.insert(name, Style::new_from_cli_spec(spec)?))
+    }
+
+    /// Builder variant of [`Theme::insert`]
+    pub fn with(mut self, name: impl Into<String>, style: Style) -> Self {
+        self.insert(name, style);
+        self
+    }
+
+    /// Builder variant of [`Theme::insert_spec`]
+    pub fn with_spec(
+        mut self,
+        name: impl Into<String>,
+        spec: impl AsRef<str>,
+    ) -> Result<Self, StylerError> {
+        self.insert_spec(name, spec)?;
+        Ok(self)
+    }
+
+    /// Remove a style from the theme
+    pub fn remove(&mut self, name: &str) -> Option<Style> {
+        self.styles.remove(name)
+    }
+
+    /// Get the style registered under `name`
+    pub fn get(&self, name: &str) -> Option<&Style> {
+        self.styles.get(name)
+    }
+
+    /// Compile the style registered under `name`
+    pub fn compiled(&self, name: &str) -> Option<CompiledStyle> {
+        self.get(name).map(Style::compile)
+    }
+
+    /// Apply the style registered under `name` to `text`, unknown names leave it unstyled
+    pub fn style(&self, name: &str, text: impl AsRef<str>) -> String {
+        match self.get(name) {
+            Some(style) => style.style(text),
+            None => text.as_ref().to_string(),
+        }
+    }
+
+    /// Apply the style of a log level to `text`, the level given by name (`"WARN"`, `"info"`)
+    ///
+    /// `WARN` uses the `warning` entry and the other levels their lowercase name, like
+    /// `error` or `trace`. This is the mapping used by the `log` and `tracing` formatters.
+    pub fn style_level(&self, level: &str, text: impl AsRef<str>) -> String {
+        let name = level.to_ascii_lowercase();
+
+        match name.as_str() {
+            "warn" => self.style("warning", text),
+            name => self.style(name, text),
+        }
+    }
+
+    /// Check whether a style is registered under `name`
+    pub fn contains(&self, name: &str) -> bool {
+        self.styles.contains_key(name)
+    }
+
+    /// Iterate over the registered styles in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Style)> {
+        self.styles
+            .iter()
+            .map(|(name, style)| (name.as_str(), style))
+    }
+
+    /// Number of registered styles
+    pub fn len(&self) -> usize {
+        self.styles.len()
+    }
+
+    /// Check whether the theme has no styles
+    pub fn is_empty(&self) -> bool {
+        self.styles.is_empty()
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Style)> for Theme {
+    fn from_iter<T: IntoIterator<Item = (K, Style)>>(iter: T) -> Self {
+        Self {
+            styles: iter
+                .into_iter()
+                .map(|(name, style)| (name.into(), style))
+                .collect(),
+        }
+    }
+}
+
+impl<K: Into<String>> Extend<(K, Style)> for Theme {
+    fn extend<T: IntoIterator<Item = (K, Style)>>(&mut self, iter: T) {
+        for (name, style) in iter {
+            self.insert(name, style);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.styles.iter().map(|(name, st)| (name, st.to_spec())))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BTreeMap::<String, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, spec)| match Style::new_from_cli_spec(&spec) {
+                Ok(style) => Ok((name, style)),
+                Err(err) => Err(D::Error::custom(format!("{name}: {err}"))),
+            })
+            .collect()
+    }
+}